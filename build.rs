@@ -0,0 +1,32 @@
+//! Records the versions of the dependencies whose behavior shows up in the
+//! output, so `-vv` can name them in bug reports.
+
+#![allow(clippy::needless_return)]
+
+use std::path::Path;
+
+const PINNED: [(&str, &str); 3] = [("sysinfo", "DEP_VERSION_SYSINFO"), ("wgpu", "DEP_VERSION_WGPU"), ("whoami", "DEP_VERSION_WHOAMI")];
+
+/// The `version` of package `name` in a `Cargo.lock`, if it's listed once.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let mut versions = lock.split("[[package]]").filter_map(|package| {
+        let mut lines = package.lines().map(str::trim);
+        lines.find(|line| *line == format!("name = \"{}\"", name))?;
+        let version = lines.find_map(|line| line.strip_prefix("version = \""))?;
+        return Some(version.trim_end_matches('"').to_string());
+    });
+    let version = versions.next()?;
+    // Two copies in the tree means we can't tell which one we call.
+    return if versions.next().is_none() { Some(version) } else { None };
+}
+
+fn main() {
+    let lock_path = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    // Installs from crates.io build without our lock file.
+    let lock = std::fs::read_to_string(&lock_path).unwrap_or_default();
+    for (name, var) in PINNED {
+        let version = locked_version(&lock, name).unwrap_or_else(|| String::from("unknown"));
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
@@ -0,0 +1,200 @@
+//! Optional on-disk cache of fields that rarely change (`cache = true` or
+//! `--cache`), so frequent runs such as prompts skip their collectors.
+//! Entries expire after a per-field TTL, and all of them on reboot.
+
+use crate::config::CacheTtl;
+use crate::field::Field;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Fields that can be cached, with how long they're kept unless `[cache_ttl]`
+/// says otherwise. Enumerating GPUs is the slowest collector by far; it's
+/// cached for a day, but never with `--gpu-live`.
+pub const DEFAULT_TTLS: [(Field, u64); 6] = [
+    (Field::Os, DAY),
+    (Field::Model, 7 * DAY),
+    (Field::Serial, 7 * DAY),
+    (Field::Chassis, 7 * DAY),
+    (Field::Kernel, DAY),
+    (Field::Gpu, DAY),
+];
+
+/// Seconds the boot time may drift between runs, since it's derived from
+/// the uptime.
+const BOOT_TIME_SLACK: u64 = 5;
+
+#[derive(Default, Deserialize, Serialize)]
+struct CacheFile {
+    boot_time: u64,
+    entries: BTreeMap<String, Entry>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    /// Unix time the value was collected.
+    stored: u64,
+    value: serde_json::Value,
+}
+
+pub struct FieldCache {
+    file: CacheFile,
+    ttl: CacheTtl,
+    now: u64,
+    changed: bool,
+}
+
+pub fn cache_path() -> Option<PathBuf> {
+    return dirs::cache_dir().map(|dir| dir.join("ashwin-fetch").join("fields.json"));
+}
+
+fn unix_now() -> u64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+}
+
+impl FieldCache {
+    /// The cache file, or an empty cache when it's missing, unreadable or
+    /// from before the last boot.
+    pub fn load(ttl: CacheTtl, boot_time: u64) -> FieldCache {
+        let file = cache_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        return FieldCache::from_file(file, ttl, boot_time, unix_now());
+    }
+
+    fn from_file(mut file: CacheFile, ttl: CacheTtl, boot_time: u64, now: u64) -> FieldCache {
+        if file.boot_time.abs_diff(boot_time) > BOOT_TIME_SLACK {
+            file = CacheFile { boot_time, entries: BTreeMap::new() };
+        }
+        return FieldCache { file, ttl, now, changed: false };
+    }
+
+    /// How long `field` is kept; 0 when it isn't cached at all.
+    fn ttl(&self, field: Field) -> u64 {
+        let default = DEFAULT_TTLS.iter().find(|(cached, _)| *cached == field).map_or(0, |(_, ttl)| *ttl);
+        return self.ttl.seconds(field.name()).unwrap_or(default);
+    }
+
+    /// The cached value of `field` while it's fresh.
+    pub fn get<T: DeserializeOwned>(&self, field: Field) -> Option<T> {
+        return self.get_entry(field, field.name().to_string());
+    }
+
+    /// Like `get`, for a value that also depends on `variant`, such as the
+    /// GPU list on the verbosity.
+    pub fn get_variant<T: DeserializeOwned>(&self, field: Field, variant: impl Display) -> Option<T> {
+        return self.get_entry(field, format!("{}@{}", field.name(), variant));
+    }
+
+    fn get_entry<T: DeserializeOwned>(&self, field: Field, key: String) -> Option<T> {
+        let entry = self.file.entries.get(&key)?;
+        if self.now.saturating_sub(entry.stored) >= self.ttl(field) {
+            return None;
+        }
+        return serde_json::from_value(entry.value.clone()).ok();
+    }
+
+    pub fn put<T: Serialize>(&mut self, field: Field, value: &T) {
+        self.put_entry(field, field.name().to_string(), value);
+    }
+
+    pub fn put_variant<T: Serialize>(&mut self, field: Field, variant: impl Display, value: &T) {
+        self.put_entry(field, format!("{}@{}", field.name(), variant), value);
+    }
+
+    fn put_entry<T: Serialize>(&mut self, field: Field, key: String, value: &T) {
+        if self.ttl(field) == 0 {
+            return;
+        }
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+        self.file.entries.insert(key, Entry { stored: self.now, value });
+        self.changed = true;
+    }
+
+    /// Moves the clock entries are stored and expire by to now, for each
+    /// collection of a `--watch` that keeps one cache loaded.
+    pub fn start_run(&mut self) {
+        self.now = unix_now();
+    }
+
+    /// `field`'s cached value, or `collect`'s, which is then cached.
+    pub fn get_or_collect<T: Serialize + DeserializeOwned>(&mut self, field: Field, collect: impl FnOnce() -> T) -> T {
+        if let Some(value) = self.get(field) {
+            return value;
+        }
+        let value = collect();
+        self.put(field, &value);
+        return value;
+    }
+
+    /// Writes the cache back if anything was collected. Failures only cost
+    /// the next run its speed-up, so they're ignored. The file is replaced
+    /// in one go, so a concurrent run never reads half of it.
+    pub fn save(&mut self) {
+        let Some(path) = cache_path().filter(|_| self.changed) else {
+            return;
+        };
+        self.changed = false;
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let Ok(contents) = serde_json::to_string(&self.file) else {
+            return;
+        };
+        let temporary = path.with_extension(format!("json.{}", std::process::id()));
+        if std::fs::write(&temporary, contents).is_err() || std::fs::rename(&temporary, &path).is_err() {
+            let _ = std::fs::remove_file(&temporary);
+        }
+    }
+}
+
+/// `field`'s value through `cache` when there is one.
+pub fn cached<T: Serialize + DeserializeOwned>(cache: Option<&mut FieldCache>, field: Field, collect: impl FnOnce() -> T) -> T {
+    return match cache {
+        Some(cache) => cache.get_or_collect(field, collect),
+        None => collect(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_their_ttl_and_on_reboot() {
+        let ttl = CacheTtl { kernel: Some(60), ..CacheTtl::default() };
+        let mut cache = FieldCache::from_file(CacheFile::default(), ttl, 1000, 5000);
+        cache.put(Field::Kernel, &"Linux 6.8.0");
+        cache.put(Field::Os, &"Arch Linux");
+        cache.put(Field::Cpu, &"never cached");
+        assert_eq!(cache.get::<String>(Field::Kernel).as_deref(), Some("Linux 6.8.0"));
+        assert_eq!(cache.get::<String>(Field::Cpu), None);
+
+        let later = FieldCache::from_file(cache.file, ttl, 1002, 5060);
+        assert_eq!(later.get::<String>(Field::Kernel), None);
+        assert_eq!(later.get::<String>(Field::Os).as_deref(), Some("Arch Linux"));
+
+        let rebooted = FieldCache::from_file(later.file, ttl, 4000, 5061);
+        assert_eq!(rebooted.get::<String>(Field::Os), None);
+    }
+
+    #[test]
+    fn variants_are_cached_apart() {
+        let mut cache = FieldCache::from_file(CacheFile::default(), CacheTtl::default(), 1000, 5000);
+        cache.put_variant(Field::Gpu, 0, &"quiet");
+        assert_eq!(cache.get_variant::<String>(Field::Gpu, 0).as_deref(), Some("quiet"));
+        assert_eq!(cache.get_variant::<String>(Field::Gpu, 1), None);
+        assert_eq!(cache.get::<String>(Field::Gpu), None);
+    }
+}
@@ -0,0 +1,615 @@
+use crate::config::FrequencyUnit;
+use crate::config::HeaderPosition;
+use crate::field;
+use crate::field::Field;
+
+pub const USAGE: &str = "\
+Usage: ashwin-fetch [OPTIONS]
+
+Options:
+  --show-env PATTERN    List environment variables matching PATTERN (e.g. 'XDG_*').
+                        May be given more than once.
+  --fast                Skip slow collectors (CPU usage, temperature, GPUs,
+                        chassis) for prompt/status bar use.
+  --no-refresh          Skip the CPU usage sampling interval but keep every
+                        other field. CPU usage is shown as --%.
+  --cpu-samples N       Average CPU usage over N short intervals (default 3,
+                        capped to stay under a second).
+  --cpu-combined        Show CPUs with different brands (big.LITTLE clusters) on
+                        one line with per-cluster core counts. More than four
+                        brands always share one line, the rest summed up.
+  --cpu-breakdown       Add the user, system and idle shares of CPU time to the
+                        CPU line, e.g. 23.10% avg (18u 5s 77i) (Linux).
+  --frequency-unit UNIT Show CPU clocks in mhz, ghz, or auto (default: GHz from
+                        1000 MHz up).
+  --show-updated        Show the kernel build date and last package update (Linux).
+  --show-updates        Show how many package updates are pending and how many
+                        are security fixes (apt, dnf, pacman; cached for an hour).
+  --kernel-date         Add the running kernel's build date to the Kernel line
+                        (Linux).
+  --check-kernel        Mention when a newer kernel is installed than the one
+                        running, i.e. a reboot is needed (Linux).
+  --show-cmdline        Show the kernel command line, cut to the terminal width
+                        (Linux).
+  --full-cmdline        Show the whole kernel command line, however long;
+                        implies --show-cmdline.
+  --show-threads        Show the thread and zombie process counts (Linux).
+  --show-inodes         Show the used and total inodes on / (Linux, macOS).
+  --show-encryption     Show whether / is encrypted (LUKS, FileVault or
+                        BitLocker; BitLocker needs an elevated prompt).
+  --show-storage-health Show whether periodic SSD TRIM (fstrim.timer) is enabled
+                        and how often it runs (Linux).
+  --show-firmware       Show the boot mode (UEFI or legacy BIOS) and whether
+                        Secure Boot is on (Linux, Windows).
+  --show-entropy        Show the bits in the kernel entropy pool (Linux).
+  --show-session        Show whether this is an SSH, RDP, desktop or console
+                        session and whether the user is root/admin.
+  --show-users          Show how many login sessions there are, and whose with
+                        -v (Linux, macOS).
+  --show-top            Show the processes using the most CPU and memory.
+  --show-fds            Show open file descriptors against the system-wide
+                        limit (Linux).
+  --show-pid            Show the PID and name of the process that started this
+                        one, usually the shell.
+  --show-cache          Show the CPU cache sizes per level, totalled over cores.
+  --show-topology       Show the CPU sockets, NUMA nodes and physical cores
+                        (Linux).
+  --show-virt           Show whether the CPU supports hardware virtualization
+                        (and, on Linux, whether KVM can use it).
+  --show-governor       Show the CPU frequency governor and power profile (Linux).
+  --show-failed-units   Show how many systemd units have failed (Linux).
+  --profile NAME        Apply the [profile.NAME] section of the config on top of
+                        the base settings.
+  --os-name NAME        Show NAME as the OS instead of the detected one.
+  --fields LIST         Only show these fields, e.g. os,cpu,memory (text and
+                        markdown output). Field names are listed in the README.
+  --fields-file PATH    Read the field list from a file, one name per line; blank
+                        lines and # comments are skipped. --fields wins.
+  --show-ip             Show the primary IP address.
+  --ip MODE             Show the primary IPv4 (v4, default) or global IPv6 (v6)
+                        address, or both. Implies --show-ip.
+  --memory-from-proc    Compute used memory as MemTotal - MemAvailable from
+                        /proc/meminfo, like `free -h` (Linux).
+  --show-swap           Show swap usage on a line of its own.
+  --memory-swap-combined
+                        Show memory and swap on one line, e.g.
+                        Mem/Swap: 15/32 GB | 1/8 GB. Implies --show-swap.
+  --show-pressure       Show memory pressure: normal, warning or critical (Linux
+                        PSI, macOS).
+  --show-toolchains     Show installed toolchain versions (runs each binary).
+  --show-git            Show the git repository of the current directory, its
+                        branch and how far it is ahead of or behind upstream.
+  --show-printer        Show the default printer (CUPS, Linux and macOS).
+  --show-network        Show the default gateway (Linux).
+  --show-wifi           Show the connected Wi-Fi network and its signal strength.
+  --show-power          Show whether a laptop runs on AC or battery, with the
+                        adapter wattage when it's reported (Linux, macOS).
+  --fields-preset NAME  Show a curated set of fields: desktop, server or laptop.
+                        --fields takes precedence.
+  --show-empty          Show N/A for fields that couldn't be detected instead of
+                        hiding them.
+  --fqdn                Show the fully-qualified hostname in the header.
+  --keep-domain         Keep the DOMAIN\\ prefix of the username in the header
+                        (Windows).
+  --width N             Treat the terminal as N columns wide; 0 disables
+                        width-based formatting. Detected when unset.
+  --gpu-list            List every GPU on its own line instead of grouping
+                        identical ones.
+  --gpu-index-base N    Number GPU labels from 0 (default) or 1.
+  --max-gpus N          Show at most N GPU lines, then how many more there are.
+                        Identical GPUs are grouped first. JSON lists them all.
+  --gpu-live            Read live GPU stats (power draw, VRAM in use) from the
+                        driver.
+  --show-gpu-temp       Show the primary GPU's temperature (Linux sysfs or
+                        nvidia-smi).
+  --show-api-versions   Show the highest Vulkan/OpenGL version of each GPU
+                        (also shown with --gpu-live).
+  --sort-gpus-by KEY    Order GPUs by index, name, vram or type (default: type,
+                        discrete first). Indices follow the PCI bus id when
+                        it's known (`bus` is the same as index), else names.
+  --border STYLE        Draw a rounded, square or no (default) box around the output.
+  --theme THEME         Color the labels and values for a dark or light
+                        background, or pick one from the terminal's background
+                        (auto).
+  --distro-colors       Tint the logo with the detected OS's brand colors, or the
+                        theme's accent color for OSes without any.
+  --group-digits        Add locale thousands separators to large numbers.
+  --uptime-since        Append the boot time to the uptime, e.g.
+                        12d 3h 16m (since 2024-06-01 08:14).
+  --locale-uptime       Spell out the uptime units in the locale's language
+                        (e.g. 3 Tage, 2 Stunden, 5 Minuten) instead of 3d 2h 5m.
+  --lang LANG           Language for --locale-uptime (en, de, es, fr, it, nl or
+                        pt) instead of the locale's; implies --locale-uptime.
+  --ascii-only          Only draw ASCII characters (implied when TERM=dumb).
+  --format FORMAT       Output format: text (default), json, markdown, table
+                        (aligned columns without the logo) or env (FETCH_OS='…'
+                        lines to eval or source in a shell).
+  --json-compact        Print JSON on a single line (implies --format json).
+  --include-errors      Add an `errors` object to the JSON output naming the
+                        fields whose collectors timed out or failed.
+  --emit FORMAT:PATH    Also write the output in FORMAT (e.g. json:info.json) to
+                        PATH, from the same collection pass. Repeatable; files
+                        never get colors.
+  --qr                  Add a QR code of the OS, kernel, CPU, GPUs, memory and
+                        uptime as JSON, to scan with a phone.
+  --render-image PATH   Write the colored output (logo and info) to PATH as an
+                        SVG image instead of printing it.
+  --crlf                End lines with \\r\\n instead of \\n (the default on every
+                        platform).
+  --check-updates       Mention when crates.io has a newer release (checked at
+                        most once a day; never installs anything).
+  --no-header           Leave out the user@host header and its underline.
+  --header-position POS Put the header at the top (default) or bottom, or
+                        leave it out (none).
+  --no-padding          Don't print the blank lines around the output.
+  --fields-json         Print every field's name, description, platforms and
+                        whether it's shown by default as JSON, then exit.
+  --dump-config         Print the configuration in effect (defaults, config file
+                        and command-line overrides) as commented TOML and exit.
+  --show-paths          Print where the config and other files are looked for
+                        and exit.
+  --compare OLD NEW     Show what changed between two saved --format json
+                        outputs and exit.
+  --columns N           Spread the fields over up to N columns beside the logo,
+                        as many as fit in the terminal width.
+  --logo-width N        Start the info lines N columns (plus a gap) from the left
+                        edge instead of after the logo's widest row.
+  --logo-scale FACTOR   Shrink the logo to FACTOR (e.g. 0.5) of its rows and
+                        columns, for large custom logos on small terminals.
+  --logo-only           Print just the logo and exit.
+  --color-test          Print the named colors and a truecolor gradient, then exit.
+  --show-terminal-caps  Print what the terminal supports (truecolor, 256 colors,
+                        Unicode) and the TERM/COLORTERM values, then exit.
+  --raw-uptime          Print the uptime in seconds and exit.
+  --uptime-gt DURATION  Exit with 0 if the uptime exceeds DURATION (e.g. 30d,
+                        12h, 90m, 45s), 1 otherwise. Prints nothing unless -v.
+  --cache               Reuse slow, static fields (OS, model, serial, chassis,
+                        kernel, GPUs) from earlier runs until they expire or
+                        the machine reboots.
+  --timings             Print how long collecting took after the output.
+  --deterministic       Zero or leave out live figures (uptime, usage, used
+                        memory and disk, temperatures, ...) so two runs on the
+                        same machine print identical output.
+  --watch INTERVAL      Refresh every INTERVAL (e.g. 2s, 1m) until interrupted.
+                        With --format json, prints one timestamped object per
+                        line instead of redrawing.
+  --serve SOCKET        Listen on a Unix domain socket and stream one JSON
+                        object per line to every connected client, every
+                        --watch interval (default 2s).
+  --watch-changes       With --watch, only rewrite the lines that changed instead
+                        of clearing the screen (less flicker).
+  --repeat N            Collect and render N times, printing the last output and
+                        min/median/max collection times (-v: every run's).
+  -v, --verbose         Show more detail; repeat (-vv) for debugging output.
+  -h, --help            Print this help and exit.
+";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+    Table,
+    /// `FETCH_OS='…'` lines for `eval`/`source`.
+    Env,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "table" => Ok(OutputFormat::Table),
+            "env" => Ok(OutputFormat::Env),
+            _ => Err(format!("unknown format `{}` (expected text, json, markdown, table or env)", value)),
+        };
+    }
+}
+
+/// Order of the GPU lines. `Type` puts discrete cards first.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GpuSort {
+    Index,
+    Name,
+    Vram,
+    #[default]
+    Type,
+}
+
+impl std::str::FromStr for GpuSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "index" | "bus" => Ok(GpuSort::Index),
+            "name" => Ok(GpuSort::Name),
+            "vram" => Ok(GpuSort::Vram),
+            "type" => Ok(GpuSort::Type),
+            _ => Err(format!("unknown GPU sort `{}` (expected index, name, vram or type)", value)),
+        };
+    }
+}
+
+/// Address families shown on the IP line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IpMode {
+    #[default]
+    V4,
+    V6,
+    Both,
+}
+
+impl IpMode {
+    pub fn v4(self) -> bool {
+        return self != IpMode::V6;
+    }
+
+    pub fn v6(self) -> bool {
+        return self != IpMode::V4;
+    }
+}
+
+impl std::str::FromStr for IpMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "v4" => Ok(IpMode::V4),
+            "v6" => Ok(IpMode::V6),
+            "both" => Ok(IpMode::Both),
+            _ => Err(format!("unknown IP mode `{}` (expected v4, v6 or both)", value)),
+        };
+    }
+}
+
+/// Accent colors for labels. `Auto` asks the terminal for its background.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Dark,
+    Light,
+    Auto,
+}
+
+impl std::str::FromStr for Theme {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            "auto" => Ok(Theme::Auto),
+            _ => Err(format!("unknown theme `{}` (expected dark, light or auto)", value)),
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BorderStyle {
+    #[default]
+    None,
+    Rounded,
+    Square,
+}
+
+impl std::str::FromStr for BorderStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "none" => Ok(BorderStyle::None),
+            "rounded" => Ok(BorderStyle::Rounded),
+            "square" => Ok(BorderStyle::Square),
+            _ => Err(format!("unknown border `{}` (expected rounded, square or none)", value)),
+        };
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub help: bool,
+    pub show_env: Vec<String>,
+    pub no_padding: bool,
+    pub no_header: bool,
+    pub header_position: Option<HeaderPosition>,
+    pub show_updated: bool,
+    pub show_updates: bool,
+    pub show_failed_units: bool,
+    pub show_virt: bool,
+    pub show_cache: bool,
+    pub show_topology: bool,
+    pub show_threads: bool,
+    pub show_fds: bool,
+    pub show_entropy: bool,
+    pub show_firmware: bool,
+    pub show_top: bool,
+    pub show_session: bool,
+    pub show_users: bool,
+    pub show_encryption: bool,
+    pub show_storage_health: bool,
+    pub show_inodes: bool,
+    pub show_swap: bool,
+    pub show_pid: bool,
+    pub check_kernel: bool,
+    pub show_cmdline: bool,
+    pub full_cmdline: bool,
+    pub kernel_date: bool,
+    pub show_governor: bool,
+    pub show_network: bool,
+    pub show_ip: bool,
+    pub show_printer: bool,
+    pub show_wifi: bool,
+    pub show_power: bool,
+    pub show_pressure: bool,
+    pub memory_from_proc: bool,
+    pub memory_swap_combined: bool,
+    pub show_toolchains: bool,
+    pub show_git: bool,
+    pub fqdn: bool,
+    pub keep_domain: bool,
+    pub width: Option<usize>,
+    pub gpu_list: bool,
+    pub ascii_only: bool,
+    pub verbose: u8,
+    pub format: OutputFormat,
+    pub json_compact: bool,
+    pub include_errors: bool,
+    pub crlf: bool,
+    pub render_image: Option<String>,
+    pub qr: bool,
+    /// `--emit` targets, written besides the regular output.
+    pub emit: Vec<(OutputFormat, String)>,
+    pub sort_gpus_by: GpuSort,
+    pub fast: bool,
+    pub deterministic: bool,
+    pub cache: bool,
+    pub no_refresh: bool,
+    pub border: BorderStyle,
+    pub theme: Option<Theme>,
+    pub distro_colors: bool,
+    pub raw_uptime: bool,
+    pub color_test: bool,
+    pub show_terminal_caps: bool,
+    pub logo_only: bool,
+    pub logo_width: Option<usize>,
+    pub logo_scale: Option<f64>,
+    pub columns: Option<usize>,
+    pub compare: Option<(String, String)>,
+    pub show_paths: bool,
+    pub fields_json: bool,
+    pub dump_config: bool,
+    /// Threshold in seconds for the `--uptime-gt` health check.
+    pub uptime_gt: Option<usize>,
+    pub group_digits: bool,
+    pub locale_uptime: bool,
+    pub uptime_since: bool,
+    pub lang: Option<String>,
+    pub os_name: Option<String>,
+    pub profile: Option<String>,
+    pub gpu_live: bool,
+    pub show_gpu_temp: bool,
+    pub gpu_index_base: Option<usize>,
+    pub max_gpus: Option<usize>,
+    pub show_api_versions: bool,
+    pub fields: Vec<Field>,
+    pub fields_file: Option<String>,
+    pub fields_preset: Vec<Field>,
+    pub show_empty: bool,
+    pub ip: IpMode,
+    pub cpu_samples: Option<usize>,
+    pub cpu_combined: bool,
+    pub frequency_unit: Option<FrequencyUnit>,
+    pub cpu_breakdown: bool,
+    pub repeat: Option<usize>,
+    /// `--watch` interval in seconds.
+    pub watch: Option<usize>,
+    pub watch_changes: bool,
+    /// `--serve` socket path.
+    pub serve: Option<String>,
+    pub check_updates: bool,
+    pub timings: bool,
+}
+
+impl Options {
+    /// Turns on an opt-in field as its `--show-*` flag would, so presets
+    /// can list them. `Extra` needs names and is left alone.
+    pub fn opt_in(&mut self, field: Field) {
+        let flag = match field {
+            Field::Firmware => &mut self.show_firmware,
+            Field::Cmdline => &mut self.show_cmdline,
+            Field::Updated => &mut self.show_updated,
+            Field::Updates => &mut self.show_updates,
+            Field::FailedUnits => &mut self.show_failed_units,
+            Field::Threads => &mut self.show_threads,
+            Field::Fds => &mut self.show_fds,
+            Field::Entropy => &mut self.show_entropy,
+            Field::ShellPid => &mut self.show_pid,
+            Field::Session => &mut self.show_session,
+            Field::Users => &mut self.show_users,
+            Field::Top => &mut self.show_top,
+            Field::Cache => &mut self.show_cache,
+            Field::Topology => &mut self.show_topology,
+            Field::Virt => &mut self.show_virt,
+            Field::Governor => &mut self.show_governor,
+            Field::GpuTemp => &mut self.show_gpu_temp,
+            Field::Printer => &mut self.show_printer,
+            Field::Ip => &mut self.show_ip,
+            Field::Gateway => &mut self.show_network,
+            Field::Wifi => &mut self.show_wifi,
+            Field::Power => &mut self.show_power,
+            Field::Pressure => &mut self.show_pressure,
+            Field::Toolchains => &mut self.show_toolchains,
+            Field::Repo => &mut self.show_git,
+            Field::Swap => &mut self.show_swap,
+            Field::Inodes => &mut self.show_inodes,
+            Field::Encryption => &mut self.show_encryption,
+            Field::Trim => &mut self.show_storage_health,
+            _ => return,
+        };
+        *flag = true;
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    return args.next().ok_or(format!("{} expects a value", flag));
+}
+
+fn parse_number(value: &str, flag: &str) -> Result<usize, String> {
+    return value
+        .parse()
+        .map_err(|_| format!("{} expects a non-negative number, got `{}`", flag, value));
+}
+
+/// A factor above 0 and at most 1.
+fn parse_scale(value: &str, flag: &str) -> Result<f64, String> {
+    return value
+        .parse::<f64>()
+        .ok()
+        .filter(|scale| *scale > 0.0 && *scale <= 1.0)
+        .ok_or_else(|| format!("{} expects a factor above 0 and at most 1, got `{}`", flag, value));
+}
+
+/// `30d`, `12h`, `90m`, `45s`, `2w`, or bare seconds.
+fn parse_duration(value: &str, flag: &str) -> Result<usize, String> {
+    let invalid = || format!("{} expects a duration such as 30d, 12h or 90m, got `{}`", flag, value);
+    let (number, unit_seconds) = match value.char_indices().last() {
+        Some((idx, 's')) => (&value[..idx], 1),
+        Some((idx, 'm')) => (&value[..idx], 60),
+        Some((idx, 'h')) => (&value[..idx], 60 * 60),
+        Some((idx, 'd')) => (&value[..idx], 24 * 60 * 60),
+        Some((idx, 'w')) => (&value[..idx], 7 * 24 * 60 * 60),
+        _ => (value, 1),
+    };
+    let number: usize = number.parse().map_err(|_| invalid())?;
+    return number.checked_mul(unit_seconds).ok_or_else(invalid);
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "-v" | "--verbose" => options.verbose += 1,
+            "-vv" => options.verbose += 2,
+            "--no-padding" => options.no_padding = true,
+            "--no-header" => options.no_header = true,
+            "--header-position" => options.header_position = Some(next_value(&mut args, &arg)?.parse()?),
+            "--fqdn" => options.fqdn = true,
+            "--keep-domain" => options.keep_domain = true,
+            "--width" => options.width = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--gpu-list" => options.gpu_list = true,
+            "--ascii-only" => options.ascii_only = true,
+            "--format" => options.format = next_value(&mut args, &arg)?.parse()?,
+            "--include-errors" => options.include_errors = true,
+            "--json-compact" => {
+                options.format = OutputFormat::Json;
+                options.json_compact = true;
+            }
+            "--crlf" => options.crlf = true,
+            "--emit" => {
+                let value = next_value(&mut args, &arg)?;
+                let Some((format, path)) = value.split_once(':').filter(|(_, path)| !path.is_empty()) else {
+                    return Err(format!("--emit expects FORMAT:PATH, got `{}`", value));
+                };
+                options.emit.push((format.parse()?, path.to_string()));
+            }
+            "--qr" => options.qr = true,
+            "--render-image" => options.render_image = Some(next_value(&mut args, &arg)?),
+            "--sort-gpus-by" => options.sort_gpus_by = next_value(&mut args, &arg)?.parse()?,
+            "--fast" => options.fast = true,
+            "--deterministic" => options.deterministic = true,
+            "--cache" => options.cache = true,
+            "--no-refresh" => options.no_refresh = true,
+            "--border" => options.border = next_value(&mut args, &arg)?.parse()?,
+            "--theme" => options.theme = Some(next_value(&mut args, &arg)?.parse()?),
+            "--distro-colors" => options.distro_colors = true,
+            "--raw-uptime" => options.raw_uptime = true,
+            "--color-test" => options.color_test = true,
+            "--show-terminal-caps" => options.show_terminal_caps = true,
+            "--logo-only" => options.logo_only = true,
+            "--columns" => options.columns = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--logo-scale" => options.logo_scale = Some(parse_scale(&next_value(&mut args, &arg)?, &arg)?),
+            "--logo-width" => options.logo_width = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--compare" => options.compare = Some((next_value(&mut args, &arg)?, next_value(&mut args, &arg)?)),
+            "--show-paths" => options.show_paths = true,
+            "--fields-json" => options.fields_json = true,
+            "--dump-config" => options.dump_config = true,
+            "--uptime-gt" => options.uptime_gt = Some(parse_duration(&next_value(&mut args, &arg)?, &arg)?),
+            "--group-digits" => options.group_digits = true,
+            "--locale-uptime" => options.locale_uptime = true,
+            "--uptime-since" => options.uptime_since = true,
+            "--lang" => options.lang = Some(next_value(&mut args, &arg)?),
+            "--profile" => options.profile = Some(next_value(&mut args, &arg)?),
+            "--os-name" => options.os_name = Some(next_value(&mut args, &arg)?),
+            "--gpu-live" => options.gpu_live = true,
+            "--show-gpu-temp" => options.show_gpu_temp = true,
+            "--gpu-index-base" => {
+                options.gpu_index_base = match next_value(&mut args, &arg)?.as_str() {
+                    "0" => Some(0),
+                    "1" => Some(1),
+                    value => return Err(format!("{} expects 0 or 1, got `{}`", arg, value)),
+                }
+            }
+            "--max-gpus" => options.max_gpus = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--show-api-versions" => options.show_api_versions = true,
+            "--cpu-combined" => options.cpu_combined = true,
+            "--frequency-unit" => options.frequency_unit = Some(next_value(&mut args, &arg)?.parse()?),
+            "--cpu-breakdown" => options.cpu_breakdown = true,
+            "--cpu-samples" => options.cpu_samples = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--fields" => options.fields = field::parse_field_list(&next_value(&mut args, &arg)?)?,
+            "--show-ip" => options.show_ip = true,
+            "--ip" => {
+                options.ip = next_value(&mut args, &arg)?.parse()?;
+                options.show_ip = true;
+            }
+            "--watch" => options.watch = Some(parse_duration(&next_value(&mut args, &arg)?, &arg)?),
+            "--watch-changes" => options.watch_changes = true,
+            "--serve" => options.serve = Some(next_value(&mut args, &arg)?),
+            "--repeat" => options.repeat = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?),
+            "--fields-file" => options.fields_file = Some(next_value(&mut args, &arg)?),
+            "--fields-preset" => options.fields_preset = field::preset(&next_value(&mut args, &arg)?)?,
+            "--show-empty" => options.show_empty = true,
+            "--check-updates" => options.check_updates = true,
+            "--timings" => options.timings = true,
+            "--show-updated" => options.show_updated = true,
+            "--show-updates" => options.show_updates = true,
+            "--show-failed-units" => options.show_failed_units = true,
+            "--show-virt" => options.show_virt = true,
+            "--show-cache" => options.show_cache = true,
+            "--show-topology" => options.show_topology = true,
+            "--show-threads" => options.show_threads = true,
+            "--show-fds" => options.show_fds = true,
+            "--show-entropy" => options.show_entropy = true,
+            "--show-firmware" => options.show_firmware = true,
+            "--show-top" => options.show_top = true,
+            "--show-session" => options.show_session = true,
+            "--show-users" => options.show_users = true,
+            "--show-encryption" => options.show_encryption = true,
+            "--show-storage-health" => options.show_storage_health = true,
+            "--show-inodes" => options.show_inodes = true,
+            "--show-swap" => options.show_swap = true,
+            "--show-pid" => options.show_pid = true,
+            "--check-kernel" => options.check_kernel = true,
+            "--show-cmdline" => options.show_cmdline = true,
+            "--full-cmdline" => options.full_cmdline = true,
+            "--kernel-date" => options.kernel_date = true,
+            "--show-governor" => options.show_governor = true,
+            "--show-network" => options.show_network = true,
+            "--show-printer" => options.show_printer = true,
+            "--show-wifi" => options.show_wifi = true,
+            "--show-power" => options.show_power = true,
+            "--show-pressure" => options.show_pressure = true,
+            "--memory-from-proc" => options.memory_from_proc = true,
+            "--memory-swap-combined" => options.memory_swap_combined = true,
+            "--show-toolchains" => options.show_toolchains = true,
+            "--show-git" => options.show_git = true,
+            "--show-env" => options.show_env.push(next_value(&mut args, &arg)?),
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    return Ok(options);
+}
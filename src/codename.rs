@@ -0,0 +1,140 @@
+//! Microarchitecture or codename of well-known CPUs, from their brand
+//! string, for the `CPU` line.
+
+use crate::glob_match;
+
+/// `(brand glob, name)`, first match wins, so specific patterns go before
+/// the general ones of the same family. AMD parts are named by their Zen
+/// core, Intel ones by their codename. Apple chips are left out: their
+/// brand (`Apple M3 Pro`) already is the generation.
+const CPU_CODENAMES: [(&str, &str); 77] = [
+    // AMD Threadripper and EPYC, by the model's first and last digits.
+    ("*Threadripper* 79??*", "Zen 4"),
+    ("*Threadripper* 59??*", "Zen 3"),
+    ("*Threadripper* 39??*", "Zen 2"),
+    ("*Threadripper* 29??*", "Zen+"),
+    ("*Threadripper* 19??*", "Zen"),
+    ("*EPYC 9??5*", "Zen 5"),
+    ("*EPYC 9??4*", "Zen 4"),
+    ("*EPYC 8??4*", "Zen 4"),
+    ("*EPYC 4??4*", "Zen 4"),
+    ("*EPYC 7??3*", "Zen 3"),
+    ("*EPYC 7??2*", "Zen 2"),
+    ("*EPYC 7??1*", "Zen"),
+    // AMD Ryzen. Mobile parts mix generations within a series, which the
+    // third digit tells apart since the 7000 series.
+    ("*Ryzen AI *", "Zen 5"),
+    ("*Ryzen ? 9???*", "Zen 5"),
+    ("*Ryzen ? 8???*", "Zen 4"),
+    ("*Ryzen ? 7?45*", "Zen 4"),
+    ("*Ryzen ? 7?40*", "Zen 4"),
+    ("*Ryzen ? 7?35*", "Zen 3+"),
+    ("*Ryzen ? 7?30*", "Zen 3"),
+    ("*Ryzen ? 7?20*", "Zen 2"),
+    ("*Ryzen ? 7???*", "Zen 4"),
+    ("*Ryzen ? 6???*", "Zen 3+"),
+    // Lucienne: the only Zen 2 parts among the 5000 mobile chips.
+    ("*Ryzen 3 5300U*", "Zen 2"),
+    ("*Ryzen 5 5500U*", "Zen 2"),
+    ("*Ryzen 7 5700U*", "Zen 2"),
+    ("*Ryzen ? 5???*", "Zen 3"),
+    ("*Ryzen ? 4???*", "Zen 2"),
+    ("*Ryzen ? 3??0U*", "Zen+"),
+    ("*Ryzen ? 3??0H*", "Zen+"),
+    ("*Ryzen ? 3?00G*", "Zen+"),
+    ("*Ryzen ? 3???*", "Zen 2"),
+    ("*Ryzen ? 2?00U*", "Zen"),
+    ("*Ryzen ? 2?00G*", "Zen"),
+    ("*Ryzen ? 2???*", "Zen+"),
+    ("*Ryzen ? 1???*", "Zen"),
+    // Intel Core Ultra and the `Nth Gen` Core brands.
+    ("*Core* Ultra ? 2??V*", "Lunar Lake"),
+    ("*Core* Ultra ? 2*", "Arrow Lake"),
+    ("*Core* Ultra ? 1*", "Meteor Lake"),
+    ("14th Gen Intel*", "Raptor Lake Refresh"),
+    ("13th Gen Intel*", "Raptor Lake"),
+    ("12th Gen Intel*", "Alder Lake"),
+    ("11th Gen Intel*-11?5G*", "Tiger Lake"),
+    ("11th Gen Intel*-11??0H*", "Tiger Lake"),
+    ("11th Gen Intel*", "Rocket Lake"),
+    // Older Intel Core, by the generation digit of the model number.
+    ("*Core* i?-10?5G*", "Ice Lake"),
+    ("*Core* i?-10???*", "Comet Lake"),
+    ("*Core* i?-9???*", "Coffee Lake"),
+    ("*Core* i?-8??5U*", "Whiskey Lake"),
+    ("*Core* i?-8??0U*", "Kaby Lake"),
+    ("*Core* i?-8???*", "Coffee Lake"),
+    ("*Core* i?-7???*", "Kaby Lake"),
+    ("*Core* i?-6???*", "Skylake"),
+    // Xeon E3/E5, by the version suffix.
+    ("*Xeon* E?-* v6*", "Kaby Lake"),
+    ("*Xeon* E?-* v5*", "Skylake"),
+    ("*Xeon* E?-* v4*", "Broadwell"),
+    ("*Xeon* E?-* v3*", "Haswell"),
+    ("*Xeon* E?-* v2*", "Ivy Bridge"),
+    // Xeon Scalable, by the generation digit after the tier's first digit.
+    // The tier is spelled out so older models (`X5670`) don't match.
+    ("*Xeon* Platinum ?5??*", "Emerald Rapids"),
+    ("*Xeon* Gold ?5??*", "Emerald Rapids"),
+    ("*Xeon* Silver ?5??*", "Emerald Rapids"),
+    ("*Xeon* Bronze ?5??*", "Emerald Rapids"),
+    ("*Xeon* Platinum ?4??*", "Sapphire Rapids"),
+    ("*Xeon* Gold ?4??*", "Sapphire Rapids"),
+    ("*Xeon* Silver ?4??*", "Sapphire Rapids"),
+    ("*Xeon* Bronze ?4??*", "Sapphire Rapids"),
+    ("*Xeon* Platinum ?3??*", "Ice Lake"),
+    ("*Xeon* Gold ?3??*", "Ice Lake"),
+    ("*Xeon* Silver ?3??*", "Ice Lake"),
+    ("*Xeon* Bronze ?3??*", "Ice Lake"),
+    ("*Xeon* Platinum ?2??*", "Cascade Lake"),
+    ("*Xeon* Gold ?2??*", "Cascade Lake"),
+    ("*Xeon* Silver ?2??*", "Cascade Lake"),
+    ("*Xeon* Bronze ?2??*", "Cascade Lake"),
+    ("*Xeon* Platinum ?1??*", "Skylake"),
+    ("*Xeon* Gold ?1??*", "Skylake"),
+    ("*Xeon* Silver ?1??*", "Skylake"),
+    ("*Xeon* Bronze ?1??*", "Skylake"),
+];
+
+/// `Zen 4` for `AMD Ryzen 9 7950X 16-Core Processor`; `None` for CPUs the
+/// table doesn't know.
+pub fn cpu_codename(brand: &str) -> Option<&'static str> {
+    return CPU_CODENAMES
+        .iter()
+        .find(|(pattern, _)| glob_match(pattern, brand))
+        .map(|(_, name)| *name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codenames_from_brand_strings() {
+        assert_eq!(cpu_codename("AMD Ryzen 9 7950X 16-Core Processor"), Some("Zen 4"));
+        assert_eq!(cpu_codename("AMD Ryzen 7 7735HS with Radeon Graphics"), Some("Zen 3+"));
+        assert_eq!(cpu_codename("AMD Ryzen 5 5500U with Radeon Graphics"), Some("Zen 2"));
+        assert_eq!(cpu_codename("AMD EPYC 7763 64-Core Processor"), Some("Zen 3"));
+        assert_eq!(cpu_codename("AMD Ryzen Threadripper 3990X 64-Core Processor"), Some("Zen 2"));
+        assert_eq!(cpu_codename("13th Gen Intel(R) Core(TM) i9-13900K"), Some("Raptor Lake"));
+        assert_eq!(cpu_codename("11th Gen Intel(R) Core(TM) i7-1165G7 @ 2.80GHz"), Some("Tiger Lake"));
+        assert_eq!(cpu_codename("Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz"), Some("Coffee Lake"));
+        assert_eq!(cpu_codename("Intel(R) Core(TM) Ultra 7 155H"), Some("Meteor Lake"));
+        assert_eq!(cpu_codename("Intel(R) Xeon(R) Gold 6248R CPU @ 3.00GHz"), Some("Cascade Lake"));
+        assert_eq!(cpu_codename("Intel(R) Xeon(R) Platinum 8480+"), Some("Sapphire Rapids"));
+        assert_eq!(cpu_codename("Intel(R) Xeon(R) CPU E5-2680 v4 @ 2.40GHz"), Some("Broadwell"));
+        assert_eq!(cpu_codename("Intel(R) Xeon(R) CPU X5670 @ 2.93GHz"), None);
+        assert_eq!(cpu_codename("Intel(R) Xeon(R) Processor"), None);
+        assert_eq!(cpu_codename("Apple M3 Pro"), None);
+    }
+
+    #[test]
+    fn ryzen_5000_mobile_chips_split_between_zen_2_and_zen_3() {
+        for model in ["3 5300U", "5 5500U", "7 5700U"] {
+            assert_eq!(cpu_codename(&format!("AMD Ryzen {} with Radeon Graphics", model)), Some("Zen 2"), "{}", model);
+        }
+        for model in ["3 5400U", "5 5600U", "7 5800U", "5 5625U", "7 5825U", "9 5900HX", "5 5600H"] {
+            assert_eq!(cpu_codename(&format!("AMD Ryzen {} with Radeon Graphics", model)), Some("Zen 3"), "{}", model);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! The extra info lines after the built-in fields: the `--show-env`
+//! variables and the `[[extra]]` commands, each collected on its own thread
+//! and rendered and serialized the same way.
+
+use crate::color;
+use crate::config::ExtraCommand;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A source of one extra line, shown as `name: value` after the built-in
+/// fields. Collectors run on their own thread, hence `Send`.
+pub trait Collector: Send {
+    fn name(&self) -> &str;
+    /// `None` leaves the line out.
+    fn collect(&self) -> Option<String>;
+}
+
+/// One environment variable, as listed by `--show-env`.
+pub struct EnvVarCollector {
+    label: String,
+    key: String,
+}
+
+impl EnvVarCollector {
+    pub fn new(key: String) -> EnvVarCollector {
+        return EnvVarCollector { label: format!("ENV {}", key), key };
+    }
+}
+
+impl Collector for EnvVarCollector {
+    fn name(&self) -> &str {
+        return &self.label;
+    }
+
+    fn collect(&self) -> Option<String> {
+        return std::env::var_os(&self.key).map(|value| value.to_string_lossy().into_owned());
+    }
+}
+
+/// A user-configured command from the `[[extra]]` config tables.
+pub struct CommandCollector {
+    label: String,
+    command: String,
+    keep_ansi: bool,
+}
+
+impl CommandCollector {
+    pub fn new(extra: &ExtraCommand) -> CommandCollector {
+        return CommandCollector {
+            label: extra.label.clone(),
+            command: extra.command.clone(),
+            keep_ansi: extra.keep_ansi,
+        };
+    }
+}
+
+impl Collector for CommandCollector {
+    fn name(&self) -> &str {
+        return &self.label;
+    }
+
+    fn collect(&self) -> Option<String> {
+        #[cfg(windows)]
+        let output = Command::new("cmd").args(["/C", &self.command]).output().ok()?;
+        #[cfg(not(windows))]
+        let output = Command::new("sh").args(["-c", &self.command]).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().next()?;
+        // Third-party colors would throw off the width of the line.
+        let line = if self.keep_ansi { line.to_string() } else { color::strip_ansi(line) };
+        let line = line.trim();
+        return if line.is_empty() { None } else { Some(line.to_string()) };
+    }
+}
+
+/// A collector running on its own thread.
+pub struct Pending<T> {
+    name: String,
+    started: Instant,
+    /// `None` when the collector of the same name that missed an earlier
+    /// run's timeout is still going, so no new one was started.
+    receiver: Option<Receiver<T>>,
+    finished: Arc<AtomicBool>,
+}
+
+/// Collectors given up on that haven't returned yet, by name. `--watch`
+/// and `--serve` collect again every tick, and one stuck on a dead mount
+/// would otherwise leave another thread behind each time.
+static STILL_RUNNING: Mutex<BTreeMap<String, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+/// Marks a collector finished when its thread ends, even by panicking.
+struct FinishGuard(Arc<AtomicBool>);
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// Starts `collect` on a detached thread, unless the collector of the same
+/// name from an earlier run is still stuck. It isn't scoped: a scope would
+/// have to join a wedged collector before returning, which is exactly the
+/// hang this guards against.
+pub fn spawn<T: Send + 'static>(name: &str, collect: impl FnOnce() -> T + Send + 'static) -> Pending<T> {
+    return start(name, collect).0;
+}
+
+/// Threads `finish_background` waits for.
+static BACKGROUND: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Like `spawn`, for collectors whose work is worth finishing after they
+/// miss the timeout, such as refreshing a cache for the next run: the
+/// process waits for them in `finish_background` before exiting.
+pub fn spawn_finished_at_exit<T: Send + 'static>(name: &str, collect: impl FnOnce() -> T + Send + 'static) -> Pending<T> {
+    let (pending, handle) = start(name, collect);
+    let mut background = BACKGROUND.lock().unwrap_or_else(PoisonError::into_inner);
+    // `--watch` keeps adding them; only the unfinished ones are worth keeping.
+    background.retain(|handle| !handle.is_finished());
+    background.extend(handle);
+    return pending;
+}
+
+/// Waits for the threads started with `spawn_finished_at_exit`, once the
+/// output is out.
+pub fn finish_background() {
+    let background = std::mem::take(&mut *BACKGROUND.lock().unwrap_or_else(PoisonError::into_inner));
+    for handle in background {
+        let _ = handle.join();
+    }
+}
+
+fn start<T: Send + 'static>(name: &str, collect: impl FnOnce() -> T + Send + 'static) -> (Pending<T>, Option<JoinHandle<()>>) {
+    let started = Instant::now();
+    let finished = Arc::new(AtomicBool::new(false));
+    {
+        let mut still_running = STILL_RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+        if still_running.get(name).is_some_and(|finished| !finished.load(Ordering::Acquire)) {
+            return (Pending { name: name.to_string(), started, receiver: None, finished }, None);
+        }
+        still_running.remove(name);
+    }
+    let (sender, receiver) = mpsc::channel();
+    let guard = FinishGuard(Arc::clone(&finished));
+    let handle = std::thread::spawn(move || {
+        let _guard = guard;
+        // The receiver is gone if we already gave up on this collector.
+        let _ = sender.send(collect());
+    });
+    return (Pending { name: name.to_string(), started, receiver: Some(receiver), finished }, Some(handle));
+}
+
+impl<T> Pending<T> {
+    /// The collector's result, or `None` with a warning when it hasn't
+    /// finished within `timeout` of being started. Each collector's time
+    /// counts from its own start, so one started late isn't cut short by
+    /// the others' budget.
+    pub fn wait(self, timeout: Duration) -> Option<T> {
+        return self.wait_result(timeout).ok();
+    }
+
+    /// Like `wait`, but the error says why there's no result (`timed out`,
+    /// `failed`, `still running`), for `--include-errors`.
+    pub fn wait_result(self, timeout: Duration) -> Result<T, String> {
+        let deadline = self.started + timeout;
+        let reason = match self.receiver.as_ref().map(|receiver| receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))) {
+            Some(Ok(value)) => return Ok(value),
+            Some(Err(mpsc::RecvTimeoutError::Timeout)) => {
+                let mut still_running = STILL_RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+                still_running.insert(self.name.clone(), Arc::clone(&self.finished));
+                "timed out"
+            }
+            Some(Err(mpsc::RecvTimeoutError::Disconnected)) => "failed",
+            None => "still running",
+        };
+        eprintln!("ashwin-fetch: {} {}, skipping it", self.name, reason);
+        return Err(reason.to_string());
+    }
+}
+
+/// Starts every collector on its own thread.
+pub fn spawn_lines(collectors: Vec<Box<dyn Collector>>) -> Vec<Pending<Option<String>>> {
+    return collectors
+        .into_iter()
+        .map(|collector| {
+            let name = collector.name().to_string();
+            spawn(&name, move || Some(format!("{}: {}", collector.name(), collector.collect()?)))
+        })
+        .collect();
+}
+
+/// The lines in registration order, skipping collectors with nothing to
+/// report or that took longer than `timeout`.
+pub fn wait_lines(pending: Vec<Pending<Option<String>>>, timeout: Duration) -> Vec<String> {
+    return pending.into_iter().filter_map(|line| line.wait(timeout).flatten()).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BuildHost(Option<&'static str>);
+
+    impl Collector for BuildHost {
+        fn name(&self) -> &str {
+            return "Build host";
+        }
+
+        fn collect(&self) -> Option<String> {
+            return self.0.map(String::from);
+        }
+    }
+
+    struct Stuck;
+
+    impl Collector for Stuck {
+        fn name(&self) -> &str {
+            return "Stuck";
+        }
+
+        fn collect(&self) -> Option<String> {
+            std::thread::sleep(Duration::from_secs(30));
+            return Some(String::from("too late"));
+        }
+    }
+
+    #[test]
+    fn renders_collectors_in_order_and_skips_empty_ones() {
+        let collectors: Vec<Box<dyn Collector>> = vec![
+            Box::new(BuildHost(Some("ci-01"))),
+            Box::new(BuildHost(None)),
+            Box::new(BuildHost(Some("ci-02"))),
+        ];
+        let lines = wait_lines(spawn_lines(collectors), Duration::from_secs(5));
+        assert_eq!(lines, ["Build host: ci-01", "Build host: ci-02"]);
+    }
+
+    #[test]
+    fn slow_collectors_are_skipped_at_the_deadline() {
+        let started = Instant::now();
+        let collectors: Vec<Box<dyn Collector>> = vec![Box::new(Stuck), Box::new(BuildHost(Some("ci-01")))];
+        let lines = wait_lines(spawn_lines(collectors), Duration::from_millis(100));
+        assert_eq!(lines, ["Build host: ci-01"]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn stuck_collectors_are_not_started_again() {
+        let starts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let start = || {
+            let starts = Arc::clone(&starts);
+            return spawn("Sleepy", move || {
+                starts.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(300));
+            });
+        };
+        assert_eq!(start().wait_result(Duration::from_millis(50)), Err(String::from("timed out")));
+        assert_eq!(start().wait_result(Duration::from_millis(50)), Err(String::from("still running")));
+        assert_eq!(starts.load(Ordering::SeqCst), 1);
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(start().wait_result(Duration::from_secs(5)), Ok(()));
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn env_var_collector_labels_with_the_key() {
+        let collector = EnvVarCollector::new(String::from("ASHWIN_FETCH_SURELY_UNSET"));
+        assert_eq!(collector.name(), "ENV ASHWIN_FETCH_SURELY_UNSET");
+        assert_eq!(collector.collect(), None);
+    }
+}
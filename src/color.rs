@@ -0,0 +1,405 @@
+use std::io::IsTerminal;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+pub const RESET: &str = "\x1b[0m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const RED: &str = "\x1b[31m";
+pub const DIM: &str = "\x1b[2m";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn parse_hex(hex: &str) -> Option<Rgb> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |range| u8::from_str_radix(&hex[range], 16).ok();
+        return Some(Rgb {
+            r: channel(0..2)?,
+            g: channel(2..4)?,
+            b: channel(4..6)?,
+        });
+    }
+
+    /// Linear interpolation, `t` in `0.0..=1.0`.
+    pub fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        return Rgb {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        };
+    }
+
+    pub fn truecolor_fg(self) -> String {
+        return format!("\x1b[38;2;{};{};{}m", self.r, self.g, self.b);
+    }
+
+    /// Nearest entry in the 6x6x6 cube of the xterm 256-color palette.
+    pub fn ansi256_fg(self) -> String {
+        let level = |c: u8| ((c as u16 * 5 + 127) / 255) as u8;
+        let index = 16 + 36 * level(self.r) + 6 * level(self.g) + level(self.b);
+        return format!("\x1b[38;5;{}m", index);
+    }
+
+    /// Truecolor where the terminal has it, else the nearest 256-color entry.
+    pub fn fg(self) -> String {
+        return if supports_truecolor() { self.truecolor_fg() } else { self.ansi256_fg() };
+    }
+}
+
+/// Accent colors for the text output, picked to stay readable on the
+/// terminal's background.
+#[derive(Debug)]
+pub struct Palette {
+    pub label: &'static str,
+    /// Field values, set apart from the labels.
+    pub value: &'static str,
+    /// Healthy states, such as no failed units.
+    pub good: &'static str,
+}
+
+pub const DARK_PALETTE: Palette = Palette {
+    label: "\x1b[96m",
+    value: "\x1b[97m",
+    good: "\x1b[92m",
+};
+pub const LIGHT_PALETTE: Palette = Palette {
+    label: "\x1b[34m",
+    value: "\x1b[30m",
+    good: "\x1b[32m",
+};
+
+/// `COLORFGBG` (set by rxvt, Konsole and others) as `fg;bg` or
+/// `fg;default;bg`. Background colors 7 and 9-15 are the light ones.
+fn colorfgbg_is_light(value: &str) -> Option<bool> {
+    let background: u8 = value.rsplit(';').next()?.parse().ok()?;
+    return Some(background == 7 || (9..=15).contains(&background));
+}
+
+/// The color in an OSC 11 reply such as `\x1b]11;rgb:ffff/ffff/dddd\x07`.
+/// Channels have 1 to 4 hex digits.
+#[cfg(any(unix, test))]
+fn parse_osc11_reply(reply: &str) -> Option<Rgb> {
+    let (_, spec) = reply.split_once("rgb:")?;
+    let spec = spec.trim_end_matches(['\x07', '\\']).trim_end_matches('\x1b');
+    let channels: Vec<u8> = spec
+        .split('/')
+        .map(|channel| {
+            if channel.is_empty() || channel.len() > 4 {
+                return None;
+            }
+            let max = (1u32 << (4 * channel.len())) - 1;
+            let value = u32::from_str_radix(channel, 16).ok()?;
+            return Some((value * 255 / max) as u8);
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    return Some(Rgb { r, g, b });
+}
+
+/// Asks the terminal for its background color (OSC 11). `stty` puts the
+/// tty in raw mode with a 200 ms read timeout, so terminals that don't
+/// answer only cost that long.
+#[cfg(unix)]
+fn query_background() -> Option<Rgb> {
+    use std::io::Read;
+    use std::io::Write;
+    use std::process::Command;
+    use std::process::Stdio;
+    let tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let stty = |args: &[&str]| {
+        let output = Command::new("stty").args(args).stdin(tty.try_clone().ok()?).stderr(Stdio::null()).output().ok()?;
+        return output.status.success().then_some(output.stdout);
+    };
+    let saved = String::from_utf8_lossy(&stty(&["-g"])?).trim().to_string();
+    stty(&["raw", "-echo", "min", "0", "time", "2"])?;
+    let mut reply = Vec::new();
+    if (&tty).write_all(b"\x1b]11;?\x07").is_ok() {
+        let mut byte = [0u8; 1];
+        while reply.len() < 64 && matches!((&tty).read(&mut byte), Ok(1)) {
+            reply.push(byte[0]);
+            if byte[0] == b'\x07' || reply.ends_with(b"\x1b\\") {
+                break;
+            }
+        }
+    }
+    stty(&[&saved]);
+    return parse_osc11_reply(&String::from_utf8_lossy(&reply));
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<Rgb> {
+    return None;
+}
+
+/// Whether the terminal has a light background, from `COLORFGBG` or an
+/// OSC 11 query. `None` when neither tells.
+pub fn background_is_light() -> Option<bool> {
+    if let Some(light) = std::env::var("COLORFGBG").ok().and_then(|value| colorfgbg_is_light(&value)) {
+        return Some(light);
+    }
+    let Rgb { r, g, b } = query_background()?;
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    return Some(luminance > 127.5);
+}
+
+static FORCE_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Colors the output even when it isn't going to a terminal, for
+/// `--render-image`.
+pub fn force_color() {
+    FORCE_COLOR.store(true, Ordering::Relaxed);
+}
+
+/// Color is used only when writing to a terminal and `NO_COLOR` is unset,
+/// unless forced with [`force_color`].
+pub fn color_enabled() -> bool {
+    if FORCE_COLOR.load(Ordering::Relaxed) {
+        return true;
+    }
+    return std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+}
+
+pub fn supports_truecolor() -> bool {
+    return matches!(
+        std::env::var("COLORTERM").as_deref(),
+        Ok("truecolor") | Ok("24bit")
+    );
+}
+
+/// `TERM` names a 256-color (or better) terminal, or `COLORTERM` claims
+/// truecolor.
+pub fn supports_256_colors() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    return supports_truecolor() || term.contains("256color") || term.contains("truecolor") || term.contains("direct");
+}
+
+/// The locale asks for UTF-8; the first of `LC_ALL`, `LC_CTYPE` and `LANG`
+/// that is set decides. The Windows console always renders Unicode.
+pub fn supports_unicode() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return false;
+    }
+    if cfg!(windows) {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    return locale.contains("utf-8") || locale.contains("utf8");
+}
+
+/// `--show-terminal-caps`: what the environment says about the terminal,
+/// for picking a theme or reporting rendering bugs.
+pub fn terminal_caps() -> String {
+    let env = |name: &str| std::env::var(name).unwrap_or_else(|_| String::from("(unset)"));
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let rows = [
+        ("TERM", env("TERM")),
+        ("COLORTERM", env("COLORTERM")),
+        ("Truecolor", yes_no(supports_truecolor()).to_string()),
+        ("256 colors", yes_no(supports_256_colors()).to_string()),
+        ("Unicode", yes_no(supports_unicode()).to_string()),
+        ("Terminal", yes_no(std::io::stdout().is_terminal()).to_string()),
+        ("NO_COLOR", yes_no(std::env::var_os("NO_COLOR").is_some()).to_string()),
+        ("Colors used", yes_no(color_enabled()).to_string()),
+    ];
+    return rows.iter().map(|(label, value)| format!("{:<13}{}\n", format!("{}:", label), value)).collect();
+}
+
+/// The 16 standard terminal colors with their SGR foreground codes.
+const NAMED_COLORS: [(&str, u8); 16] = [
+    ("black", 30),
+    ("red", 31),
+    ("green", 32),
+    ("yellow", 33),
+    ("blue", 34),
+    ("magenta", 35),
+    ("cyan", 36),
+    ("white", 37),
+    ("bright-black", 90),
+    ("bright-red", 91),
+    ("bright-green", 92),
+    ("bright-yellow", 93),
+    ("bright-blue", 94),
+    ("bright-magenta", 95),
+    ("bright-cyan", 96),
+    ("bright-white", 97),
+];
+
+/// `--color-test`: the named colors, a truecolor gradient with its hex stops,
+/// and whether the terminal claims truecolor support. Always colored, since
+/// that's the point.
+pub fn color_test() -> String {
+    let mut output = String::from("Named colors:\n");
+    for (name, code) in NAMED_COLORS {
+        output.push_str(&format!("  \x1b[{}m\u{2588}\u{2588}\u{2588}{} {:<15} \\e[{}m\n", code, RESET, name, code));
+    }
+    const STOPS: [Rgb; 7] = [
+        Rgb { r: 0xff, g: 0x00, b: 0x00 },
+        Rgb { r: 0xff, g: 0xff, b: 0x00 },
+        Rgb { r: 0x00, g: 0xff, b: 0x00 },
+        Rgb { r: 0x00, g: 0xff, b: 0xff },
+        Rgb { r: 0x00, g: 0x00, b: 0xff },
+        Rgb { r: 0xff, g: 0x00, b: 0xff },
+        Rgb { r: 0xff, g: 0x00, b: 0x00 },
+    ];
+    const STEPS: usize = 10;
+    output.push_str("\nTruecolor gradient:\n  ");
+    for pair in STOPS.windows(2) {
+        for step in 0..STEPS {
+            let Rgb { r, g, b } = pair[0].lerp(pair[1], step as f64 / STEPS as f64);
+            output.push_str(&format!("\x1b[48;2;{};{};{}m ", r, g, b));
+        }
+    }
+    output.push_str(RESET);
+    output.push_str("\n  ");
+    let hex: Vec<String> = STOPS.iter().map(|Rgb { r, g, b }| format!("#{:02x}{:02x}{:02x}", r, g, b)).collect();
+    output.push_str(&hex.join(" -> "));
+    output.push_str(&format!(
+        "\n\nTruecolor: {}\n",
+        if supports_truecolor() {
+            "supported (COLORTERM advertises it)"
+        } else {
+            "not advertised (COLORTERM isn't truecolor or 24bit); gradients fall back to 256 colors"
+        }
+    ));
+    return output;
+}
+
+/// Removes ANSI escape sequences (CSI such as colors and cursor movement,
+/// OSC such as hyperlinks and titles, and two-byte escapes) from `text`.
+pub fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters and intermediates up to a final byte in `@..=~`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ST (`ESC \`).
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    return output;
+}
+
+/// Per-row escape prefixes for the logo. Without truecolor the gradient
+/// collapses to its first color.
+pub fn logo_row_colors(gradient: Option<(Rgb, Rgb)>, height: usize) -> Vec<String> {
+    let Some((from, to)) = gradient else {
+        return vec![];
+    };
+    if !color_enabled() {
+        return vec![];
+    }
+    if !supports_truecolor() {
+        return vec![from.ansi256_fg(); height];
+    }
+    let steps = height.saturating_sub(1).max(1) as f64;
+    return (0..height)
+        .map(|row| from.lerp(to, row as f64 / steps).truecolor_fg())
+        .collect();
+}
+
+/// Escape prefixes for the `logo_mask` color numbers 1 to 9: the configured
+/// `logo_mask_colors`, else the basic red through white (1 to 7), as
+/// neofetch numbers them.
+pub fn logo_mask_colors(colors: Option<Vec<Rgb>>) -> Vec<String> {
+    if !color_enabled() {
+        return vec![];
+    }
+    return match colors {
+        Some(colors) if supports_truecolor() => colors.into_iter().map(Rgb::truecolor_fg).collect(),
+        Some(colors) => colors.into_iter().map(Rgb::ansi256_fg).collect(),
+        None => (31..=37).map(|code| format!("\x1b[{}m", code)).collect(),
+    };
+}
+
+const fn rgb(hex: u32) -> Rgb {
+    return Rgb { r: (hex >> 16) as u8, g: (hex >> 8) as u8, b: hex as u8 };
+}
+
+/// Brand colors of common OSes, as the two ends of a `--distro-colors`
+/// gradient. Matched against the start of the OS name, ignoring case, so
+/// derivatives come before the distro they're named after.
+const DISTRO_COLORS: [(&str, Rgb, Rgb); 14] = [
+    ("arch", rgb(0x1793d1), rgb(0x0f5f8a)),
+    ("manjaro", rgb(0x35bf5c), rgb(0x1a7a3b)),
+    ("endeavouros", rgb(0x7f3fbf), rgb(0xe0498c)),
+    ("kubuntu", rgb(0x0079c1), rgb(0x55aadd)),
+    ("ubuntu", rgb(0xe95420), rgb(0x77216f)),
+    ("debian", rgb(0xd70a53), rgb(0x8a0636)),
+    ("fedora", rgb(0x51a2da), rgb(0x294172)),
+    ("linux mint", rgb(0x87cf3e), rgb(0x4d8a1c)),
+    ("pop!_os", rgb(0x48b9c7), rgb(0xfaa41a)),
+    ("opensuse", rgb(0x73ba25), rgb(0x35b9ab)),
+    ("gentoo", rgb(0x54487a), rgb(0xdddaec)),
+    ("nixos", rgb(0x7ebae4), rgb(0x5277c3)),
+    ("macos", rgb(0x61bb46), rgb(0x009ddc)),
+    ("windows", rgb(0x00a4ef), rgb(0x0078d4)),
+];
+
+/// `--distro-colors`: the logo rows tinted with `os`'s brand colors, or all
+/// in `accent` for OSes not in the table.
+pub fn distro_logo_colors(os: &str, accent: &str, height: usize) -> Vec<String> {
+    let os = os.to_lowercase();
+    return match DISTRO_COLORS.iter().find(|(name, _, _)| os.starts_with(name)) {
+        Some((_, from, to)) => logo_row_colors(Some((*from, *to)), height),
+        None if color_enabled() => vec![accent.to_string(); height],
+        None => vec![],
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorfgbg_background() {
+        assert_eq!(colorfgbg_is_light("15;0"), Some(false));
+        assert_eq!(colorfgbg_is_light("0;15"), Some(true));
+        assert_eq!(colorfgbg_is_light("0;default;7"), Some(true));
+        assert_eq!(colorfgbg_is_light("default"), None);
+    }
+
+    #[test]
+    fn osc11_reply_channels() {
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:ffff/ffff/dddd\x07"), Some(Rgb { r: 255, g: 255, b: 221 }));
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:00/80/ff\x1b\\"), Some(Rgb { r: 0, g: 128, b: 255 }));
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:12/34\x07"), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! `--compare OLD NEW`: what changed between two saved `--format json`
+//! outputs, e.g. before and after an upgrade.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Fields that change from one run to the next on any machine and would
+/// drown out the interesting differences.
+const VOLATILE: [&str; 16] = [
+    "timestamp",
+    "uptime",
+    "uptime_seconds",
+    "avg_usage",
+    "max_frequency_mhz",
+    "celsius",
+    "gpu_temperature",
+    "power_watts",
+    "vram_used_mb",
+    "memory_used_mb",
+    "swap_used_mb",
+    "used_bytes",
+    "signal_dbm",
+    "fd_counts",
+    "entropy_bits",
+    "top_processes",
+];
+
+/// Flattens nested objects and arrays into `gpu.0.gpu_name`-style paths.
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                if !VOLATILE.contains(&key.as_str()) {
+                    flatten(&join(key), value, out);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                flatten(&join(&idx.to_string()), item, out);
+            }
+        }
+        Value::Null => {}
+        Value::String(text) => {
+            out.insert(prefix.to_string(), text.clone());
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+fn load(path: &str) -> Result<BTreeMap<String, String>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let value: Value = serde_json::from_str(&contents).map_err(|err| format!("{}: {}", path, err))?;
+    let mut fields = BTreeMap::new();
+    flatten("", &value, &mut fields);
+    return Ok(fields);
+}
+
+/// `+` added, `-` removed and `~` changed fields, one per line.
+pub fn compare_files(old_path: &str, new_path: &str) -> Result<String, String> {
+    return Ok(compare(&load(old_path)?, &load(new_path)?));
+}
+
+fn compare(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> String {
+    let mut output = String::new();
+    for (path, old_value) in old {
+        match new.get(path) {
+            None => output.push_str(&format!("- {}: {}\n", path, old_value)),
+            Some(new_value) if new_value != old_value => {
+                output.push_str(&format!("~ {}: {} -> {}\n", path, old_value, new_value));
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, new_value) in new {
+        if !old.contains_key(path) {
+            output.push_str(&format!("+ {}: {}\n", path, new_value));
+        }
+    }
+    if output.is_empty() {
+        output.push_str("No differences.\n");
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(json: &str) -> BTreeMap<String, String> {
+        let mut fields = BTreeMap::new();
+        flatten("", &serde_json::from_str(json).unwrap(), &mut fields);
+        return fields;
+    }
+
+    #[test]
+    fn nested_values_flatten_to_paths_without_volatile_ones() {
+        let flat = fields(r#"{"os": "Arch", "uptime": 3600, "model": null, "gpu": [{"gpu_name": "RTX 4070", "vram_used_mb": 812}]}"#);
+        let expected = [("gpu.0.gpu_name", "RTX 4070"), ("os", "Arch")];
+        assert_eq!(flat, expected.map(|(path, value)| (path.to_string(), value.to_string())).into());
+    }
+
+    #[test]
+    fn added_removed_and_changed_fields() {
+        let old = fields(r#"{"kernel": "6.8.0", "swap_total_mb": 2048, "uptime": 60}"#);
+        let new = fields(r#"{"kernel": "6.9.0", "gpu": [{"gpu_name": "Arc A770"}], "uptime": 7200}"#);
+        assert_eq!(compare(&old, &new), "~ kernel: 6.8.0 -> 6.9.0\n- swap_total_mb: 2048\n+ gpu.0.gpu_name: Arc A770\n");
+        assert_eq!(compare(&old, &old), "No differences.\n");
+    }
+
+    #[test]
+    fn unreadable_files_name_the_path() {
+        let err = compare_files("/nonexistent/old.json", "/nonexistent/new.json").unwrap_err();
+        assert!(err.starts_with("/nonexistent/old.json: "));
+    }
+}
@@ -0,0 +1,496 @@
+use crate::color::Rgb;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+/// How the memory and swap lines present usage.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryDisplay {
+    #[default]
+    Used,
+    Free,
+    Both,
+}
+
+/// How much of the adapter's name the GPU lines keep. JSON always has the
+/// raw name.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuNameStyle {
+    /// As the driver reports it.
+    Raw,
+    /// Without trademark marks and OpenGL's `/PCIe/SSE2` suffix.
+    #[default]
+    Light,
+    /// Also without the vendor and a trailing `GPU`.
+    Short,
+}
+
+/// A command whose output is shown as an extra line.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtraCommand {
+    pub label: String,
+    /// Run through `sh -c` (`cmd /C` on Windows); the first line of stdout
+    /// is shown.
+    pub command: String,
+    /// Pass the command's ANSI escape sequences through instead of stripping
+    /// them. They're not counted correctly when truncating to the width.
+    #[serde(default)]
+    pub keep_ansi: bool,
+}
+
+/// Usage percentages at which memory, swap and disk figures turn yellow
+/// (`warn`) or red (`critical`) in colored output.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub critical: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Thresholds {
+        return Thresholds { warn: 80.0, critical: 95.0 };
+    }
+}
+
+/// Where CPU usage is measured.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CpuUsageSource {
+    /// sysinfo's own refresh, on every platform.
+    #[default]
+    Sysinfo,
+    /// Deltas of `/proc/stat` jiffies, as `top` computes them. Linux only;
+    /// other platforms use sysinfo.
+    ProcStat,
+}
+
+/// What the "used" memory figure is based on.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryBasis {
+    /// sysinfo's `used_memory`.
+    #[default]
+    Used,
+    /// `total - available`, the figure `free -h` reports as used.
+    Available,
+}
+
+/// A fixed unit for a size, instead of the largest one that keeps the number
+/// at least 1. All of them are powers of 1024, like the automatic units;
+/// `mib`/`gib`/`tib` only spell the label the IEC way.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnit {
+    Mb,
+    Gb,
+    Tb,
+    Mib,
+    Gib,
+    Tib,
+}
+
+impl SizeUnit {
+    pub fn bytes(self) -> u64 {
+        return match self {
+            SizeUnit::Mb | SizeUnit::Mib => 1 << 20,
+            SizeUnit::Gb | SizeUnit::Gib => 1 << 30,
+            SizeUnit::Tb | SizeUnit::Tib => 1 << 40,
+        };
+    }
+
+    pub fn suffix(self) -> &'static str {
+        return match self {
+            SizeUnit::Mb => "MB",
+            SizeUnit::Gb => "GB",
+            SizeUnit::Tb => "TB",
+            SizeUnit::Mib => "MiB",
+            SizeUnit::Gib => "GiB",
+            SizeUnit::Tib => "TiB",
+        };
+    }
+}
+
+/// The `[units]` table: a unit per size field. Fields left out keep picking
+/// their unit automatically.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Units {
+    pub memory: Option<SizeUnit>,
+    pub swap: Option<SizeUnit>,
+    pub disk: Option<SizeUnit>,
+    pub vram: Option<SizeUnit>,
+}
+
+/// The `[cache_ttl]` table: seconds each cacheable field is kept with
+/// `cache = true`; 0 turns caching off for it. Fields left out keep their
+/// default TTL.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheTtl {
+    pub os: Option<u64>,
+    pub model: Option<u64>,
+    pub serial: Option<u64>,
+    pub chassis: Option<u64>,
+    pub kernel: Option<u64>,
+    pub gpu: Option<u64>,
+}
+
+impl CacheTtl {
+    /// The configured TTL of the field called `name`.
+    pub fn seconds(&self, name: &str) -> Option<u64> {
+        return match name {
+            "os" => self.os,
+            "model" => self.model,
+            "serial" => self.serial,
+            "chassis" => self.chassis,
+            "kernel" => self.kernel,
+            "gpu" => self.gpu,
+            _ => None,
+        };
+    }
+}
+
+/// Where the memory figures come from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemorySource {
+    /// sysinfo, on every platform, with `memory_basis` deciding what "used" is.
+    #[default]
+    Sysinfo,
+    /// `MemTotal - MemAvailable` straight from `/proc/meminfo`, exactly what
+    /// `free -h` shows. Linux only; other platforms use sysinfo.
+    ProcMeminfo,
+}
+
+/// Where the `user@host` header and its underline go.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderPosition {
+    #[default]
+    Top,
+    Bottom,
+    None,
+}
+
+impl std::str::FromStr for HeaderPosition {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "top" => Ok(HeaderPosition::Top),
+            "bottom" => Ok(HeaderPosition::Bottom),
+            "none" => Ok(HeaderPosition::None),
+            _ => Err(format!("unknown header position `{}` (expected top, bottom or none)", value)),
+        };
+    }
+}
+
+/// Unit of the CPU clock speeds on the `CPU` line.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrequencyUnit {
+    /// GHz from 1000 MHz up, MHz below, for low-clocked embedded boards.
+    #[default]
+    Auto,
+    Mhz,
+    Ghz,
+}
+
+impl std::str::FromStr for FrequencyUnit {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "auto" => Ok(FrequencyUnit::Auto),
+            "mhz" => Ok(FrequencyUnit::Mhz),
+            "ghz" => Ok(FrequencyUnit::Ghz),
+            _ => Err(format!("unknown frequency unit `{}` (expected auto, mhz or ghz)", value)),
+        };
+    }
+}
+
+/// Settings read from `<config dir>/ashwin-fetch/config.toml`. Every key is
+/// optional; a missing file behaves like an empty one.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Shown as the OS instead of the detected name.
+    pub os_name: Option<String>,
+    /// Text file with ASCII art to show instead of the built-in logo.
+    pub logo_file: Option<PathBuf>,
+    /// Field list, one name per line, like `--fields-file`.
+    pub fields_file: Option<PathBuf>,
+    /// Two `#rrggbb` colors the logo fades between, top to bottom.
+    pub logo_gradient: Option<Vec<String>>,
+    /// Text file laid over the logo whose digits pick a color for the
+    /// character at the same position.
+    pub logo_mask: Option<PathBuf>,
+    /// `#rrggbb` colors for the mask digits 1, 2, …. Defaults to the basic
+    /// terminal colors red through white.
+    pub logo_mask_colors: Option<Vec<String>>,
+    /// Spaces between the logo and the info lines. Defaults to 4.
+    pub gap: Option<usize>,
+    /// `top` (default), `bottom` or `none`; `--header-position` overrides it.
+    pub header_position: Option<HeaderPosition>,
+    /// Character repeated under the header, e.g. `"─"` or `"="`. Defaults
+    /// to `-`, which `--ascii-only` always uses.
+    pub header_separator: Option<String>,
+    /// `#rrggbb` color of the header underline; uncolored when unset.
+    pub header_separator_color: Option<String>,
+    /// `#rrggbb` colors of the field labels and values; the `--theme` colors
+    /// (or none) when unset.
+    pub label_color: Option<String>,
+    pub value_color: Option<String>,
+    /// Column at which field values start. Computed from the labels when unset.
+    pub label_width: Option<usize>,
+    /// Label (or part of one) of the sensor to report as the CPU temperature,
+    /// e.g. `"Package id 0"`. Picked automatically when unset.
+    pub cpu_temp_sensor: Option<String>,
+    /// Whether the memory and swap lines show the used, free or both
+    /// figures.
+    pub memory_display: MemoryDisplay,
+    pub memory_basis: MemoryBasis,
+    pub memory_source: MemorySource,
+    /// One `Mem/Swap` line instead of separate memory and swap lines, like
+    /// `--memory-swap-combined`.
+    pub memory_swap_combined: bool,
+    pub cpu_usage_source: CpuUsageSource,
+    /// `auto` (default), `mhz` or `ghz`; `--frequency-unit` overrides it.
+    pub frequency_unit: Option<FrequencyUnit>,
+    /// Decimals of GHz figures, 0 to 3. Defaults to 2; MHz are whole.
+    pub frequency_decimals: Option<usize>,
+    /// Number the `GPU` labels from 0 (default) or 1. JSON keeps 0-based
+    /// indices either way.
+    pub gpu_index_base: Option<usize>,
+    pub gpu_name_style: GpuNameStyle,
+    /// How long optional collectors (GPUs, sensors, `[[extra]]` commands)
+    /// may take, in milliseconds, before they're skipped.
+    pub collector_timeout_ms: Option<u64>,
+    /// Temperatures the `--watch` sparklines span, one per tick; 0 turns
+    /// them off. Defaults to 10.
+    pub temp_history: Option<usize>,
+    /// Mount point globs (`*`, `?`) of the disks to show, e.g. `["/", "/home"]`.
+    /// Every physical disk is shown when empty.
+    pub disk_include: Vec<String>,
+    pub thresholds: Thresholds,
+    pub units: Units,
+    /// Binaries `--show-toolchains` asks for their version. Defaults to
+    /// rustc, python3, node, go and java.
+    pub toolchains: Option<Vec<String>>,
+    /// Show the git repository of the current directory, like `--show-git`.
+    pub show_git: bool,
+    /// Append the boot time to the relative uptime, like `--uptime-since`.
+    pub uptime_since: bool,
+    /// Print how long collection took after the output, like `--timings`.
+    pub show_timings: bool,
+    /// Keep slow, static fields (OS, model, serial, chassis, kernel, GPUs)
+    /// on disk between runs, like `--cache`.
+    pub cache: bool,
+    pub cache_ttl: CacheTtl,
+    pub extra: Vec<ExtraCommand>,
+}
+
+impl Config {
+    pub fn logo_mask_colors(&self) -> Option<Vec<Rgb>> {
+        return self.logo_mask_colors.as_ref()?.iter().map(|color| Rgb::parse_hex(color)).collect();
+    }
+
+    pub fn header_separator_color(&self) -> Option<Rgb> {
+        return Rgb::parse_hex(self.header_separator_color.as_deref()?);
+    }
+
+    pub fn label_color(&self) -> Option<Rgb> {
+        return Rgb::parse_hex(self.label_color.as_deref()?);
+    }
+
+    pub fn value_color(&self) -> Option<Rgb> {
+        return Rgb::parse_hex(self.value_color.as_deref()?);
+    }
+
+    pub fn logo_gradient(&self) -> Option<(Rgb, Rgb)> {
+        let colors = self.logo_gradient.as_ref()?;
+        return Some((Rgb::parse_hex(&colors[0])?, Rgb::parse_hex(&colors[1])?));
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(colors) = &self.logo_gradient {
+            if colors.len() != 2 {
+                return Err(format!("logo_gradient expects 2 colors, got {}", colors.len()));
+            }
+            for color in colors {
+                if Rgb::parse_hex(color).is_none() {
+                    return Err(format!("logo_gradient: `{}` is not a #rrggbb color", color));
+                }
+            }
+        }
+        for color in self.logo_mask_colors.iter().flatten() {
+            if Rgb::parse_hex(color).is_none() {
+                return Err(format!("logo_mask_colors: `{}` is not a #rrggbb color", color));
+            }
+        }
+        if let Some(separator) = self.header_separator.as_ref().filter(|separator| separator.chars().count() != 1 || separator.width() == 0) {
+            return Err(format!("header_separator must be a single visible character, got `{}`", separator));
+        }
+        if let Some(color) = self.header_separator_color.as_ref().filter(|color| Rgb::parse_hex(color).is_none()) {
+            return Err(format!("header_separator_color: `{}` is not a #rrggbb color", color));
+        }
+        for (key, color) in [("label_color", &self.label_color), ("value_color", &self.value_color)] {
+            if let Some(color) = color.as_ref().filter(|color| Rgb::parse_hex(color).is_none()) {
+                return Err(format!("{}: `{}` is not a #rrggbb color", key, color));
+            }
+        }
+        let Thresholds { warn, critical } = self.thresholds;
+        if !(0.0..=100.0).contains(&warn) || !(0.0..=100.0).contains(&critical) || warn > critical {
+            return Err(format!(
+                "thresholds: warn ({}) and critical ({}) must be percentages with warn <= critical",
+                warn, critical
+            ));
+        }
+        if let Some(decimals) = self.frequency_decimals.filter(|decimals| *decimals > 3) {
+            return Err(format!("frequency_decimals must be 0 to 3, got {}", decimals));
+        }
+        if let Some(base) = self.gpu_index_base.filter(|base| *base > 1) {
+            return Err(format!("gpu_index_base must be 0 or 1, got {}", base));
+        }
+        return Ok(());
+    }
+}
+
+/// The comment `--dump-config` puts above each key, and an example for keys
+/// that are unset. Plain keys come first: TOML puts every key after a table
+/// header into that table.
+const KEY_DOCS: [(&str, &str, &str); 35] = [
+    ("os_name", "Shown as the OS instead of the detected name.", "\"Arch Linux\""),
+    ("logo_file", "Text file with ASCII art to show instead of the built-in logo.", "\"~/.config/ashwin-fetch/logo.txt\""),
+    ("fields_file", "File listing the fields to show, one per line, like --fields-file.", "\"~/.config/ashwin-fetch/fields.txt\""),
+    ("logo_gradient", "Two #rrggbb colors the logo fades between, top to bottom.", "[\"#ff5f6d\", \"#ffc371\"]"),
+    ("logo_mask", "Text file over the logo whose digits 1-9 color the character at the same position.", "\"~/.config/ashwin-fetch/logo-mask.txt\""),
+    ("logo_mask_colors", "#rrggbb colors for the mask digits 1, 2, ...; basic red through white when unset.", "[\"#e01b24\", \"#1c71d8\"]"),
+    ("gap", "Spaces between the logo and the info lines.", "4"),
+    ("header_position", "Where the user@host header goes: top, bottom or none.", "\"top\""),
+    ("header_separator", "Character repeated under the header; --ascii-only always uses -.", "\"─\""),
+    ("header_separator_color", "#rrggbb color of the header underline.", "\"#5f87ff\""),
+    ("label_color", "#rrggbb color of the field labels; the --theme color when unset.", "\"#5fafff\""),
+    ("value_color", "#rrggbb color of the field values; the --theme color when unset.", "\"#e4e4e4\""),
+    ("label_width", "Column at which field values start; computed from the labels when unset.", "12"),
+    ("cpu_temp_sensor", "Label (or part of one) of the sensor to report as the CPU temperature.", "\"Package id 0\""),
+    ("memory_display", "How memory and swap usage is shown: used, free or both.", "\"used\""),
+    ("memory_basis", "What \"used\" memory means: used, or available (total minus available).", "\"used\""),
+    ("memory_source", "Where memory figures come from: sysinfo or proc-meminfo (Linux).", "\"sysinfo\""),
+    ("memory_swap_combined", "Show memory and swap on one Mem/Swap line, like --memory-swap-combined.", "false"),
+    ("cpu_usage_source", "Where CPU usage is measured: sysinfo or proc-stat (Linux).", "\"sysinfo\""),
+    ("frequency_unit", "Unit of the CPU clock speeds: auto (GHz from 1000 MHz up), mhz or ghz.", "\"auto\""),
+    ("frequency_decimals", "Decimals of GHz clock speeds, 0 to 3; MHz are whole.", "2"),
+    ("gpu_index_base", "Number the GPU labels from 0 or 1.", "0"),
+    ("gpu_name_style", "How much of the GPU name to keep: raw, light (no trademark marks) or short (no vendor).", "\"light\""),
+    ("collector_timeout_ms", "How long each optional collector may take, in milliseconds, before it's skipped.", "1000"),
+    ("temp_history", "Ticks of CPU and GPU temperature --watch shows as a sparkline; 0 turns it off.", "10"),
+    ("disk_include", "Mount point globs of the disks to show; every physical disk when empty.", "[\"/\", \"/home\"]"),
+    ("toolchains", "Binaries --show-toolchains asks for their version.", "[\"rustc\", \"python3\"]"),
+    ("show_git", "Show the git repository of the current directory, like --show-git.", "false"),
+    ("uptime_since", "Append the boot time to the uptime, like --uptime-since.", "false"),
+    ("show_timings", "Print how long collection took after the output, like --timings.", "false"),
+    ("cache", "Keep slow, static fields (OS, model, serial, chassis, kernel, GPUs) on disk between runs, like --cache.", "false"),
+    ("thresholds", "Usage percentages at which memory, swap and disk figures turn yellow and red.", "[thresholds]\nwarn = 80.0\ncritical = 95.0"),
+    ("units", "A fixed unit (mb, gb, tb, mib, gib, tib) per size field instead of the best fit.", "[units]\nmemory = \"gib\""),
+    ("cache_ttl", "Seconds each cached field is kept (0: never cached); entries also expire on reboot.", "[cache_ttl]\ngpu = 86400"),
+    ("extra", "Commands whose first line of output is shown as an extra line.", "[[extra]]\nlabel = \"Docker\"\ncommand = \"docker --version\""),
+];
+
+pub fn config_path() -> Option<PathBuf> {
+    return dirs::config_dir().map(|dir| dir.join("ashwin-fetch").join("config.toml"));
+}
+
+/// `--dump-config`: `config` as TOML with a comment above each key. Unset
+/// keys are listed commented out, with an example value.
+pub fn dump_config(config: &Config) -> String {
+    let table = toml::Table::try_from(config).expect("Config always serializes");
+    let mut output = String::new();
+    for (key, doc, example) in KEY_DOCS {
+        output.push_str(&format!("# {}\n", doc));
+        // An empty `[units]` or `extra = []` says less than the example.
+        let value = table.get(key).filter(|value| match value {
+            toml::Value::Table(entries) => !entries.is_empty(),
+            toml::Value::Array(items) => !items.is_empty() || key != "extra",
+            _ => true,
+        });
+        match value {
+            Some(value) => {
+                let mut entry = toml::Table::new();
+                entry.insert(key.to_string(), value.clone());
+                output.push_str(&toml::to_string(&entry).expect("config values always serialize"));
+            }
+            None if example.starts_with('[') && example.contains('\n') => {
+                for line in example.lines() {
+                    output.push_str(&format!("# {}\n", line));
+                }
+            }
+            None => output.push_str(&format!("# {} = {}\n", key, example)),
+        }
+        output.push('\n');
+    }
+    return output;
+}
+
+/// `--show-paths`: where the tool looks for its files, and whether each
+/// exists. The config is only read for `logo_file`, so a broken one is
+/// still located.
+pub fn show_paths(profile: Option<&str>) -> String {
+    let describe = |label: &str, path: Option<PathBuf>| match path {
+        Some(path) => format!(
+            "{:<17}{} ({})\n",
+            format!("{}:", label),
+            path.display(),
+            if path.exists() { "exists" } else { "missing" }
+        ),
+        None => format!("{:<17}unknown on this platform\n", format!("{}:", label)),
+    };
+    let mut output = describe("Home", dirs::home_dir());
+    output.push_str(&describe("Config dir", dirs::config_dir().map(|dir| dir.join("ashwin-fetch"))));
+    output.push_str(&describe("Config file", config_path()));
+    match load_config(profile).ok().and_then(|config| config.logo_file) {
+        Some(logo_file) => output.push_str(&describe("Logo file", Some(logo_file))),
+        None => output.push_str(&format!("{:<17}not set\n", "Logo file:")),
+    }
+    output.push_str(&describe("Field cache", crate::cache::cache_path()));
+    #[cfg(target_os = "linux")]
+    let pending_updates = crate::linux::pending_updates_cache_path();
+    #[cfg(not(target_os = "linux"))]
+    let pending_updates = None;
+    output.push_str(&describe("Pending updates", pending_updates));
+    output.push_str(&describe("Update check", crate::update::cache_path()));
+    return output;
+}
+
+/// Reads the config, with the `[profile.NAME]` table's keys replacing the
+/// base ones when a profile is given.
+pub fn load_config(profile: Option<&str>) -> Result<Config, String> {
+    let Some(path) = config_path() else {
+        return Ok(Config::default());
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && profile.is_none() => return Ok(Config::default()),
+        Err(err) => return Err(format!("{}: {}", path.display(), err)),
+    };
+    let mut table: toml::Table = toml::from_str(&contents).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let profiles = match table.remove("profile") {
+        Some(toml::Value::Table(profiles)) => profiles,
+        Some(_) => return Err(format!("{}: `profile` must be a table of [profile.NAME] sections", path.display())),
+        None => toml::Table::new(),
+    };
+    if let Some(name) = profile {
+        let Some(toml::Value::Table(overrides)) = profiles.get(name) else {
+            return Err(format!("{}: no [profile.{}] section", path.display(), name));
+        };
+        table.extend(overrides.clone());
+    }
+    let config: Config = table.try_into().map_err(|err| format!("{}: {}", path.display(), err))?;
+    config.validate().map_err(|err| format!("{}: {}", path.display(), err))?;
+    return Ok(config);
+}
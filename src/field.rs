@@ -0,0 +1,451 @@
+//! The names fields go by in `--fields`, `--fields-file` and
+//! `--fields-json`, so they share one vocabulary and one set of aliases.
+
+use serde::Serialize;
+use std::path::Path;
+
+/// Every displayable field, in default display order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Os,
+    Model,
+    Serial,
+    Chassis,
+    Firmware,
+    Kernel,
+    Cmdline,
+    Updated,
+    Updates,
+    Uptime,
+    FailedUnits,
+    Threads,
+    Fds,
+    Entropy,
+    ShellPid,
+    Session,
+    Users,
+    Top,
+    Cpu,
+    CpuTemp,
+    Cache,
+    Topology,
+    Virt,
+    Governor,
+    Gpu,
+    Vram,
+    GpuTemp,
+    Displays,
+    Audio,
+    Printer,
+    Ip,
+    Gateway,
+    Wifi,
+    Battery,
+    Power,
+    Memory,
+    Swap,
+    Pressure,
+    Toolchains,
+    Repo,
+    Disk,
+    Inodes,
+    Encryption,
+    Trim,
+    /// `--show-env` variables and `[[extra]]` command lines.
+    Extra,
+}
+
+impl Field {
+    pub const ALL: [Field; 45] = [
+        Field::Os,
+        Field::Model,
+        Field::Serial,
+        Field::Chassis,
+        Field::Firmware,
+        Field::Kernel,
+        Field::Cmdline,
+        Field::Updated,
+        Field::Updates,
+        Field::Uptime,
+        Field::FailedUnits,
+        Field::Threads,
+        Field::Fds,
+        Field::Entropy,
+        Field::ShellPid,
+        Field::Session,
+        Field::Users,
+        Field::Top,
+        Field::Cpu,
+        Field::CpuTemp,
+        Field::Cache,
+        Field::Topology,
+        Field::Virt,
+        Field::Governor,
+        Field::Gpu,
+        Field::Vram,
+        Field::GpuTemp,
+        Field::Displays,
+        Field::Audio,
+        Field::Printer,
+        Field::Ip,
+        Field::Gateway,
+        Field::Wifi,
+        Field::Battery,
+        Field::Power,
+        Field::Memory,
+        Field::Swap,
+        Field::Pressure,
+        Field::Toolchains,
+        Field::Repo,
+        Field::Disk,
+        Field::Inodes,
+        Field::Encryption,
+        Field::Trim,
+        Field::Extra,
+    ];
+
+    /// Canonical name, as accepted by `--fields`.
+    pub fn name(self) -> &'static str {
+        return match self {
+            Field::Os => "os",
+            Field::Model => "model",
+            Field::Serial => "serial",
+            Field::Chassis => "chassis",
+            Field::Firmware => "firmware",
+            Field::Kernel => "kernel",
+            Field::Cmdline => "cmdline",
+            Field::Updated => "updated",
+            Field::Updates => "updates",
+            Field::Uptime => "uptime",
+            Field::FailedUnits => "failed-units",
+            Field::Threads => "threads",
+            Field::Fds => "fds",
+            Field::Entropy => "entropy",
+            Field::ShellPid => "shell-pid",
+            Field::Session => "session",
+            Field::Users => "users",
+            Field::Top => "top",
+            Field::Cpu => "cpu",
+            Field::CpuTemp => "cpu-temp",
+            Field::Cache => "cache",
+            Field::Topology => "topology",
+            Field::Virt => "virt",
+            Field::Governor => "governor",
+            Field::Gpu => "gpu",
+            Field::Vram => "vram",
+            Field::GpuTemp => "gpu-temp",
+            Field::Displays => "displays",
+            Field::Audio => "audio",
+            Field::Printer => "printer",
+            Field::Ip => "ip",
+            Field::Gateway => "gateway",
+            Field::Wifi => "wifi",
+            Field::Battery => "battery",
+            Field::Power => "power-source",
+            Field::Memory => "memory",
+            Field::Swap => "swap",
+            Field::Pressure => "pressure",
+            Field::Toolchains => "toolchains",
+            Field::Repo => "repo",
+            Field::Disk => "disk",
+            Field::Inodes => "inodes",
+            Field::Encryption => "encryption",
+            Field::Trim => "trim",
+            Field::Extra => "extra",
+        };
+    }
+
+    /// Label in the text output.
+    pub fn label(self) -> &'static str {
+        return match self {
+            Field::Os => "OS",
+            Field::Model => "Model",
+            Field::Serial => "Serial",
+            Field::Chassis => "Chassis",
+            Field::Firmware => "Boot",
+            Field::Kernel => "Kernel",
+            Field::Cmdline => "Cmdline",
+            Field::Updated => "Updated",
+            Field::Updates => "Updates",
+            Field::Uptime => "Uptime",
+            Field::FailedUnits => "Failed",
+            Field::Threads => "Threads",
+            Field::Fds => "FDs",
+            Field::Entropy => "Entropy",
+            Field::ShellPid => "Shell PID",
+            Field::Session => "Session",
+            Field::Users => "Users",
+            Field::Top => "Top",
+            Field::Cpu => "CPU",
+            Field::CpuTemp => "CPU Temp",
+            Field::Cache => "Cache",
+            Field::Topology => "Topology",
+            Field::Virt => "Virt",
+            Field::Governor => "Governor",
+            Field::Gpu => "GPU",
+            Field::Vram => "VRAM",
+            Field::GpuTemp => "GPU Temp",
+            Field::Displays => "Displays",
+            Field::Audio => "Audio",
+            Field::Printer => "Printer",
+            Field::Ip => "IP",
+            Field::Gateway => "Gateway",
+            Field::Wifi => "WiFi",
+            Field::Battery => "Battery",
+            Field::Power => "Power",
+            Field::Memory => "Memory",
+            Field::Swap => "Swap",
+            Field::Pressure => "Pressure",
+            Field::Toolchains => "Toolchains",
+            Field::Repo => "Repo",
+            Field::Disk => "Disk",
+            Field::Inodes => "Inodes",
+            Field::Encryption => "Encryption",
+            Field::Trim => "TRIM",
+            Field::Extra => "Extra",
+        };
+    }
+}
+
+impl Field {
+    /// One-line summary for `--fields-json`.
+    pub fn description(self) -> &'static str {
+        return match self {
+            Field::Os => "Operating system name and version",
+            Field::Model => "Hardware model name",
+            Field::Serial => "Motherboard serial number",
+            Field::Chassis => "Chassis type from DMI/SMBIOS, or the hypervisor in a VM",
+            Field::Firmware => "Firmware boot mode (UEFI or legacy BIOS) and Secure Boot state",
+            Field::Kernel => "Kernel version, optionally with its build date and newer installed kernels",
+            Field::Cmdline => "Kernel command line from /proc/cmdline, cut to the terminal width",
+            Field::Updated => "Time since the last package update",
+            Field::Updates => "Pending package updates (apt, dnf or pacman) and how many are security fixes",
+            Field::Uptime => "Time since boot",
+            Field::FailedUnits => "Number of failed systemd units",
+            Field::Threads => "Running threads and zombie processes",
+            Field::Fds => "Open file descriptors against the system-wide limit",
+            Field::Entropy => "Bits in the kernel entropy pool",
+            Field::ShellPid => "PID and name of the process that started ashwin-fetch",
+            Field::Session => "Session type (SSH, RDP, desktop or console) and whether the user is root/admin",
+            Field::Users => "Number of login sessions, with their users at -v",
+            Field::Top => "The processes using the most CPU and the most memory",
+            Field::Cpu => "CPU model, core count, usage and frequency",
+            Field::CpuTemp => "CPU temperature",
+            Field::Cache => "CPU cache sizes per level",
+            Field::Topology => "CPU sockets, NUMA nodes and physical cores",
+            Field::Virt => "Hardware virtualization support (VT-x or AMD-V)",
+            Field::Governor => "cpufreq governor and power profile",
+            Field::Gpu => "Graphics adapters",
+            Field::Vram => "Total VRAM across GPUs",
+            Field::GpuTemp => "GPU temperature",
+            Field::Displays => "Connected monitors and their resolutions",
+            Field::Audio => "Sound server or card",
+            Field::Printer => "Default printer",
+            Field::Ip => "Primary IP address",
+            Field::Gateway => "Default gateway",
+            Field::Wifi => "Connected Wi-Fi network and signal strength",
+            Field::Battery => "Battery charge, health and cycle count",
+            Field::Power => "Whether a laptop runs on AC or battery, and the adapter wattage",
+            Field::Memory => "Used and total memory",
+            Field::Swap => "Used and total swap",
+            Field::Pressure => "Memory pressure",
+            Field::Toolchains => "Installed developer toolchain versions",
+            Field::Repo => "Git repository of the current directory, its branch and upstream ahead/behind counts",
+            Field::Disk => "Used and total space per disk",
+            Field::Inodes => "Used and total inodes on the root filesystem",
+            Field::Encryption => "Whether the root filesystem is encrypted (LUKS, FileVault, BitLocker)",
+            Field::Trim => "Whether periodic SSD TRIM (fstrim.timer) is enabled, and its schedule",
+            Field::Extra => "Environment variables from --show-env and [[extra]] command lines",
+        };
+    }
+
+    /// The flag that turns an opt-in field on; `None` for fields shown by
+    /// default.
+    pub fn opt_in_flag(self) -> Option<&'static str> {
+        return match self {
+            Field::Firmware => Some("--show-firmware"),
+            Field::Cmdline => Some("--show-cmdline"),
+            Field::Updated => Some("--show-updated"),
+            Field::Updates => Some("--show-updates"),
+            Field::FailedUnits => Some("--show-failed-units"),
+            Field::Threads => Some("--show-threads"),
+            Field::Fds => Some("--show-fds"),
+            Field::Entropy => Some("--show-entropy"),
+            Field::ShellPid => Some("--show-pid"),
+            Field::Session => Some("--show-session"),
+            Field::Users => Some("--show-users"),
+            Field::Top => Some("--show-top"),
+            Field::Cache => Some("--show-cache"),
+            Field::Topology => Some("--show-topology"),
+            Field::Virt => Some("--show-virt"),
+            Field::Governor => Some("--show-governor"),
+            Field::GpuTemp => Some("--show-gpu-temp"),
+            Field::Printer => Some("--show-printer"),
+            Field::Ip => Some("--show-ip"),
+            Field::Gateway => Some("--show-network"),
+            Field::Wifi => Some("--show-wifi"),
+            Field::Power => Some("--show-power"),
+            Field::Pressure => Some("--show-pressure"),
+            Field::Toolchains => Some("--show-toolchains"),
+            Field::Repo => Some("--show-git"),
+            Field::Swap => Some("--show-swap"),
+            Field::Inodes => Some("--show-inodes"),
+            Field::Encryption => Some("--show-encryption"),
+            Field::Trim => Some("--show-storage-health"),
+            Field::Extra => Some("--show-env"),
+            _ => None,
+        };
+    }
+
+    /// The platforms a field can be read on; empty when it's read everywhere.
+    pub fn platforms(self) -> &'static [&'static str] {
+        return match self {
+            Field::Model => &["macos"],
+            Field::Chassis | Field::Firmware => &["linux", "windows"],
+            Field::Updated | Field::Updates | Field::Cmdline | Field::FailedUnits | Field::Threads | Field::Fds | Field::Entropy | Field::Topology | Field::Governor | Field::Audio | Field::Gateway | Field::Trim => &["linux"],
+            Field::Printer | Field::Pressure | Field::Power | Field::Users | Field::Inodes => &["linux", "macos"],
+            _ => &[],
+        };
+    }
+}
+
+/// One entry of the field registry printed by `--fields-json`.
+#[derive(Debug, Serialize)]
+pub struct FieldEntry {
+    name: &'static str,
+    label: &'static str,
+    description: &'static str,
+    default_shown: bool,
+    opt_in_flag: Option<&'static str>,
+    platform_specific: bool,
+    platforms: &'static [&'static str],
+}
+
+/// Every field, in default display order.
+pub fn registry() -> Vec<FieldEntry> {
+    return Field::ALL
+        .into_iter()
+        .map(|field| FieldEntry {
+            name: field.name(),
+            label: field.label(),
+            description: field.description(),
+            default_shown: field.opt_in_flag().is_none(),
+            opt_in_flag: field.opt_in_flag(),
+            platform_specific: !field.platforms().is_empty(),
+            platforms: field.platforms(),
+        })
+        .collect();
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.write_str(self.name());
+    }
+}
+
+impl std::str::FromStr for Field {
+    type Err = String;
+
+    /// Canonical names plus a few aliases, ignoring case; `_` and `-` are
+    /// interchangeable.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized = value.trim().to_ascii_lowercase().replace('_', "-");
+        let alias = match normalized.as_str() {
+            "temp" | "temperature" | "cpu-temperature" => Some(Field::CpuTemp),
+            "gpu-temperature" => Some(Field::GpuTemp),
+            "display" | "resolution" | "monitors" => Some(Field::Displays),
+            "ram" | "mem" => Some(Field::Memory),
+            "env" => Some(Field::Extra),
+            "failed" | "units" => Some(Field::FailedUnits),
+            "cpu-cache" | "caches" => Some(Field::Cache),
+            "virtualization" => Some(Field::Virt),
+            "numa" | "sockets" => Some(Field::Topology),
+            "power-profile" => Some(Field::Governor),
+            "zombies" => Some(Field::Threads),
+            "boot" | "uefi" | "secure-boot" => Some(Field::Firmware),
+            "file-descriptors" | "handles" => Some(Field::Fds),
+            "random" | "entropy-avail" => Some(Field::Entropy),
+            "pending" | "upgrades" | "upgradable" => Some(Field::Updates),
+            "kernel-cmdline" | "boot-params" => Some(Field::Cmdline),
+            "pid" | "parent" => Some(Field::ShellPid),
+            "ssh" | "root" => Some(Field::Session),
+            "logins" | "who" => Some(Field::Users),
+            "top-process" | "processes" => Some(Field::Top),
+            "router" | "default-route" => Some(Field::Gateway),
+            "wi-fi" | "ssid" => Some(Field::Wifi),
+            "disks" | "storage" => Some(Field::Disk),
+            "inode" | "files" => Some(Field::Inodes),
+            "luks" | "filevault" | "bitlocker" => Some(Field::Encryption),
+            "fstrim" | "storage-health" => Some(Field::Trim),
+            "bat" | "power" => Some(Field::Battery),
+            "ac" | "adapter" => Some(Field::Power),
+            "memory-pressure" | "psi" => Some(Field::Pressure),
+            "toolchain" | "dev" => Some(Field::Toolchains),
+            "git" | "repository" => Some(Field::Repo),
+            _ => None,
+        };
+        return alias
+            .or_else(|| Field::ALL.into_iter().find(|field| field.name() == normalized))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Field::ALL.iter().map(|field| field.name()).collect();
+                format!("unknown field `{}` (expected one of {})", value, names.join(", "))
+            });
+    }
+}
+
+/// Curated `--fields-preset` layouts. Fields that aren't detected are still
+/// left out; opt-in ones are turned on by the preset.
+pub fn preset(name: &str) -> Result<Vec<Field>, String> {
+    use Field::*;
+    return match name {
+        "server" => Ok(vec![Os, Kernel, Uptime, FailedUnits, Threads, Cpu, Virt, Memory, Swap, Pressure, Disk, Ip, Gateway]),
+        "desktop" => Ok(vec![Os, Model, Kernel, Uptime, Cpu, CpuTemp, Gpu, Vram, Displays, Audio, Memory]),
+        "laptop" => Ok(vec![Os, Model, Chassis, Kernel, Uptime, Cpu, CpuTemp, Gpu, Displays, Audio, Battery, Memory, Ip]),
+        _ => Err(format!("unknown fields preset `{}` (expected desktop, server or laptop)", name)),
+    };
+}
+
+/// Parses a comma-separated field list such as `os,cpu,memory`.
+pub fn parse_field_list(list: &str) -> Result<Vec<Field>, String> {
+    return list
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| name.parse())
+        .collect();
+}
+
+/// A `--fields-file` layout: one field name per line, with blank lines and
+/// `#` comments ignored. Errors carry the line number.
+pub fn parse_field_file(contents: &str) -> Result<Vec<Field>, String> {
+    let mut fields = vec![];
+    for (idx, line) in contents.lines().enumerate() {
+        let name = line.split('#').next().unwrap_or("").trim();
+        if name.is_empty() {
+            continue;
+        }
+        fields.push(name.parse().map_err(|err| format!("line {}: {}", idx + 1, err))?);
+    }
+    if fields.is_empty() {
+        return Err(String::from("no fields listed"));
+    }
+    return Ok(fields);
+}
+
+pub fn load_field_file(path: &Path) -> Result<Vec<Field>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    return parse_field_file(&contents).map_err(|err| format!("{}: {}", path.display(), err));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_files_skip_comments_and_report_lines() {
+        let layout = "# status bar\nos\n\n  CPU  # with usage\nram\n";
+        assert_eq!(parse_field_file(layout), Ok(vec![Field::Os, Field::Cpu, Field::Memory]));
+        let err = parse_field_file("os\n# fine\ncpu-speed\n").unwrap_err();
+        assert!(err.starts_with("line 3: unknown field `cpu-speed`"), "{}", err);
+        assert!(parse_field_file("# nothing\n").is_err());
+    }
+}
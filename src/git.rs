@@ -0,0 +1,101 @@
+//! `--show-git`: the git repository the current directory is in, found by
+//! running `git`.
+
+use serde::Serialize;
+use std::fmt::Debug;
+use std::process::Command;
+use std::process::Stdio;
+
+/// Where the working directory's repository stands against its upstream.
+#[derive(Serialize)]
+pub struct RepoInfo {
+    /// Name of the top-level directory.
+    pub name: String,
+    /// The branch, or the short hash of a detached HEAD.
+    pub head: String,
+    pub detached: bool,
+    /// Commits on either side of the upstream; `None` without one.
+    pub ahead: Option<usize>,
+    pub behind: Option<usize>,
+}
+
+impl Debug for RepoInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RepoInfo")
+            .field("name", &self.name)
+            .field("head", &self.head)
+            .field("detached", &self.detached)
+            .field("ahead", &self.ahead)
+            .field("behind", &self.behind)
+            .finish()
+    }
+}
+
+/// The top-level directory's name and the branch from
+/// `git rev-parse --show-toplevel --abbrev-ref HEAD`, which says `HEAD`
+/// when detached.
+fn parse_rev_parse(output: &str) -> Option<(String, Option<String>)> {
+    let mut lines = output.lines().map(str::trim);
+    let toplevel = lines.next().filter(|line| !line.is_empty())?;
+    let branch = lines.next().filter(|line| !line.is_empty())?;
+    let name = toplevel.rsplit(['/', '\\']).find(|part| !part.is_empty()).unwrap_or(toplevel);
+    return Some((name.to_string(), if branch == "HEAD" { None } else { Some(branch.to_string()) }));
+}
+
+/// Ahead and behind from `git rev-list --left-right --count HEAD...@{upstream}`.
+fn parse_counts(output: &str) -> Option<(usize, usize)> {
+    let counts: Vec<usize> = output.split_whitespace().filter_map(|count| count.parse().ok()).collect();
+    return match counts.as_slice() {
+        [ahead, behind] => Some((*ahead, *behind)),
+        _ => None,
+    };
+}
+
+/// `git` with `args` in the current directory; its stdout when it succeeds.
+/// Optional locks are turned off so the query never contends with a git
+/// command running in the same repository.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return Some(String::from_utf8_lossy(&output.stdout).into_owned());
+}
+
+/// The repository the current directory is in, or `None` outside one or
+/// without git installed. A detached HEAD has no upstream to count against.
+pub fn get_repo_info() -> Option<RepoInfo> {
+    let (name, branch) = parse_rev_parse(&git(&["rev-parse", "--show-toplevel", "--abbrev-ref", "HEAD"])?)?;
+    let Some(branch) = branch else {
+        let commit = git(&["rev-parse", "--short", "HEAD"])?;
+        return Some(RepoInfo { name, head: commit.trim().to_string(), detached: true, ahead: None, behind: None });
+    };
+    let counts = git(&["rev-list", "--left-right", "--count", "HEAD...@{upstream}"]).and_then(|output| parse_counts(&output));
+    return Some(RepoInfo {
+        name,
+        head: branch,
+        detached: false,
+        ahead: counts.map(|(ahead, _)| ahead),
+        behind: counts.map(|(_, behind)| behind),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_name_branch_and_upstream_counts() {
+        assert_eq!(parse_rev_parse("/home/user/src/ashwin-fetch\nmain\n"), Some((String::from("ashwin-fetch"), Some(String::from("main")))));
+        assert_eq!(parse_rev_parse("C:/src/ashwin-fetch\nHEAD\n"), Some((String::from("ashwin-fetch"), None)));
+        assert_eq!(parse_rev_parse(""), None);
+        assert_eq!(parse_counts("3\t0\n"), Some((3, 0)));
+        assert_eq!(parse_counts(""), None);
+    }
+}
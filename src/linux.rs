@@ -0,0 +1,1257 @@
+//! Readers for Linux-only sources under `/proc`, `/sys` and `/var`.
+
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::path::Path;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Package databases whose mtime changes on every install/upgrade.
+const PACKAGE_DBS: [&str; 5] = [
+    "/var/lib/dpkg/status",
+    "/var/lib/pacman/local",
+    "/var/lib/rpm",
+    "/lib/apk/db/installed",
+    "/var/db/xbps",
+];
+
+/// Pulls the build date out of a `/proc/version` or `uname -v` string. Both
+/// the `Thu Jan 11 12:03:07 UTC 2024` (or `Thu, 11 Jan 2024`) and Debian's
+/// `(2024-02-01)` styles are understood; anything else yields `None`.
+pub fn parse_kernel_build_date(version: &str) -> Option<NaiveDate> {
+    let tokens: Vec<&str> = version
+        .split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ';' | ','))
+        .filter(|token| !token.is_empty())
+        .collect();
+    for (idx, token) in tokens.iter().enumerate().rev() {
+        if let Ok(date) = NaiveDate::parse_from_str(token, "%Y-%m-%d") {
+            return Some(date);
+        }
+        let Some(month) = MONTHS.iter().position(|month| month == token) else {
+            continue;
+        };
+        let year_from = |start: usize| {
+            return tokens
+                .iter()
+                .skip(start)
+                .take(3)
+                .find_map(|year| year.parse::<i32>().ok().filter(|year| *year >= 1991));
+        };
+        let day_at = |idx: Option<usize>| idx.and_then(|idx| tokens.get(idx)).and_then(|day| day.parse::<u32>().ok());
+        // `Mar 11 13:06:41 UTC 2024`, or RFC 2822's `15 Mar 2024` (Arch).
+        let candidates = [(day_at(Some(idx + 1)), year_from(idx + 2)), (day_at(idx.checked_sub(1)), year_from(idx + 1))];
+        if let Some(date) = candidates
+            .into_iter()
+            .find_map(|(day, year)| NaiveDate::from_ymd_opt(year?, month as u32 + 1, day?))
+        {
+            return Some(date);
+        }
+    }
+    return None;
+}
+
+/// From `/proc/version`, or `uname -v` where `/proc` isn't mounted.
+pub fn get_kernel_build_date() -> Option<NaiveDate> {
+    let version = std::fs::read_to_string("/proc/version").ok().or_else(|| {
+        let output = std::process::Command::new("uname").arg("-v").output().ok()?;
+        return Some(String::from_utf8_lossy(&output.stdout).into_owned());
+    })?;
+    return parse_kernel_build_date(&version);
+}
+
+/// Sort key for kernel releases: their numbers in order, so
+/// `6.10.0-1-generic` sorts after `6.9.12-3-generic`.
+fn kernel_release_key(release: &str) -> Vec<u64> {
+    return release
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+}
+
+/// What's left of a kernel release without its numbers, e.g. `..--generic`
+/// for `6.8.0-45-generic`: a `lowlatency` or `lts` kernel installed next to
+/// the usual one is an alternative, not an update.
+fn kernel_flavour(release: &str) -> String {
+    return release.chars().filter(|c| !c.is_ascii_digit()).collect();
+}
+
+/// The newest kernel release in `installed` of the same flavour as
+/// `running`, when it's newer.
+fn newer_kernel_release(running: &str, installed: &[String]) -> Option<String> {
+    let newest = installed
+        .iter()
+        .filter(|release| kernel_flavour(release) == kernel_flavour(running))
+        .max_by_key(|release| kernel_release_key(release))?;
+    return if kernel_release_key(newest) > kernel_release_key(running) { Some(newest.clone()) } else { None };
+}
+
+/// The newest installed kernel (one with a `/lib/modules` directory) when
+/// it's newer than the running one, meaning a reboot would switch to it.
+pub fn get_newer_installed_kernel() -> Option<String> {
+    let running = std::fs::read_to_string("/proc/sys/kernel/osrelease").ok()?;
+    let installed: Vec<String> = ["/usr/lib/modules", "/lib/modules"]
+        .iter()
+        .find_map(|dir| std::fs::read_dir(dir).ok())?
+        .flatten()
+        .filter(|entry| entry.path().join("modules.dep").exists() || entry.path().join("kernel").is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    return newer_kernel_release(running.trim(), &installed);
+}
+
+pub fn get_package_db_update_date() -> Option<NaiveDate> {
+    return PACKAGE_DBS
+        .iter()
+        .filter_map(|path| Path::new(path).metadata().ok()?.modified().ok())
+        .max()
+        .map(|modified| DateTime::<Local>::from(modified).date_naive());
+}
+
+/// How long a pending update count is reused; installing anything
+/// invalidates it sooner.
+const PENDING_UPDATES_TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// `(pending, security)` from `apt list --upgradable`; security updates are
+/// the ones coming from a `-security` suite.
+fn parse_apt_upgradable(output: &str) -> (usize, Option<usize>) {
+    let packages: Vec<&str> = output.lines().filter(|line| line.contains("[upgradable from")).collect();
+    let security = packages
+        .iter()
+        .filter(|line| line.split_whitespace().next().is_some_and(|origin| origin.contains("-security")))
+        .count();
+    return (packages.len(), Some(security));
+}
+
+/// Package names listed by `dnf check-update`, up to the `Obsoleting
+/// Packages` section, which repeats some of them.
+fn parse_dnf_check_update(output: &str) -> Vec<&str> {
+    return output
+        .lines()
+        .take_while(|line| !line.starts_with("Obsoleting"))
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            return match columns[..] {
+                [package, _, _] if package.contains('.') => Some(package),
+                _ => None,
+            };
+        })
+        .collect();
+}
+
+/// Distinct packages in `dnf updateinfo list --security`, one advisory per
+/// line as `FEDORA-2024-1a2b3c Moderate/Sec. openssl-3.1.4-1.fc39.x86_64`.
+fn parse_dnf_security(output: &str) -> usize {
+    let mut packages: Vec<&str> = output.lines().filter_map(|line| line.split_whitespace().nth(2)).collect();
+    packages.sort_unstable();
+    packages.dedup();
+    return packages.len();
+}
+
+/// stdout of a package manager query. `ok_codes` are the exit codes that
+/// mean success, since `dnf check-update` and `checkupdates` report
+/// pending updates (or none) through them.
+fn package_query(program: &str, args: &[&str], ok_codes: &[i32]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.code().is_some_and(|code| ok_codes.contains(&code)) {
+        return None;
+    }
+    return Some(String::from_utf8_lossy(&output.stdout).into_owned());
+}
+
+/// Asks the package manager owning the system's package database. pacman
+/// has no security metadata, so only apt and dnf report a security count.
+fn query_pending_updates() -> Option<(usize, Option<usize>)> {
+    if Path::new("/var/lib/dpkg/status").exists() {
+        return Some(parse_apt_upgradable(&package_query("apt", &["list", "--upgradable"], &[0])?));
+    }
+    if Path::new("/var/lib/pacman/local").exists() {
+        // Exits with 2 when everything is up to date.
+        let output = package_query("checkupdates", &[], &[0, 2])?;
+        return Some((output.lines().filter(|line| line.contains(" -> ")).count(), None));
+    }
+    if Path::new("/var/lib/rpm").exists() {
+        // Exits with 100 when updates are pending.
+        let output = package_query("dnf", &["-q", "check-update"], &[0, 100])?;
+        let security = package_query("dnf", &["-q", "updateinfo", "list", "--security"], &[0])
+            .map(|advisories| parse_dnf_security(&advisories));
+        return Some((parse_dnf_check_update(&output).len(), security));
+    }
+    return None;
+}
+
+pub fn pending_updates_cache_path() -> Option<std::path::PathBuf> {
+    return dirs::cache_dir().map(|dir| dir.join("ashwin-fetch").join("pending-updates"));
+}
+
+/// `(pending, security)` updates, or `None` when no supported package
+/// manager answers. Queries can take seconds or hit the network, so the
+/// result is cached for an hour, or until the package database changes.
+pub fn get_pending_updates() -> Option<(usize, Option<usize>)> {
+    let path = pending_updates_cache_path();
+    let cached_at = path.as_ref().and_then(|path| path.metadata().ok()?.modified().ok());
+    let db_changed = PACKAGE_DBS.iter().filter_map(|db| Path::new(db).metadata().ok()?.modified().ok()).max();
+    let fresh = cached_at.is_some_and(|cached_at| {
+        let young = cached_at.elapsed().is_ok_and(|age| age < PENDING_UPDATES_TTL);
+        return young && db_changed.is_none_or(|changed| changed < cached_at);
+    });
+    if let (true, Some(path)) = (fresh, &path) {
+        let cached = std::fs::read_to_string(path).ok()?;
+        let mut counts = cached.split_whitespace().map(|count| count.parse::<usize>().ok());
+        let pending = counts.next().flatten()?;
+        return Some((pending, counts.next().flatten()));
+    }
+    let pending = query_pending_updates();
+    if let (Some((count, security)), Some(path)) = (pending, &path) {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let security = security.map_or(String::from("-"), |security| security.to_string());
+        let _ = std::fs::write(path, format!("{} {}", count, security));
+    }
+    return pending;
+}
+
+fn read_hex_id(path: &Path) -> Option<u32> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    return u32::from_str_radix(contents.trim().trim_start_matches("0x"), 16).ok();
+}
+
+/// A numeric sysfs attribute of the DRM card with the given PCI
+/// vendor/device ids.
+fn read_gpu_attribute(vendor_id: u32, device_id: u32, name: &str) -> Option<u64> {
+    for entry in std::fs::read_dir("/sys/class/drm").ok()?.flatten() {
+        let device_dir = entry.path().join("device");
+        if read_hex_id(&device_dir.join("vendor")) != Some(vendor_id)
+            || read_hex_id(&device_dir.join("device")) != Some(device_id)
+        {
+            continue;
+        }
+        if let Ok(value) = std::fs::read_to_string(device_dir.join(name)) {
+            return value.trim().parse().ok();
+        }
+    }
+    return None;
+}
+
+/// Dedicated VRAM of the DRM card with the given PCI vendor/device ids, in
+/// bytes. Only drivers that publish `mem_info_vram_total` (amdgpu, i915/xe
+/// discrete parts) are covered.
+pub fn get_gpu_vram_bytes(vendor_id: u32, device_id: u32) -> Option<u64> {
+    return read_gpu_attribute(vendor_id, device_id, "mem_info_vram_total");
+}
+
+/// VRAM currently in use, in bytes, from the same drivers' `mem_info_vram_used`.
+pub fn get_gpu_vram_used_bytes(vendor_id: u32, device_id: u32) -> Option<u64> {
+    return read_gpu_attribute(vendor_id, device_id, "mem_info_vram_used");
+}
+
+fn read_khz_as_mhz(path: &str) -> Option<f64> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    return contents.trim().parse::<f64>().ok().map(|khz| khz / 1000.0);
+}
+
+/// `(base, boost)` clocks of one logical CPU in MHz, from cpufreq. `base_frequency`
+/// is only published by intel_pstate; `cpuinfo_max_freq` by every driver.
+pub fn get_cpu_frequency_limits(cpu_index: usize) -> (Option<f64>, Option<f64>) {
+    let dir = format!("/sys/devices/system/cpu/cpu{}/cpufreq", cpu_index);
+    return (
+        read_khz_as_mhz(&format!("{}/base_frequency", dir)),
+        read_khz_as_mhz(&format!("{}/cpuinfo_max_freq", dir)),
+    );
+}
+
+fn read_dmi(name: &str) -> Option<String> {
+    let value = std::fs::read_to_string(format!("/sys/class/dmi/id/{}", name)).ok()?;
+    let value = value.trim();
+    return if value.is_empty() { None } else { Some(value.to_string()) };
+}
+
+pub fn get_dmi_chassis_type() -> Option<u32> {
+    return read_dmi("chassis_type")?.parse().ok();
+}
+
+/// DMI vendor and product strings, used to spot well-known hypervisors.
+pub fn get_dmi_system_identity() -> String {
+    return [read_dmi("sys_vendor"), read_dmi("product_name")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+/// Counts the CPUs in a kernel cpulist such as `0-3,8-11,14`.
+pub fn parse_cpu_list(list: &str) -> Option<usize> {
+    return Some(cpu_list_ids(list)?.len());
+}
+
+/// The CPU numbers in a kernel cpulist, in order.
+fn cpu_list_ids(list: &str) -> Option<Vec<usize>> {
+    let mut ids = vec![];
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => ids.extend(start.parse::<usize>().ok()?..=end.parse::<usize>().ok()?),
+            None => ids.push(range.parse::<usize>().ok()?),
+        }
+    }
+    return Some(ids);
+}
+
+/// `(sockets, physical cores)` from the `physical id` and `core id` lines of
+/// `/proc/cpuinfo`. `None` where they're missing, as on most ARM boards.
+fn parse_cpuinfo_topology(cpuinfo: &str) -> Option<(usize, usize)> {
+    let mut packages = std::collections::BTreeSet::new();
+    let mut cores = std::collections::BTreeSet::new();
+    let mut package = None;
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "physical id" => {
+                package = Some(value.trim().to_string());
+                packages.insert(value.trim().to_string());
+            }
+            "core id" => {
+                cores.insert((package.clone()?, value.trim().to_string()));
+            }
+            _ => {}
+        }
+    }
+    if packages.is_empty() {
+        return None;
+    }
+    return Some((packages.len(), cores.len()));
+}
+
+/// `(sockets, NUMA nodes, physical cores)`. The node count comes from
+/// `/sys/devices/system/node` and is `None` on kernels built without NUMA.
+pub fn get_cpu_topology() -> Option<(usize, Option<usize>, usize)> {
+    let (sockets, cores) = parse_cpuinfo_topology(&std::fs::read_to_string("/proc/cpuinfo").ok()?)?;
+    let nodes = std::fs::read_to_string("/sys/devices/system/node/online").ok().and_then(|list| parse_cpu_list(&list));
+    return Some((sockets, nodes, cores));
+}
+
+/// `32K`-style sizes from the sysfs cache directories, in bytes.
+fn parse_cache_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.strip_suffix('K') {
+        Some(number) => (number, 1024),
+        None => match size.strip_suffix('M') {
+            Some(number) => (number, 1024 * 1024),
+            None => (size, 1),
+        },
+    };
+    return number.parse::<u64>().ok().map(|number| number * multiplier);
+}
+
+/// `(level, bytes)` of every CPU cache level, summed over the distinct
+/// caches: per-core L1/L2 count once per core, a shared L3 once. Caches are
+/// told apart by level, type and the CPUs sharing them.
+pub fn get_cpu_caches() -> Vec<(u8, u64)> {
+    let mut seen = std::collections::HashSet::new();
+    let mut totals = std::collections::BTreeMap::new();
+    let Ok(cpus) = std::fs::read_dir("/sys/devices/system/cpu") else {
+        return vec![];
+    };
+    for cpu in cpus.flatten() {
+        let Ok(indices) = std::fs::read_dir(cpu.path().join("cache")) else {
+            continue;
+        };
+        for index in indices.flatten() {
+            let read = |name: &str| std::fs::read_to_string(index.path().join(name)).ok().map(|value| value.trim().to_string());
+            let level = read("level").and_then(|level| level.parse::<u8>().ok());
+            let size = read("size").as_deref().and_then(parse_cache_size);
+            let (Some(level), Some(size)) = (level, size) else {
+                continue;
+            };
+            if seen.insert((level, read("type"), read("shared_cpu_list"))) {
+                *totals.entry(level).or_insert(0) += size;
+            }
+        }
+    }
+    return totals.into_iter().collect();
+}
+
+/// `(configured, online)` logical CPU counts.
+pub fn get_cpu_online_counts() -> Option<(usize, usize)> {
+    let read = |name: &str| parse_cpu_list(&std::fs::read_to_string(format!("/sys/devices/system/cpu/{}", name)).ok()?);
+    return Some((read("present")?, read("online")?));
+}
+
+/// `(performance, efficiency, logical CPUs)` on hybrid Intel CPUs, from the
+/// CPU lists of the `cpu_core` and `cpu_atom` perf PMUs, which only exist
+/// there. P-cores run two threads each with Hyper-Threading, so cores are
+/// told apart by their `core_id`.
+pub fn get_hybrid_core_counts() -> Option<(usize, usize, usize)> {
+    let read = |pmu: &str| cpu_list_ids(&std::fs::read_to_string(format!("/sys/devices/{}/cpus", pmu)).ok()?);
+    let (performance, efficiency) = (read("cpu_core")?, read("cpu_atom")?);
+    let cores = |cpus: &[usize]| {
+        let mut cores = std::collections::BTreeSet::new();
+        for cpu in cpus {
+            let topology = |name: &str| std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{}/topology/{}", cpu, name)).ok();
+            cores.insert((topology("physical_package_id")?, topology("core_id")?));
+        }
+        return Some(cores.len());
+    };
+    return Some((cores(&performance)?, cores(&efficiency)?, performance.len() + efficiency.len()));
+}
+
+/// cpufreq scaling governor of cpu0, e.g. `schedutil`. `None` without
+/// cpufreq (most VMs and containers).
+pub fn get_cpu_governor() -> Option<String> {
+    let governor = std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor").ok()?;
+    let governor = governor.trim();
+    return if governor.is_empty() { None } else { Some(governor.to_string()) };
+}
+
+/// Active power-profiles-daemon profile (`balanced`, `performance`,
+/// `power-saver`), when the daemon and `powerprofilesctl` are installed.
+pub fn get_power_profile() -> Option<String> {
+    let output = std::process::Command::new("powerprofilesctl").arg("get").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let profile = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    return if profile.is_empty() { None } else { Some(profile) };
+}
+
+/// `(some, full)` 10-second memory stall averages in percent, from PSI
+/// (`/proc/pressure/memory`, Linux 4.20+ with `CONFIG_PSI`). `some` is the
+/// share of time at least one task waited on memory (reclaim, swap-in),
+/// `full` the share when every task did.
+pub fn get_memory_pressure() -> Option<(f64, f64)> {
+    let contents = std::fs::read_to_string("/proc/pressure/memory").ok()?;
+    let avg10 = |kind: &str| {
+        let line = contents.lines().find(|line| line.starts_with(kind))?;
+        return line.split_whitespace().find_map(|field| field.strip_prefix("avg10="))?.parse::<f64>().ok();
+    };
+    return Some((avg10("some")?, avg10("full")?));
+}
+
+/// `(idle, total)` jiffies of every CPU, in `cpuN` order, from
+/// `/proc/stat`. Idle includes iowait; guest time is already part of user.
+pub fn get_cpu_times() -> Option<Vec<(u64, u64)>> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    return stat
+        .lines()
+        .filter(|line| line.starts_with("cpu") && line.as_bytes().get(3).is_some_and(u8::is_ascii_digit))
+        .map(|line| {
+            let values: Vec<u64> = line.split_whitespace().skip(1).take(8).map(|value| value.parse().ok()).collect::<Option<_>>()?;
+            let idle = values.get(3)? + values.get(4).unwrap_or(&0);
+            return Some((idle, values.iter().sum()));
+        })
+        .collect();
+}
+
+/// `(user, system, idle, total)` jiffies summed over all CPUs, from the
+/// `cpu` line of `/proc/stat`. Nice time counts as user, IRQ time as
+/// system and iowait as idle; steal is only part of the total.
+pub fn parse_cpu_state_times(stat: &str) -> Option<(u64, u64, u64, u64)> {
+    let line = stat.lines().find(|line| line.split_whitespace().next() == Some("cpu"))?;
+    let values: Vec<u64> = line.split_whitespace().skip(1).take(8).map(|value| value.parse().ok()).collect::<Option<_>>()?;
+    let value = |index: usize| values.get(index).copied().unwrap_or(0);
+    return Some((value(0) + value(1), value(2) + value(5) + value(6), value(3) + value(4), values.iter().sum()));
+}
+
+pub fn get_cpu_state_times() -> Option<(u64, u64, u64, u64)> {
+    return parse_cpu_state_times(&std::fs::read_to_string("/proc/stat").ok()?);
+}
+
+/// `(user, system, idle)` percentages of the time elapsed between two
+/// `get_cpu_state_times` readings.
+pub fn cpu_states_between(before: (u64, u64, u64, u64), after: (u64, u64, u64, u64)) -> Option<(f64, f64, f64)> {
+    let elapsed = after.3.checked_sub(before.3).filter(|elapsed| *elapsed > 0)? as f64;
+    let percent = |after: u64, before: u64| after.saturating_sub(before) as f64 / elapsed * 100.0;
+    return Some((percent(after.0, before.0), percent(after.1, before.1), percent(after.2, before.2)));
+}
+
+/// Per-CPU busy percentage between two `get_cpu_times` readings. A CPU
+/// with no elapsed time (or one that went offline) reads as idle.
+pub fn cpu_usage_between(before: &[(u64, u64)], after: &[(u64, u64)]) -> Vec<f32> {
+    return after
+        .iter()
+        .enumerate()
+        .map(|(index, &(idle, total))| {
+            let Some(&(idle_before, total_before)) = before.get(index) else {
+                return 0.0;
+            };
+            let elapsed = total.saturating_sub(total_before);
+            if elapsed == 0 {
+                return 0.0;
+            }
+            let busy = elapsed.saturating_sub(idle.saturating_sub(idle_before));
+            return (busy as f64 / elapsed as f64 * 100.0) as f32;
+        })
+        .collect();
+}
+
+/// CPUs in our affinity mask (what `sched_getaffinity` reports), from
+/// `Cpus_allowed_list` in `/proc/self/status`. Smaller than the online count
+/// when pinned with `taskset` or a cpuset.
+pub fn get_cpu_affinity_count() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let list = status.lines().find_map(|line| line.strip_prefix("Cpus_allowed_list:"))?;
+    return parse_cpu_list(list);
+}
+
+/// CPU bandwidth limit of our cgroup as a number of CPUs (`cpu.max` on v2,
+/// `cfs_quota_us`/`cfs_period_us` on v1). `None` when unlimited.
+pub fn get_cgroup_cpu_limit() -> Option<f64> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    let (quota, period) = if let Some(cpu_max) = read("/sys/fs/cgroup/cpu.max") {
+        let (quota, period) = cpu_max.trim().split_once(' ')?;
+        (quota.parse::<f64>().ok()?, period.parse::<f64>().ok()?)
+    } else {
+        (
+            read("/sys/fs/cgroup/cpu/cpu.cfs_quota_us")?.trim().parse::<f64>().ok()?,
+            read("/sys/fs/cgroup/cpu/cpu.cfs_period_us")?.trim().parse::<f64>().ok()?,
+        )
+    };
+    if quota <= 0.0 || period <= 0.0 {
+        return None;
+    }
+    return Some(quota / period);
+}
+
+/// Sound server by its client socket, then by daemon process name, falling
+/// back to bare ALSA when the kernel at least has a sound card.
+/// `process_names` may be empty when processes weren't loaded.
+pub fn get_audio_server(process_names: &[String]) -> Option<String> {
+    if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR").map(std::path::PathBuf::from) {
+        if runtime_dir.join("pipewire-0").exists() {
+            return Some(String::from("PipeWire"));
+        }
+        if runtime_dir.join("pulse/native").exists() {
+            return Some(String::from("PulseAudio"));
+        }
+    }
+    const DAEMONS: [(&str, &str); 4] = [
+        ("pipewire", "PipeWire"),
+        ("pulseaudio", "PulseAudio"),
+        ("jackd", "JACK"),
+        ("jackdbus", "JACK"),
+    ];
+    for (process, server) in DAEMONS {
+        if process_names.iter().any(|name| name == process) {
+            return Some(String::from(server));
+        }
+    }
+    let cards = std::fs::read_to_string("/proc/asound/cards").ok()?;
+    if cards.trim().is_empty() || cards.contains("no soundcards") {
+        return None;
+    }
+    return Some(String::from("ALSA"));
+}
+
+/// PCI addresses (`0000:01:00.0`) of every DRM card with the given PCI ids,
+/// sorted. Identical cards share ids, so callers hand them out in order.
+pub fn get_gpu_pci_bus_ids(vendor_id: u32, device_id: u32) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/drm") else {
+        return vec![];
+    };
+    let mut bus_ids: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            // `card0`, not connectors such as `card0-DP-1` or render nodes.
+            let name = entry.file_name().to_string_lossy().into_owned();
+            return name.strip_prefix("card").is_some_and(|index| !index.is_empty() && index.bytes().all(|byte| byte.is_ascii_digit()));
+        })
+        .filter_map(|entry| {
+            let device_dir = entry.path().join("device");
+            if read_hex_id(&device_dir.join("vendor")) != Some(vendor_id)
+                || read_hex_id(&device_dir.join("device")) != Some(device_id)
+            {
+                return None;
+            }
+            let target = std::fs::read_link(&device_dir).ok()?;
+            return Some(target.file_name()?.to_string_lossy().into_owned());
+        })
+        .collect();
+    bus_ids.sort();
+    bus_ids.dedup();
+    return bus_ids;
+}
+
+/// `(charge percent, health percent, cycle count)` of the first system
+/// battery under `/sys/class/power_supply`. Health is the full capacity
+/// against the design capacity; drivers publish it as energy (µWh) or
+/// charge (µAh). Peripherals such as wireless mice are skipped.
+pub fn get_battery() -> Option<(f64, Option<f64>, Option<u32>)> {
+    let mut supplies: Vec<_> = std::fs::read_dir("/sys/class/power_supply").ok()?.flatten().map(|entry| entry.path()).collect();
+    supplies.sort();
+    for supply in supplies {
+        let read = |name: &str| std::fs::read_to_string(supply.join(name)).ok().map(|value| value.trim().to_string());
+        let number = |name: &str| read(name)?.parse::<f64>().ok();
+        if read("type").as_deref() != Some("Battery") || read("scope").as_deref() == Some("Device") {
+            continue;
+        }
+        let Some(percent) = number("capacity") else {
+            continue;
+        };
+        let health = ["energy", "charge"].iter().find_map(|kind| {
+            let full = number(&format!("{}_full", kind))?;
+            let design = number(&format!("{}_full_design", kind)).filter(|design| *design > 0.0)?;
+            return Some(full / design * 100.0);
+        });
+        // Some firmware reports 0 when it doesn't track cycles.
+        let cycles = read("cycle_count").and_then(|cycles| cycles.parse::<u32>().ok()).filter(|cycles| *cycles > 0);
+        return Some((percent, health, cycles));
+    }
+    return None;
+}
+
+/// Watts from a supply's `voltage_max` (µV) and `current_max` (µA), which
+/// USB-C and some barrel adapter drivers publish; rounded to whole watts.
+fn adapter_watts(voltage_max_uv: f64, current_max_ua: f64) -> Option<u32> {
+    let watts = (voltage_max_uv * current_max_ua / 1e12).round();
+    return if watts > 0.0 { Some(watts as u32) } else { None };
+}
+
+/// `(on AC, adapter watts)` from the `Mains` and `USB` supplies under
+/// `/sys/class/power_supply`. `None` without a system battery, since
+/// desktops are always on AC.
+pub fn get_power_source() -> Option<(bool, Option<u32>)> {
+    let supplies: Vec<_> = std::fs::read_dir("/sys/class/power_supply").ok()?.flatten().map(|entry| entry.path()).collect();
+    let read = |supply: &Path, name: &str| std::fs::read_to_string(supply.join(name)).ok().map(|value| value.trim().to_string());
+    let has_battery = supplies
+        .iter()
+        .any(|supply| read(supply, "type").as_deref() == Some("Battery") && read(supply, "scope").as_deref() != Some("Device"));
+    if !has_battery {
+        return None;
+    }
+    let adapter = supplies.iter().find(|supply| {
+        return matches!(read(supply, "type").as_deref(), Some("Mains" | "USB")) && read(supply, "online").as_deref() == Some("1");
+    });
+    let Some(adapter) = adapter else {
+        return Some((false, None));
+    };
+    let number = |name: &str| read(adapter, name)?.parse::<f64>().ok();
+    let watts = number("voltage_max").zip(number("current_max")).and_then(|(voltage, current)| adapter_watts(voltage, current));
+    return Some((true, watts));
+}
+
+/// Current board power of the DRM card with the given PCI ids, in watts,
+/// from its hwmon node (amdgpu publishes `power1_average` or `power1_input`
+/// in microwatts).
+pub fn get_gpu_power_watts(vendor_id: u32, device_id: u32) -> Option<f64> {
+    for entry in std::fs::read_dir("/sys/class/drm").ok()?.flatten() {
+        let device_dir = entry.path().join("device");
+        if read_hex_id(&device_dir.join("vendor")) != Some(vendor_id)
+            || read_hex_id(&device_dir.join("device")) != Some(device_id)
+        {
+            continue;
+        }
+        for hwmon in std::fs::read_dir(device_dir.join("hwmon")).ok()?.flatten() {
+            for name in ["power1_average", "power1_input"] {
+                if let Ok(microwatts) = std::fs::read_to_string(hwmon.path().join(name)) {
+                    return microwatts.trim().parse::<f64>().ok().map(|microwatts| microwatts / 1_000_000.0);
+                }
+            }
+        }
+    }
+    return None;
+}
+
+/// Temperature of the primary GPU in °C, from the `temp1_input` hwmon node
+/// of the DRM card the firmware booted on (`boot_vga`), or the first card
+/// with a sensor. NVIDIA's proprietary driver doesn't publish one.
+pub fn get_gpu_temperature() -> Option<f64> {
+    let mut devices: Vec<_> = std::fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().contains('-'))
+        .map(|entry| entry.path().join("device"))
+        .collect();
+    devices.sort();
+    devices.sort_by_key(|device| std::fs::read_to_string(device.join("boot_vga")).ok().as_deref().map(str::trim) != Some("1"));
+    return devices.iter().find_map(|device| {
+        return std::fs::read_dir(device.join("hwmon")).ok()?.flatten().find_map(|hwmon| {
+            let millidegrees = std::fs::read_to_string(hwmon.path().join("temp1_input")).ok()?;
+            return millidegrees.trim().parse::<f64>().ok().map(|millidegrees| millidegrees / 1000.0);
+        });
+    });
+}
+
+/// Bits per color and HDR support from a monitor's EDID: the depth is the
+/// EDID 1.4 digital input field, HDR a CTA-861 HDR static metadata block
+/// listing the PQ (ST 2084) or HLG transfer function. Both describe what
+/// the panel accepts, not the current mode.
+pub fn parse_edid_color(edid: &[u8]) -> Option<(Option<u8>, bool)> {
+    if edid.len() < 128 || edid[..8] != [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00] {
+        return None;
+    }
+    let digital = edid[20] & 0x80 != 0;
+    let version_1_4 = edid[18] == 1 && edid[19] >= 4;
+    let bit_depth = match (edid[20] >> 4) & 0x07 {
+        depth @ 1..=6 if digital && version_1_4 => Some(4 + 2 * depth),
+        _ => None,
+    };
+    let mut hdr = false;
+    for block in edid[128..].chunks_exact(128).take(edid[126] as usize) {
+        if block[0] != 0x02 {
+            continue;
+        }
+        let end = (block[2] as usize).clamp(4, 127);
+        let mut offset = 4;
+        while offset < end {
+            let (tag, length) = (block[offset] >> 5, (block[offset] & 0x1f) as usize);
+            let data = &block[(offset + 1).min(end)..(offset + 1 + length).min(end)];
+            // Extended tag 6 is the HDR static metadata block; its first
+            // byte flags the supported transfer functions.
+            if tag == 7 && data.len() >= 2 && data[0] == 6 && data[1] & 0b1100 != 0 {
+                hdr = true;
+            }
+            offset += 1 + length;
+        }
+    }
+    return Some((bit_depth, hdr));
+}
+
+/// Color depth and HDR support of the monitor on the named connector
+/// (`DP-1`), from its EDID in `/sys/class/drm`. X11's modesetting driver
+/// calls `HDMI-A-1` `HDMI-1`, so that spelling is tried too.
+pub fn get_display_color(connector: &str) -> Option<(Option<u8>, bool)> {
+    let names = [connector.to_string(), connector.replacen("HDMI-", "HDMI-A-", 1)];
+    return std::fs::read_dir("/sys/class/drm").ok()?.flatten().find_map(|entry| {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let (_, name) = file_name.split_once('-')?;
+        if !names.iter().any(|candidate| candidate == name) {
+            return None;
+        }
+        return parse_edid_color(&std::fs::read(entry.path().join("edid")).ok()?);
+    });
+}
+
+/// IPv6 addresses the kernel flags as temporary (privacy extensions) or
+/// deprecated, from `/proc/net/if_inet6`. Neither makes a good "primary"
+/// address since they rotate.
+pub fn get_ipv6_unstable_addrs() -> Vec<Ipv6Addr> {
+    const IFA_F_TEMPORARY: u32 = 0x01;
+    const IFA_F_DEPRECATED: u32 = 0x20;
+    let Ok(contents) = std::fs::read_to_string("/proc/net/if_inet6") else {
+        return vec![];
+    };
+    return contents
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(columns.get(4)?, 16).ok()?;
+            if flags & (IFA_F_TEMPORARY | IFA_F_DEPRECATED) == 0 {
+                return None;
+            }
+            return u128::from_str_radix(columns.first()?, 16).ok().map(Ipv6Addr::from);
+        })
+        .collect();
+}
+
+/// Number of systemd units in the failed state, or `None` when systemd
+/// isn't the init system.
+pub fn get_failed_unit_count() -> Option<usize> {
+    if !Path::new("/run/systemd/system").exists() {
+        return None;
+    }
+    let output = std::process::Command::new("systemctl")
+        .args(["--failed", "--no-legend", "--plain"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return Some(String::from_utf8_lossy(&output.stdout).lines().filter(|line| !line.trim().is_empty()).count());
+}
+
+/// The `OnCalendar=` schedule and whether the timer is enabled, from
+/// `systemctl show fstrim.timer`; `None` when the unit doesn't exist.
+fn parse_fstrim_timer(show: &str) -> Option<(bool, Option<String>)> {
+    let property = |key: &str| show.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix('='));
+    if property("LoadState")? != "loaded" {
+        return None;
+    }
+    let enabled = property("UnitFileState").is_some_and(|state| state == "enabled");
+    // `{ OnCalendar=weekly ; next_elapse=Mon 2024-06-10 00:00:00 UTC }`
+    let schedule = property("TimersCalendar")
+        .and_then(|timers| timers.split_once("OnCalendar=")?.1.split(" ;").next())
+        .map(|schedule| schedule.trim_end_matches(" }").trim().to_string())
+        .filter(|schedule| !schedule.is_empty());
+    return Some((enabled, schedule));
+}
+
+/// Whether systemd's `fstrim.timer` periodically trims mounted SSDs, as
+/// `enabled (weekly)` or `disabled`. `None` without systemd or the unit.
+pub fn get_fstrim_status() -> Option<String> {
+    if !Path::new("/run/systemd/system").exists() {
+        return None;
+    }
+    let output = std::process::Command::new("systemctl")
+        .args(["show", "fstrim.timer", "--property=LoadState,UnitFileState,TimersCalendar"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return match parse_fstrim_timer(&String::from_utf8_lossy(&output.stdout))? {
+        (true, Some(schedule)) => Some(format!("enabled ({})", schedule)),
+        (true, None) => Some(String::from("enabled")),
+        (false, _) => Some(String::from("disabled")),
+    };
+}
+
+/// `(pid, user)` of a utmp entry when it's a login session.
+fn login_session(entry: &libc::utmpx) -> Option<(i32, String)> {
+    if entry.ut_type != libc::USER_PROCESS {
+        return None;
+    }
+    let user: Vec<u8> = entry.ut_user.iter().take_while(|byte| **byte != 0).map(|byte| *byte as u8).collect();
+    return if user.is_empty() { None } else { Some((entry.ut_pid, String::from_utf8_lossy(&user).into_owned())) };
+}
+
+/// `getutxent` walks a process-wide cursor, so only one reader at a time.
+static UTMP_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// User names of the login sessions in utmp, one per session like `who`.
+/// Entries whose process is gone (left behind by a crash) are skipped.
+/// `None` on systems that don't keep utmp.
+pub fn get_logged_in_users() -> Option<Vec<String>> {
+    let _lock = UTMP_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut sessions = Vec::new();
+    // SAFETY: the lock keeps other threads off the utmp cursor, and each
+    // entry is copied out before the next call overwrites it.
+    unsafe {
+        libc::setutxent();
+        loop {
+            let entry = libc::getutxent();
+            if entry.is_null() {
+                break;
+            }
+            sessions.extend(login_session(&*entry));
+        }
+        libc::endutxent();
+    }
+    if sessions.is_empty() && !Path::new("/run/utmp").exists() && !Path::new("/var/run/utmp").exists() {
+        return None;
+    }
+    return Some(
+        sessions
+            .into_iter()
+            .filter(|(pid, _)| Path::new(&format!("/proc/{}", pid)).exists())
+            .map(|(_, user)| user)
+            .collect(),
+    );
+}
+
+/// `(threads, zombies)` across every process, from `/proc/<pid>/status`.
+pub fn get_thread_counts() -> Option<(usize, usize)> {
+    let (mut threads, mut zombies) = (0, 0);
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        if !entry.file_name().to_string_lossy().bytes().all(|byte| byte.is_ascii_digit()) {
+            continue;
+        }
+        // The process may have exited since the directory was listed.
+        let Ok(status) = std::fs::read_to_string(entry.path().join("status")) else {
+            continue;
+        };
+        for line in status.lines() {
+            if let Some(state) = line.strip_prefix("State:") {
+                if state.trim_start().starts_with('Z') {
+                    zombies += 1;
+                }
+            } else if let Some(count) = line.strip_prefix("Threads:") {
+                threads += count.trim().parse::<usize>().unwrap_or(0);
+            }
+        }
+    }
+    return Some((threads, zombies));
+}
+
+/// `SecureBoot` EFI variable: four attribute bytes, then 1 when enforcing.
+const SECURE_BOOT_VARIABLE: &str = "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// `(uefi, secure_boot)`: UEFI when the kernel exposes `/sys/firmware/efi`,
+/// which only exists after an EFI boot. Secure Boot is `None` when the
+/// variable can't be read.
+pub fn get_firmware_mode() -> (bool, Option<bool>) {
+    if !Path::new("/sys/firmware/efi").exists() {
+        return (false, None);
+    }
+    let secure_boot = std::fs::read(SECURE_BOOT_VARIABLE).ok().and_then(|bytes| parse_secure_boot(&bytes));
+    return (true, secure_boot);
+}
+
+/// Whether the `SecureBoot` variable says it's enforcing; the first four
+/// bytes are the variable's attributes.
+fn parse_secure_boot(bytes: &[u8]) -> Option<bool> {
+    return match bytes.get(4)? {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    };
+}
+
+/// Bits in the kernel's entropy pool. Kernels since 5.18 always report 256
+/// once the pool is seeded.
+pub fn get_entropy_avail() -> Option<usize> {
+    return std::fs::read_to_string("/proc/sys/kernel/random/entropy_avail").ok()?.trim().parse().ok();
+}
+
+/// Kernel parameters from `/proc/cmdline`.
+pub fn get_kernel_cmdline() -> Option<String> {
+    let cmdline = filter_cmdline(&std::fs::read_to_string("/proc/cmdline").ok()?);
+    return if cmdline.is_empty() { None } else { Some(cmdline) };
+}
+
+/// `cmdline` without what the bootloader adds on its own (`BOOT_IMAGE=`,
+/// `initrd=`), which says nothing about the configuration.
+fn filter_cmdline(cmdline: &str) -> String {
+    return cmdline
+        .split_whitespace()
+        .filter(|param| !param.starts_with("BOOT_IMAGE=") && !param.starts_with("initrd="))
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+/// Device mounted at `/` in `/proc/self/mounts`; the last entry wins, as
+/// later mounts hide earlier ones.
+pub fn parse_root_device(mounts: &str) -> Option<&str> {
+    return mounts.lines().rev().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let device = fields.next()?;
+        return if fields.next()? == "/" { Some(device) } else { None };
+    });
+}
+
+/// `LUKS2` from a device-mapper UUID such as `CRYPT-LUKS2-<uuid>-<name>`.
+pub fn crypt_type_from_dm_uuid(uuid: &str) -> Option<&str> {
+    return uuid.trim().strip_prefix("CRYPT-")?.split('-').next().filter(|kind| !kind.is_empty());
+}
+
+/// The dm-crypt layer under block device `name` (e.g. `dm-1`), following
+/// LVM and other device-mapper layers down through `slaves`.
+fn find_crypt_layer(name: &str, depth: usize) -> Option<String> {
+    let block = Path::new("/sys/class/block").join(name);
+    if let Ok(uuid) = std::fs::read_to_string(block.join("dm/uuid"))
+        && let Some(kind) = crypt_type_from_dm_uuid(&uuid)
+    {
+        return Some(kind.to_string());
+    }
+    if depth == 0 {
+        return None;
+    }
+    return std::fs::read_dir(block.join("slaves"))
+        .ok()?
+        .flatten()
+        .find_map(|slave| find_crypt_layer(&slave.file_name().to_string_lossy(), depth - 1));
+}
+
+/// `LUKS2 (active)` when `/` sits on dm-crypt, `none` when it's on a plain
+/// block device, and `None` when it isn't a block device at all
+/// (containers, network roots).
+pub fn get_root_encryption() -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    let device = parse_root_device(&mounts)?;
+    if !device.starts_with("/dev/") {
+        return None;
+    }
+    // `/dev/mapper/root` and `/dev/disk/by-uuid/…` are links to `/dev/dm-0`
+    // and the like.
+    let device = std::fs::canonicalize(device).ok()?;
+    let name = device.file_name()?.to_string_lossy().into_owned();
+    if !Path::new("/sys/class/block").join(&name).exists() {
+        return None;
+    }
+    return match find_crypt_layer(&name, 4) {
+        Some(kind) => Some(format!("{} (active)", kind)),
+        None => Some(String::from("none")),
+    };
+}
+
+/// `(open, max)` from `/proc/sys/fs/file-nr`, whose fields are allocated,
+/// allocated-but-unused (always 0 since 2.6) and the maximum.
+pub fn parse_file_nr(contents: &str) -> Option<(usize, usize)> {
+    let numbers: Vec<usize> = contents.split_whitespace().map(|field| field.parse().ok()).collect::<Option<_>>()?;
+    let [allocated, unused, max] = numbers[..] else {
+        return None;
+    };
+    return Some((allocated.saturating_sub(unused), max));
+}
+
+/// Open file descriptors system-wide and the `fs.file-max` limit.
+pub fn get_fd_counts() -> Option<(usize, usize)> {
+    let (open, max) = parse_file_nr(&std::fs::read_to_string("/proc/sys/fs/file-nr").ok()?)?;
+    let max = std::fs::read_to_string("/proc/sys/fs/file-max")
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(max);
+    return Some((open, max));
+}
+
+/// Gateway of the lowest-metric IPv4 default route, from `/proc/net/route`.
+/// Addresses there are hex in host (little-endian) byte order.
+pub fn get_default_gateway() -> Option<Ipv4Addr> {
+    const RTF_UP: u32 = 0x1;
+    const RTF_GATEWAY: u32 = 0x2;
+    let contents = std::fs::read_to_string("/proc/net/route").ok()?;
+    return contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(columns.get(3)?, 16).ok()?;
+            let destination = u32::from_str_radix(columns.get(1)?, 16).ok()?;
+            if destination != 0 || flags & (RTF_UP | RTF_GATEWAY) != RTF_UP | RTF_GATEWAY {
+                return None;
+            }
+            let gateway = u32::from_str_radix(columns.get(2)?, 16).ok()?;
+            let metric = columns.get(6)?.parse::<u32>().ok()?;
+            return Some((metric, Ipv4Addr::from(gateway.to_le_bytes())));
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, gateway)| gateway);
+}
+
+/// `(MemTotal, MemAvailable)` in bytes from `/proc/meminfo`, which lists
+/// them in kB.
+pub fn parse_meminfo(contents: &str) -> Option<(u64, u64)> {
+    let kilobytes = |key: &str| {
+        return contents.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix(':')?;
+            return value.trim().trim_end_matches("kB").trim().parse::<u64>().ok();
+        });
+    };
+    return Some((kilobytes("MemTotal")? * 1024, kilobytes("MemAvailable")? * 1024));
+}
+
+pub fn get_meminfo() -> Option<(u64, u64)> {
+    return parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?);
+}
+
+/// SSID of the connected Wi-Fi network, from `iwgetid` (wireless-tools) or
+/// else NetworkManager's `nmcli`, which escapes `:` in its terse output.
+fn get_wifi_ssid() -> Option<String> {
+    let run = |program: &str, args: &[&str]| {
+        let output = std::process::Command::new(program).args(args).env("LC_ALL", "C").output().ok()?;
+        return if output.status.success() { Some(String::from_utf8_lossy(&output.stdout).into_owned()) } else { None };
+    };
+    let ssid = run("iwgetid", &["-r"]).map(|ssid| ssid.trim().to_string()).filter(|ssid| !ssid.is_empty());
+    return ssid.or_else(|| {
+        let networks = run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"])?;
+        return networks
+            .lines()
+            .find_map(|line| line.strip_prefix("yes:"))
+            .map(|ssid| ssid.replace("\\:", ":"))
+            .filter(|ssid| !ssid.is_empty());
+    });
+}
+
+/// Signal level in dBm of the first interface in `/proc/net/wireless`,
+/// whose third column is printed with a trailing dot (`-52.`).
+fn parse_wireless_signal(contents: &str) -> Option<i32> {
+    return contents.lines().skip(2).find_map(|line| {
+        let level = line.split_whitespace().nth(3)?.trim_end_matches('.');
+        return level.parse::<i32>().ok().filter(|level| *level < 0);
+    });
+}
+
+/// `(SSID, signal dBm)` of the connected Wi-Fi network.
+pub fn get_wifi() -> Option<(String, Option<i32>)> {
+    let ssid = get_wifi_ssid()?;
+    let signal = std::fs::read_to_string("/proc/net/wireless").ok().and_then(|contents| parse_wireless_signal(&contents));
+    return Some((ssid, signal));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_usage_is_the_busy_share_of_the_delta() {
+        let before = [(100, 200), (50, 100), (10, 10)];
+        let after = [(150, 300), (50, 200), (10, 10)];
+        // CPU 0: 100 elapsed, 50 idle; CPU 1: fully busy; CPU 2: no time passed.
+        assert_eq!(cpu_usage_between(&before, &after), [50.0, 100.0, 0.0]);
+    }
+
+    #[test]
+    fn edid_color_depth_and_hdr() {
+        let mut edid = vec![0u8; 256];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        edid[18] = 1;
+        edid[19] = 4;
+        // Digital input, 10 bits per color.
+        edid[20] = 0x80 | (3 << 4);
+        assert_eq!(parse_edid_color(&edid[..128]), Some((Some(10), false)));
+        // A CTA-861 extension whose only data block is HDR static metadata
+        // supporting PQ.
+        edid[126] = 1;
+        edid[128..136].copy_from_slice(&[0x02, 0x03, 0x08, 0x00, 0xe3, 0x06, 0x05, 0x01]);
+        assert_eq!(parse_edid_color(&edid), Some((Some(10), true)));
+        edid[19] = 3;
+        assert_eq!(parse_edid_color(&edid), Some((None, true)));
+        assert_eq!(parse_edid_color(&[]), None);
+    }
+
+    #[test]
+    fn utmp_login_sessions() {
+        let entry = |kind: libc::c_short, pid: i32, user: &str| {
+            // SAFETY: `utmpx` is plain old data, all zeroes is a valid value.
+            let mut entry: libc::utmpx = unsafe { std::mem::zeroed() };
+            entry.ut_type = kind;
+            entry.ut_pid = pid;
+            for (slot, byte) in entry.ut_user.iter_mut().zip(user.bytes()) {
+                *slot = byte as libc::c_char;
+            }
+            return entry;
+        };
+        assert_eq!(login_session(&entry(libc::USER_PROCESS, 812, "alice")), Some((812, String::from("alice"))));
+        assert_eq!(login_session(&entry(libc::BOOT_TIME, 0, "reboot")), None);
+        assert_eq!(login_session(&entry(libc::USER_PROCESS, 1290, "")), None);
+    }
+
+    #[test]
+    fn cache_sizes_are_parsed_to_bytes() {
+        assert_eq!(parse_cache_size("48K"), Some(48 * 1024));
+        assert_eq!(parse_cache_size("32M\n"), Some(32 * 1024 * 1024));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size(""), None);
+    }
+
+    #[test]
+    fn newer_kernels_sort_numerically() {
+        let installed = [String::from("6.9.12-3-generic"), String::from("6.10.0-1-generic")];
+        assert_eq!(newer_kernel_release("6.9.12-3-generic", &installed).as_deref(), Some("6.10.0-1-generic"));
+        assert_eq!(newer_kernel_release("6.10.0-1-generic", &installed), None);
+        assert_eq!(newer_kernel_release("6.11.0-arch1-1", &installed), None);
+    }
+
+    #[test]
+    fn newer_kernels_of_other_flavours_are_ignored() {
+        let installed = [String::from("6.8.0-45-generic"), String::from("6.8.0-1010-lowlatency"), String::from("6.11.0-1-lts")];
+        assert_eq!(newer_kernel_release("6.8.0-45-generic", &installed), None);
+        assert_eq!(newer_kernel_release("6.6.30-1-lts", &installed).as_deref(), Some("6.11.0-1-lts"));
+        let fedora = [String::from("6.9.4-200.fc40.x86_64"), String::from("6.8.11-300.fc40.x86_64")];
+        assert_eq!(newer_kernel_release("6.8.11-300.fc40.x86_64", &fedora).as_deref(), Some("6.9.4-200.fc40.x86_64"));
+    }
+
+    #[test]
+    fn wireless_signal_is_read_in_dbm() {
+        let header = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE\n \
+                      face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22\n";
+        let contents = format!("{} wlan0: 0000   58.  -52.  -256        0      0      0      3      0        0\n", header);
+        assert_eq!(parse_wireless_signal(&contents), Some(-52));
+        assert_eq!(parse_wireless_signal(header), None);
+    }
+
+    #[test]
+    fn kernel_build_dates_in_several_styles() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day);
+        let arch = "Linux version 6.8.1-arch1-1 (linux@archlinux) (gcc (GCC) 13.2.1) #1 SMP PREEMPT_DYNAMIC Fri, 15 Mar 2024 16:48:06 +0000";
+        assert_eq!(parse_kernel_build_date(arch), date(2024, 3, 15));
+        let ubuntu = "#35-Ubuntu SMP PREEMPT_DYNAMIC Mon Mar 11 13:06:41 UTC 2024";
+        assert_eq!(parse_kernel_build_date(ubuntu), date(2024, 3, 11));
+        assert_eq!(parse_kernel_build_date("#1 SMP PREEMPT_DYNAMIC Debian 6.1.76-1 (2024-02-01)"), date(2024, 2, 1));
+        assert_eq!(parse_kernel_build_date("Darwin Kernel Version 24.5.0: Tue Apr 22 19:54:26 PDT 2025; root:xnu"), date(2025, 4, 22));
+        assert_eq!(parse_kernel_build_date("#1 SMP"), None);
+    }
+
+    #[test]
+    fn meminfo_used_is_total_minus_available() {
+        let contents = "MemTotal:       16303804 kB\nMemFree:         1203312 kB\nMemAvailable:    9878320 kB\nBuffers:          412300 kB\n";
+        let (total, available) = parse_meminfo(contents).unwrap();
+        assert_eq!(total, 16303804 * 1024);
+        assert_eq!(total - available, (16303804 - 9878320) * 1024);
+        assert_eq!(parse_meminfo("MemTotal: 1024 kB\n"), None);
+    }
+
+    #[test]
+    fn topology_counts_sockets_and_physical_cores() {
+        let cpuinfo = "processor\t: 0\nphysical id\t: 0\ncore id\t\t: 0\n\n\
+                       processor\t: 1\nphysical id\t: 0\ncore id\t\t: 0\n\n\
+                       processor\t: 2\nphysical id\t: 1\ncore id\t\t: 0\n\n\
+                       processor\t: 3\nphysical id\t: 1\ncore id\t\t: 1\n";
+        assert_eq!(parse_cpuinfo_topology(cpuinfo), Some((2, 3)));
+        assert_eq!(parse_cpuinfo_topology("processor\t: 0\nBogoMIPS\t: 48.00\n"), None);
+    }
+
+    #[test]
+    fn root_device_and_crypt_type() {
+        let mounts = "sysfs /sys sysfs rw 0 0\n/dev/mapper/vg-root / ext4 rw 0 0\n/dev/nvme0n1p1 /boot vfat rw 0 0\n";
+        assert_eq!(parse_root_device(mounts), Some("/dev/mapper/vg-root"));
+        assert_eq!(parse_root_device("overlay / overlay rw 0 0\nrootfs / rootfs rw 0 0\n"), Some("rootfs"));
+        assert_eq!(crypt_type_from_dm_uuid("CRYPT-LUKS2-0f3e9c1d8a7b4c2e-luks-0f3e\n"), Some("LUKS2"));
+        assert_eq!(crypt_type_from_dm_uuid("CRYPT-PLAIN-cryptswap"), Some("PLAIN"));
+        assert_eq!(crypt_type_from_dm_uuid("LVM-Xq2bY7"), None);
+    }
+
+    #[test]
+    fn file_nr_gives_open_and_max_descriptors() {
+        assert_eq!(parse_file_nr("12043\t0\t1048576\n"), Some((12043, 1048576)));
+        assert_eq!(parse_file_nr("3000\t200\t9000\n"), Some((2800, 9000)));
+        assert_eq!(parse_file_nr("12043\t0\n"), None);
+    }
+
+    #[test]
+    fn cpu_usage_counts_new_cpus_as_idle() {
+        assert_eq!(cpu_usage_between(&[(0, 10)], &[(5, 20), (5, 20)]), [50.0, 0.0]);
+    }
+
+    #[test]
+    fn secure_boot_byte_follows_the_attributes() {
+        assert_eq!(parse_secure_boot(&[0x06, 0, 0, 0, 1]), Some(true));
+        assert_eq!(parse_secure_boot(&[0x06, 0, 0, 0, 0]), Some(false));
+        assert_eq!(parse_secure_boot(&[0x06, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn cmdline_drops_bootloader_parameters() {
+        let cmdline = "BOOT_IMAGE=/boot/vmlinuz-6.8.0 root=UUID=0f3e ro  quiet splash initrd=\\initrd.img\n";
+        assert_eq!(filter_cmdline(cmdline), "root=UUID=0f3e ro quiet splash");
+    }
+
+    #[test]
+    fn cpu_states_are_shares_of_the_elapsed_time() {
+        let before = parse_cpu_state_times("cpu  100 20 50 800 30 0 0 0 0 0\ncpu0 100 20 50 800 30 0 0 0 0 0\n").unwrap();
+        assert_eq!(before, (120, 50, 830, 1000));
+        let after = parse_cpu_state_times("cpu  250 30 90 1380 40 5 5 0 0 0\n").unwrap();
+        assert_eq!(cpu_states_between(before, after), Some((20.0, 6.25, 73.75)));
+        assert_eq!(cpu_states_between(after, after), None);
+    }
+
+    #[test]
+    fn adapter_watts_from_the_maximum_voltage_and_current() {
+        assert_eq!(adapter_watts(20_000_000.0, 4_750_000.0), Some(95));
+        assert_eq!(adapter_watts(5_000_000.0, 0.0), None);
+    }
+
+    #[test]
+    fn fstrim_timer_state_and_schedule() {
+        let enabled = "LoadState=loaded\nUnitFileState=enabled\n\
+                       TimersCalendar={ OnCalendar=weekly ; next_elapse=Mon 2024-06-10 00:00:00 UTC }\n";
+        assert_eq!(parse_fstrim_timer(enabled), Some((true, Some(String::from("weekly")))));
+        let disabled = "LoadState=loaded\nUnitFileState=disabled\nTimersCalendar={ OnCalendar=weekly ; next_elapse=n/a }\n";
+        assert_eq!(parse_fstrim_timer(disabled), Some((false, Some(String::from("weekly")))));
+        assert_eq!(parse_fstrim_timer("LoadState=not-found\nUnitFileState=\nTimersCalendar=\n"), None);
+    }
+
+    #[test]
+    fn pending_updates_from_package_manager_output() {
+        let apt = "Listing... Done\n\
+                   bash/jammy-updates 5.1-6ubuntu1.1 amd64 [upgradable from: 5.1-6ubuntu1]\n\
+                   libssl3/jammy-updates,jammy-security 3.0.2-0ubuntu1.15 amd64 [upgradable from: 3.0.2-0ubuntu1.14]\n";
+        assert_eq!(parse_apt_upgradable(apt), (2, Some(1)));
+        let dnf = "\nkernel.x86_64    6.8.9-300.fc40    updates\nopenssl.x86_64    1:3.2.1-2.fc40    updates\n\
+                   Obsoleting Packages\ngrub2-tools.x86_64    1:2.06-1.fc40    updates\n";
+        assert_eq!(parse_dnf_check_update(dnf), ["kernel.x86_64", "openssl.x86_64"]);
+        let advisories = "FEDORA-2024-1a Moderate/Sec. openssl-1:3.2.1-2.fc40.x86_64\nFEDORA-2024-2b Important/Sec. openssl-1:3.2.1-2.fc40.x86_64\n";
+        assert_eq!(parse_dnf_security(advisories), 1);
+    }
+}
@@ -0,0 +1,236 @@
+//! The art beside the info lines: built in, or loaded from `logo_file`.
+
+use std::path::Path;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+const BUILTIN: [&str; 9] = [
+    "       :#.",
+    "       :#-:****************+",
+    "         -::::::::.......:::",
+    "   .#*               -**=:.",
+    "    #-::            =%%=:.",
+    "     --::.        :*%#::",
+    "       -:::.    .=%%-:",
+    "         :::=######:.",
+    "          .::::::..",
+];
+
+/// Columns between the widest logo row and the info lines, unless the
+/// config sets `gap`.
+pub const GAP: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct Logo {
+    /// Every row padded to `width`.
+    rows: Vec<String>,
+    width: usize,
+    /// Trailing columns of `width` that separate the art from the info lines.
+    gap: usize,
+    /// Per-character color numbers from `logo_mask`, one list per row; 0
+    /// keeps the row's color.
+    mask: Option<Vec<Vec<u8>>>,
+}
+
+impl Default for Logo {
+    fn default() -> Self {
+        return Logo::parse(&BUILTIN.join("\n"));
+    }
+}
+
+impl Logo {
+    /// Strips `\r` and trailing whitespace from every line, drops trailing
+    /// blank lines, and pads the rows to the widest one, so hand-edited or
+    /// Windows-authored files line up.
+    pub fn parse(text: &str) -> Logo {
+        let mut lines: Vec<&str> = text.split('\n').map(|line| line.trim_end()).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) + GAP;
+        let rows = lines
+            .iter()
+            .map(|line| format!("{}{}", line, " ".repeat(width - line.width())))
+            .collect();
+        return Logo { rows, width, gap: GAP, mask: None };
+    }
+
+    /// Puts `gap` columns between the art and the info lines (`gap` in the
+    /// config) instead of the default.
+    pub fn with_gap(self, gap: usize) -> Logo {
+        let width = self.width - self.gap + gap;
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| format!("{}{}", row.trim_end(), " ".repeat(width - row.trim_end().width())))
+            .collect();
+        return Logo { rows, width, gap, mask: self.mask };
+    }
+
+    /// Treats the art as `art_width` columns wide instead of its measured
+    /// width (`--logo-width`), so the info lines start at `art_width` plus
+    /// the gap. Rows wider than that are cut.
+    pub fn with_width(self, art_width: usize) -> Logo {
+        let width = art_width + self.gap;
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut cut = String::new();
+                let mut used = 0;
+                for c in row.trim_end().chars() {
+                    let char_width = c.width().unwrap_or(0);
+                    if used + char_width > art_width {
+                        break;
+                    }
+                    used += char_width;
+                    cut.push(c);
+                }
+                return format!("{}{}", cut, " ".repeat(width - used));
+            })
+            .collect();
+        return Logo { rows, width, gap: self.gap, mask: self.mask };
+    }
+
+    /// Shrinks the art to `scale` (`--logo-scale`, in `0.0..=1.0`) of its
+    /// rows and columns by sampling them evenly, e.g. every other one at 0.5.
+    pub fn scaled(self, scale: f64) -> Logo {
+        if scale >= 1.0 {
+            return self;
+        }
+        // Source index of each kept row or column.
+        let sample = |len: usize| {
+            let kept = ((len as f64 * scale).ceil() as usize).max(1).min(len);
+            return (0..kept).map(move |idx| (idx as f64 / scale) as usize).filter(move |source| *source < len);
+        };
+        let art: Vec<Vec<char>> = self.rows.iter().map(|row| row.trim_end().chars().collect()).collect();
+        let lines: Vec<String> = sample(art.len())
+            .map(|row| sample(art[row].len()).map(|column| art[row][column]).collect())
+            .collect();
+        let mask = self.mask.map(|mask| {
+            sample(art.len())
+                .map(|row| sample(art[row].len()).map(|column| mask[row].get(column).copied().unwrap_or(0)).collect())
+                .collect()
+        });
+        return Logo { mask, ..Logo::parse(&lines.join("\n")).with_gap(self.gap) };
+    }
+
+    /// Colors characters individually from a mask laid over the art: digit
+    /// `N` at a position colors the character there with the `N`th mask
+    /// color, spaces and `0` keep the row color. The mask needs a line per
+    /// row of art, none longer than its row.
+    pub fn with_mask(self, text: &str) -> Result<Logo, String> {
+        let mut lines: Vec<&str> = text.split('\n').map(|line| line.trim_end()).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        if lines.len() != self.rows.len() {
+            return Err(format!("the mask has {} lines but the logo has {}", lines.len(), self.rows.len()));
+        }
+        let mut mask = vec![];
+        for (index, (line, row)) in lines.iter().zip(&self.rows).enumerate() {
+            let art_len = row.trim_end().chars().count();
+            if line.chars().count() > art_len {
+                return Err(format!("mask line {} is longer than the logo row ({} characters)", index + 1, art_len));
+            }
+            let numbers = line
+                .chars()
+                .map(|c| match c {
+                    ' ' => Ok(0),
+                    '0'..='9' => Ok(c as u8 - b'0'),
+                    _ => Err(format!("mask line {}: `{}` is not a digit or space", index + 1, c)),
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            mask.push(numbers);
+        }
+        return Ok(Logo { mask: Some(mask), ..self });
+    }
+
+    pub fn load(path: &Path) -> Result<Logo, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        return Ok(Logo::parse(&text));
+    }
+
+    pub fn rows(&self) -> &[String] {
+        return &self.rows;
+    }
+
+    /// Color numbers for the characters of row `index`, with a mask.
+    pub fn mask_row(&self, index: usize) -> Option<&[u8]> {
+        return self.mask.as_ref()?.get(index).map(Vec::as_slice);
+    }
+
+    pub fn height(&self) -> usize {
+        return self.rows.len();
+    }
+
+    /// Display width of every row, gap included.
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_logo_keeps_its_layout() {
+        let logo = Logo::default();
+        assert_eq!(logo.height(), 9);
+        assert_eq!(logo.width(), 32);
+        assert!(logo.rows().iter().all(|row| row.width() == 32));
+    }
+
+    #[test]
+    fn size_comes_from_the_art_in_display_columns() {
+        let logo = Logo::parse("日本\nab\nabc");
+        assert_eq!(logo.height(), 3);
+        assert_eq!(logo.width(), 8);
+        assert_eq!(logo.rows(), ["日本    ", "ab      ", "abc     "]);
+    }
+
+    #[test]
+    fn crlf_logo_file_is_normalized() {
+        let path = std::env::temp_dir().join(format!("ashwin-fetch-logo-{}.txt", std::process::id()));
+        std::fs::write(&path, " /\\ \r\n/  \\   \r\n\\__/\r\n\r\n").unwrap();
+        let logo = Logo::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let logo = logo.unwrap();
+        assert_eq!(logo.rows(), [" /\\     ", "/  \\    ", "\\__/    "]);
+        assert_eq!(logo.width(), 8);
+    }
+
+    #[test]
+    fn logo_width_override_pads_and_cuts() {
+        let logo = Logo::parse("ab\nabcdef").with_width(4);
+        assert_eq!(logo.width(), 8);
+        assert_eq!(logo.rows(), ["ab      ", "abcd    "]);
+    }
+
+    #[test]
+    fn half_scale_keeps_every_other_row_and_column() {
+        let logo = Logo::parse("abcd\nefgh\nijkl").scaled(0.5);
+        assert_eq!(logo.rows(), ["ac    ", "ik    "]);
+        assert_eq!(Logo::parse("ab").scaled(1.0).rows(), ["ab    "]);
+    }
+
+    #[test]
+    fn mask_must_fit_the_art() {
+        let logo = Logo::parse("/\\\n\\/");
+        let masked = logo.clone().with_mask("12\n 1\n\n").unwrap();
+        assert_eq!(masked.mask_row(0), Some(&[1, 2][..]));
+        assert_eq!(masked.mask_row(1), Some(&[0, 1][..]));
+        assert!(logo.clone().with_mask("12").unwrap_err().contains("1 lines but the logo has 2"));
+        assert!(logo.clone().with_mask("123\n1").unwrap_err().contains("longer"));
+        assert!(logo.with_mask("1x\n1").unwrap_err().contains("`x`"));
+    }
+
+    #[test]
+    fn gap_applies_to_every_row() {
+        let logo = Logo::parse("ab\nabcd").with_gap(1);
+        assert_eq!(logo.width(), 5);
+        assert_eq!(logo.rows(), ["ab   ", "abcd "]);
+        assert_eq!(logo.with_width(2).rows(), ["ab ", "ab "]);
+    }
+}
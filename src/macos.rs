@@ -0,0 +1,174 @@
+//! Readers for macOS-only sources (`sysctl`, IOKit tools).
+
+use std::process::Command;
+
+/// `hw.model` identifiers mapped to the names shown in "About This Mac".
+/// Identifiers missing here are displayed as-is.
+const MAC_MODELS: &[(&str, &str)] = &[
+    ("MacBookAir10,1", "MacBook Air (M1, 2020)"),
+    ("Mac14,2", "MacBook Air (M2, 2022)"),
+    ("Mac14,15", "MacBook Air (15-inch, M2, 2023)"),
+    ("Mac15,12", "MacBook Air (13-inch, M3, 2024)"),
+    ("Mac15,13", "MacBook Air (15-inch, M3, 2024)"),
+    ("MacBookPro17,1", "MacBook Pro (13-inch, M1, 2020)"),
+    ("MacBookPro18,1", "MacBook Pro (16-inch, 2021)"),
+    ("MacBookPro18,2", "MacBook Pro (16-inch, 2021)"),
+    ("MacBookPro18,3", "MacBook Pro (14-inch, 2021)"),
+    ("MacBookPro18,4", "MacBook Pro (14-inch, 2021)"),
+    ("Mac14,7", "MacBook Pro (13-inch, M2, 2022)"),
+    ("Mac14,5", "MacBook Pro (14-inch, 2023)"),
+    ("Mac14,9", "MacBook Pro (14-inch, 2023)"),
+    ("Mac14,6", "MacBook Pro (16-inch, 2023)"),
+    ("Mac14,10", "MacBook Pro (16-inch, 2023)"),
+    ("Mac15,3", "MacBook Pro (14-inch, M3, Nov 2023)"),
+    ("Mac15,6", "MacBook Pro (14-inch, M3 Pro or M3 Max, Nov 2023)"),
+    ("Mac15,8", "MacBook Pro (14-inch, M3 Pro or M3 Max, Nov 2023)"),
+    ("Mac15,7", "MacBook Pro (16-inch, Nov 2023)"),
+    ("Mac15,9", "MacBook Pro (16-inch, Nov 2023)"),
+    ("Macmini9,1", "Mac mini (M1, 2020)"),
+    ("Mac14,3", "Mac mini (2023)"),
+    ("Mac14,12", "Mac mini (2023)"),
+    ("iMac21,1", "iMac (24-inch, M1, 2021)"),
+    ("iMac21,2", "iMac (24-inch, M1, 2021)"),
+    ("Mac15,4", "iMac (24-inch, 2023)"),
+    ("Mac15,5", "iMac (24-inch, 2023)"),
+    ("Mac13,1", "Mac Studio (2022)"),
+    ("Mac13,2", "Mac Studio (2022)"),
+    ("Mac14,13", "Mac Studio (2023)"),
+    ("Mac14,14", "Mac Studio (2023)"),
+    ("Mac14,8", "Mac Pro (2023)"),
+];
+
+fn sysctl(name: &str) -> Option<String> {
+    let output = Command::new("sysctl").args(["-n", name]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    return if value.is_empty() { None } else { Some(value) };
+}
+
+/// `(level, bytes)` of the L1 (data plus instruction), L2 and L3 caches,
+/// totalled over the CPUs: `hw.cacheconfig` says how many logical CPUs share
+/// each level, so the per-cache size is multiplied by the number of caches.
+pub fn get_cpu_caches() -> Vec<(u8, u64)> {
+    let number = |name: &str| sysctl(name).and_then(|value| value.parse::<u64>().ok());
+    let Some(logical_cpus) = number("hw.logicalcpu") else {
+        return vec![];
+    };
+    let sharing: Vec<u64> = sysctl("hw.cacheconfig")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|count| count.parse().ok())
+        .collect();
+    let sizes = [
+        (1, number("hw.l1dcachesize").zip(number("hw.l1icachesize")).map(|(data, instruction)| data + instruction)),
+        (2, number("hw.l2cachesize")),
+        (3, number("hw.l3cachesize")),
+    ];
+    return sizes
+        .into_iter()
+        .filter_map(|(level, size)| {
+            let size = size.filter(|size| *size > 0)?;
+            let shared_by = sharing.get(level as usize).copied().filter(|count| *count > 0).unwrap_or(1);
+            return Some((level, size * logical_cpus.div_ceil(shared_by)));
+        })
+        .collect();
+}
+
+/// `(performance, efficiency, logical CPUs)` on Apple Silicon, where perf
+/// level 0 is the performance cluster. Intel Macs have a single level.
+pub fn get_hybrid_core_counts() -> Option<(usize, usize, usize)> {
+    let number = |name: &str| sysctl(name).and_then(|value| value.parse::<usize>().ok());
+    if number("hw.nperflevels")? < 2 {
+        return None;
+    }
+    let threads = number("hw.perflevel0.logicalcpu")? + number("hw.perflevel1.logicalcpu")?;
+    return Some((number("hw.perflevel0.physicalcpu")?, number("hw.perflevel1.physicalcpu")?, threads));
+}
+
+/// `kern.memorystatus_vm_pressure_level`: 1 normal, 2 warning, 4 critical,
+/// the tiers Activity Monitor's memory pressure graph shows.
+pub fn get_memory_pressure_level() -> Option<u32> {
+    return sysctl("kern.memorystatus_vm_pressure_level")?.parse().ok();
+}
+
+pub fn get_mac_model() -> Option<String> {
+    let identifier = sysctl("hw.model")?;
+    return match MAC_MODELS.iter().find(|(id, _)| *id == identifier) {
+        Some((_, name)) => Some(name.to_string()),
+        None => Some(identifier),
+    };
+}
+
+/// `(charge percent, health percent, cycle count)` from the
+/// `AppleSmartBattery` IOKit entry. Health is the raw full capacity against
+/// the design capacity, as "Maximum Capacity" in System Settings.
+pub fn get_battery() -> Option<(f64, Option<f64>, Option<u32>)> {
+    let output = Command::new("ioreg").args(["-rn", "AppleSmartBattery"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let properties = String::from_utf8_lossy(&output.stdout);
+    let number = |key: &str| {
+        let prefix = format!("\"{}\" = ", key);
+        return properties.lines().find_map(|line| line.trim().strip_prefix(prefix.as_str())?.parse::<f64>().ok());
+    };
+    let percent = number("CurrentCapacity")? / number("MaxCapacity").filter(|max| *max > 0.0)? * 100.0;
+    let health = number("AppleRawMaxCapacity")
+        .zip(number("DesignCapacity").filter(|design| *design > 0.0))
+        .map(|(full, design)| full / design * 100.0);
+    let cycles = number("CycleCount").map(|cycles| cycles as u32);
+    return Some((percent, health, cycles));
+}
+
+/// `(on AC, adapter watts)` from the same IOKit entry: `ExternalConnected`
+/// and the `Watts` of `AdapterDetails`. Macs without a battery have none.
+pub fn get_power_source() -> Option<(bool, Option<u32>)> {
+    let output = Command::new("ioreg").args(["-rn", "AppleSmartBattery"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let properties = String::from_utf8_lossy(&output.stdout);
+    let on_ac = properties.lines().find_map(|line| line.trim().strip_prefix("\"ExternalConnected\" = "))? == "Yes";
+    let watts = properties
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("\"AdapterDetails\" = "))
+        .and_then(|details| details.split_once("\"Watts\"=")?.1.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok())
+        .filter(|watts| *watts > 0 && on_ac);
+    return Some((on_ac, watts));
+}
+
+/// SSID of the connected Wi-Fi network. CoreWLAN would also give the signal
+/// strength but needs Objective-C bindings, so `networksetup` is asked
+/// instead, for the Wi-Fi device first and then its network.
+pub fn get_wifi_ssid() -> Option<String> {
+    let run = |args: &[&str]| {
+        let output = Command::new("networksetup").args(args).output().ok()?;
+        return if output.status.success() { Some(String::from_utf8_lossy(&output.stdout).into_owned()) } else { None };
+    };
+    let ports = run(&["-listallhardwareports"])?;
+    let mut lines = ports.lines();
+    lines.find(|line| line.trim() == "Hardware Port: Wi-Fi")?;
+    let device = lines.next()?.trim().strip_prefix("Device: ")?.to_string();
+    let network = run(&["-getairportnetwork", &device])?;
+    let ssid = network.trim().strip_prefix("Current Wi-Fi Network: ")?.trim();
+    return if ssid.is_empty() { None } else { Some(ssid.to_string()) };
+}
+
+/// FileVault state from `fdesetup status`: `FileVault (active)` when it's
+/// on, `none` when it's off. Encrypting or decrypting counts as on.
+pub fn get_filevault_status() -> Option<String> {
+    let output = Command::new("fdesetup").arg("status").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let status = String::from_utf8_lossy(&output.stdout);
+    if status.contains("FileVault is On") {
+        return Some(String::from("FileVault (active)"));
+    }
+    if status.contains("FileVault is Off") {
+        return Some(String::from("none"));
+    }
+    return None;
+}
@@ -1,31 +1,105 @@
+#![allow(clippy::needless_return)]
+
+mod cache;
+mod cli;
+mod codename;
+mod collector;
+mod color;
+mod compare;
+mod config;
+mod field;
+mod git;
+#[cfg(target_os = "linux")]
+mod linux;
+mod logo;
+#[cfg(target_os = "macos")]
+mod macos;
+mod nvidia;
+mod svg;
+mod toolchain;
+mod update;
+#[cfg(windows)]
+mod windows;
+
+use cache::FieldCache;
 use chrono::Duration;
+use cli::BorderStyle;
+use cli::GpuSort;
+use cli::IpMode;
+use cli::OutputFormat;
+use cli::Theme;
+use color::Palette;
+use collector::Collector;
+use collector::CommandCollector;
+use collector::EnvVarCollector;
+use collector::Pending;
+use config::CpuUsageSource;
+use config::FrequencyUnit;
+use config::GpuNameStyle;
+use config::HeaderPosition;
+use config::MemoryBasis;
+use config::MemoryDisplay;
+use config::MemorySource;
+use config::SizeUnit;
+use config::Thresholds;
+use config::Units;
+use field::Field;
+use logo::Logo;
+use num_format::Locale;
+use num_format::ToFormattedString;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde::ser::SerializeMap;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::fmt::Debug;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::UdpSocket;
+use std::path::PathBuf;
 use std::process::ExitCode;
+use std::time::Instant;
+use sysinfo::Components;
+use sysinfo::CpuRefreshKind;
+use sysinfo::MemoryRefreshKind;
 use sysinfo::Motherboard;
+use sysinfo::Networks;
+use sysinfo::ProcessesToUpdate;
+use sysinfo::RefreshKind;
 use sysinfo::System;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
 use wgpu::Backends;
 use wgpu::Instance;
 use wgpu::InstanceDescriptor;
+use winit::application::ApplicationHandler;
+use winit::event::StartCause;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::EventLoop;
+use winit::platform::pump_events::EventLoopExtPumpEvents;
+use winit::window::WindowId;
 
-const LOGO_HEIGHT: usize = 9;
-const LOGO_WIDTH: usize = 32;
-const LOGO: [&str; LOGO_HEIGHT] = [
-    "       :#.                      ",
-    "       :#-:****************+    ",
-    "         -::::::::.......:::    ",
-    "   .#*               -**=:.     ",
-    "    #-::            =%%=:.      ",
-    "     --::.        :*%#::        ",
-    "       -:::.    .=%%-:          ",
-    "         :::=######:.           ",
-    "          .::::::..             ",
-];
-
+const DEFAULT_LABEL_WIDTH: usize = 11;
+/// With more GPUs than this, identical ones are collapsed into one line.
+const GPU_GROUP_THRESHOLD: usize = 2;
+#[derive(Serialize)]
 struct CpuInfo {
     num_cores: usize,
-    avg_usage: f64,
+    /// `None` when usage wasn't sampled (`--fast`).
+    avg_usage: Option<f64>,
     max_frequency_mhz: f64,
+    base_frequency_mhz: Option<f64>,
+    boost_frequency_mhz: Option<f64>,
+    /// Microarchitecture or codename, `None` when the brand isn't known.
+    codename: Option<&'static str>,
 }
 
 impl Debug for CpuInfo {
@@ -34,13 +108,148 @@ impl Debug for CpuInfo {
             .field("num_cores", &self.num_cores)
             .field("avg_usage", &self.avg_usage)
             .field("max_frequency_mhz", &self.max_frequency_mhz)
+            .field("base_frequency_mhz", &self.base_frequency_mhz)
+            .field("boost_frequency_mhz", &self.boost_frequency_mhz)
+            .field("codename", &self.codename)
+            .finish()
+    }
+}
+
+/// The fields `--qr` encodes, few enough that the code stays small enough to
+/// scan from a terminal.
+#[derive(Serialize)]
+struct QrSummary<'a> {
+    os: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kernel: Option<&'a str>,
+    cpu: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    gpu: Vec<&'a str>,
+    memory_mb: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uptime_seconds: Option<usize>,
+}
+
+/// Where CPU time went system-wide, for `--cpu-breakdown`.
+#[derive(Serialize)]
+struct CpuBreakdown {
+    user_percent: f64,
+    system_percent: f64,
+    idle_percent: f64,
+}
+
+impl Debug for CpuBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CpuBreakdown")
+            .field("user_percent", &self.user_percent)
+            .field("system_percent", &self.system_percent)
+            .field("idle_percent", &self.idle_percent)
+            .finish()
+    }
+}
+
+/// Logical CPUs the kernel knows about vs. those currently online.
+#[derive(Serialize)]
+struct CoreCounts {
+    configured: usize,
+    online: usize,
+}
+
+impl Debug for CoreCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoreCounts")
+            .field("configured", &self.configured)
+            .field("online", &self.online)
+            .finish()
+    }
+}
+
+/// Physical cores by type on hybrid CPUs (Intel P/E cores, Apple Silicon
+/// performance/efficiency clusters).
+#[derive(Serialize)]
+struct HybridCores {
+    performance: usize,
+    efficiency: usize,
+    /// Logical CPUs across both types.
+    threads: usize,
+}
+
+impl Debug for HybridCores {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HybridCores")
+            .field("performance", &self.performance)
+            .field("efficiency", &self.efficiency)
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
+
+/// How the firmware booted the OS.
+#[derive(Serialize)]
+struct FirmwareInfo {
+    uefi: bool,
+    /// Only known on UEFI, and only when the firmware variable is readable.
+    secure_boot: Option<bool>,
+}
+
+impl Debug for FirmwareInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FirmwareInfo")
+            .field("uefi", &self.uefi)
+            .field("secure_boot", &self.secure_boot)
+            .finish()
+    }
+}
+
+/// How the CPUs are laid out across packages and memory nodes.
+#[derive(Serialize)]
+struct CpuTopology {
+    sockets: usize,
+    numa_nodes: Option<usize>,
+    physical_cores: usize,
+}
+
+impl Debug for CpuTopology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CpuTopology")
+            .field("sockets", &self.sockets)
+            .field("numa_nodes", &self.numa_nodes)
+            .field("physical_cores", &self.physical_cores)
+            .finish()
+    }
+}
+
+#[derive(Serialize)]
+struct CpuTemperature {
+    sensor: String,
+    celsius: f32,
+}
+
+impl Debug for CpuTemperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CpuTemperature")
+            .field("sensor", &self.sensor)
+            .field("celsius", &self.celsius)
             .finish()
     }
 }
 
+#[derive(Deserialize, Serialize)]
 struct GpuInfo {
     device_index: usize,
     gpu_name: String,
+    #[serde(serialize_with = "serialize_device_type", deserialize_with = "deserialize_device_type")]
+    device_type: wgpu::DeviceType,
+    vram_mb: Option<usize>,
+    /// VRAM in use, only read with `--gpu-live`.
+    vram_used_mb: Option<usize>,
+    /// Current board power, only read with `--gpu-live`.
+    power_watts: Option<f64>,
+    /// Highest graphics API version of this adapter's backend, e.g.
+    /// `Vulkan 1.3`. Only read with `--show-api-versions` or `--gpu-live`.
+    api_version: Option<String>,
+    /// PCI address such as `0000:01:00.0`, when the platform exposes it.
+    pci_bus_id: Option<String>,
 }
 
 impl Debug for GpuInfo {
@@ -48,183 +257,4792 @@ impl Debug for GpuInfo {
         f.debug_struct("GpuInfo")
             .field("device_index", &self.device_index)
             .field("gpu_name", &self.gpu_name)
+            .field("device_type", &self.device_type)
+            .field("vram_mb", &self.vram_mb)
+            .field("vram_used_mb", &self.vram_used_mb)
+            .field("power_watts", &self.power_watts)
+            .field("api_version", &self.api_version)
+            .field("pci_bus_id", &self.pci_bus_id)
+            .finish()
+    }
+}
+
+fn serialize_device_type<S: Serializer>(device_type: &wgpu::DeviceType, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_str(match device_type {
+        wgpu::DeviceType::DiscreteGpu => "discrete",
+        wgpu::DeviceType::IntegratedGpu => "integrated",
+        wgpu::DeviceType::VirtualGpu => "virtual",
+        wgpu::DeviceType::Cpu => "cpu",
+        wgpu::DeviceType::Other => "other",
+    });
+}
+
+/// Reads back what `serialize_device_type` wrote, for the field cache.
+fn deserialize_device_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<wgpu::DeviceType, D::Error> {
+    return Ok(match String::deserialize(deserializer)?.as_str() {
+        "discrete" => wgpu::DeviceType::DiscreteGpu,
+        "integrated" => wgpu::DeviceType::IntegratedGpu,
+        "virtual" => wgpu::DeviceType::VirtualGpu,
+        "cpu" => wgpu::DeviceType::Cpu,
+        _ => wgpu::DeviceType::Other,
+    });
+}
+
+/// One connected monitor, in physical pixels.
+#[derive(Clone, Serialize)]
+struct DisplayInfo {
+    width: u32,
+    height: u32,
+    refresh_hz: Option<f64>,
+    /// Bits per color the panel accepts, from its EDID.
+    bit_depth: Option<u8>,
+    /// `None` when the EDID couldn't be read.
+    hdr: Option<bool>,
+    primary: bool,
+}
+
+impl Debug for DisplayInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DisplayInfo")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("refresh_hz", &self.refresh_hz)
+            .field("bit_depth", &self.bit_depth)
+            .field("hdr", &self.hdr)
+            .field("primary", &self.primary)
+            .finish()
+    }
+}
+
+/// Charge and wear of the system battery.
+#[derive(Serialize)]
+struct BatteryInfo {
+    percent: f64,
+    /// Full capacity as a share of the design capacity.
+    health_percent: Option<f64>,
+    cycle_count: Option<u32>,
+}
+
+impl Debug for BatteryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatteryInfo")
+            .field("percent", &self.percent)
+            .field("health_percent", &self.health_percent)
+            .field("cycle_count", &self.cycle_count)
+            .finish()
+    }
+}
+
+/// Where a laptop draws power from, for `--show-power`.
+#[derive(Serialize)]
+struct PowerInfo {
+    on_ac: bool,
+    /// Only some adapters (mostly USB-C) report it.
+    adapter_watts: Option<u32>,
+}
+
+impl Debug for PowerInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowerInfo")
+            .field("on_ac", &self.on_ac)
+            .field("adapter_watts", &self.adapter_watts)
+            .finish()
+    }
+}
+
+/// The connected Wi-Fi network.
+#[derive(Serialize)]
+struct WifiInfo {
+    ssid: String,
+    signal_dbm: Option<i32>,
+}
+
+impl Debug for WifiInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WifiInfo")
+            .field("ssid", &self.ssid)
+            .field("signal_dbm", &self.signal_dbm)
+            .finish()
+    }
+}
+
+/// The process that started this one, usually the user's shell.
+#[derive(Serialize)]
+struct ParentProcess {
+    pid: u32,
+    name: String,
+}
+
+impl Debug for ParentProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParentProcess")
+            .field("pid", &self.pid)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Upgradable packages, for `--show-updates`.
+#[derive(Serialize)]
+struct PendingUpdates {
+    count: usize,
+    /// `None` where the package manager has no security metadata (pacman).
+    security: Option<usize>,
+}
+
+impl Debug for PendingUpdates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingUpdates")
+            .field("count", &self.count)
+            .field("security", &self.security)
+            .finish()
+    }
+}
+
+/// How the user reached this shell, for `--show-session`.
+#[derive(Serialize)]
+struct SessionInfo {
+    /// `SSH (remote)`, `RDP (remote)`, `desktop (wayland)` or `console`.
+    kind: Option<String>,
+    /// Root on Unix, an elevated token on Windows.
+    privileged: Option<bool>,
+    /// Name of the privileged account when `privileged`, `root` on most
+    /// Unix systems. `None` on Windows or when it couldn't be looked up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+}
+
+impl Debug for SessionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionInfo")
+            .field("kind", &self.kind)
+            .field("privileged", &self.privileged)
+            .field("account", &self.account)
+            .finish()
+    }
+}
+
+/// The heaviest CPU and memory consumers, for `--show-top`.
+#[derive(Serialize)]
+struct TopProcesses {
+    cpu_name: String,
+    /// Of one core, so a busy multithreaded process can pass 100.
+    cpu_percent: f32,
+    memory_name: String,
+    memory_bytes: u64,
+}
+
+impl Debug for TopProcesses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopProcesses")
+            .field("cpu_name", &self.cpu_name)
+            .field("cpu_percent", &self.cpu_percent)
+            .field("memory_name", &self.memory_name)
+            .field("memory_bytes", &self.memory_bytes)
+            .finish()
+    }
+}
+
+/// One mounted filesystem, in bytes.
+#[derive(Clone, Serialize)]
+struct DiskInfo {
+    mount_point: String,
+    file_system: String,
+    used_bytes: u64,
+    total_bytes: u64,
+    /// Spinning disk (`true`) or solid state; `None` when unknown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rotational: Option<bool>,
+    /// Backed by an NVMe device.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    nvme: bool,
+}
+
+impl DiskInfo {
+    /// `NVMe`, `SSD` or `HDD`, when the storage type is known.
+    fn storage_type(&self) -> Option<&'static str> {
+        if self.nvme {
+            return Some("NVMe");
+        }
+        return self.rotational.map(|rotational| if rotational { "HDD" } else { "SSD" });
+    }
+}
+
+impl Debug for DiskInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskInfo")
+            .field("mount_point", &self.mount_point)
+            .field("file_system", &self.file_system)
+            .field("used_bytes", &self.used_bytes)
+            .field("total_bytes", &self.total_bytes)
+            .field("rotational", &self.rotational)
+            .field("nvme", &self.nvme)
             .finish()
     }
 }
 
+/// Characters the renderer draws itself. `ASCII_GLYPHS` stands in on
+/// terminals and fonts without Unicode coverage.
+struct Glyphs {
+    ellipsis: &'static str,
+    degree: &'static str,
+    dash: &'static str,
+    /// Sparkline bars, lowest first.
+    spark: [char; 8],
+}
+
+const UNICODE_GLYPHS: Glyphs = Glyphs {
+    ellipsis: "…",
+    degree: "°",
+    dash: "—",
+    spark: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+};
+const ASCII_GLYPHS: Glyphs = Glyphs {
+    ellipsis: "...",
+    degree: "",
+    dash: "-",
+    spark: ['_', '_', '.', '.', '-', '-', '^', '^'],
+};
+
+/// Presentation choices that don't depend on the collected data.
+#[derive(Debug, Default)]
+struct RenderOptions {
+    padding: bool,
+    /// Where the `user@host` line and its underline go, if anywhere.
+    header_position: HeaderPosition,
+    /// `header_separator`; `None` underlines with `-`.
+    header_separator: Option<String>,
+    /// Escape for the underline's `header_separator_color`; only set along
+    /// with `color`.
+    header_separator_color: Option<String>,
+    fqdn: bool,
+    /// Keep the `DOMAIN\` prefix of the username in the header.
+    keep_domain: bool,
+    /// Terminal columns available; `None` disables width-based formatting.
+    width: Option<usize>,
+    label_width: Option<usize>,
+    /// One line per GPU even when identical ones could be grouped.
+    gpu_list: bool,
+    /// Added to `device_index` in the `GPU` labels (0 or 1).
+    gpu_index_base: usize,
+    /// Most GPU lines shown before the rest are summed up; `None` shows all.
+    max_gpus: Option<usize>,
+    ascii_only: bool,
+    verbose: u8,
+    memory_display: MemoryDisplay,
+    gpu_name_style: GpuNameStyle,
+    frequency_unit: FrequencyUnit,
+    /// Decimals of GHz figures.
+    frequency_decimals: usize,
+    border: BorderStyle,
+    /// Fields picked with `--fields`; empty shows all of them.
+    fields: Vec<Field>,
+    /// Which addresses `--show-ip` asked for; `None` leaves the line out.
+    ip: Option<IpMode>,
+    /// Show `N/A` for optional fields that came up empty instead of hiding them.
+    show_empty: bool,
+    /// Thousands separators for large numbers; never used for JSON.
+    digit_grouping: Option<Locale>,
+    /// Spelled-out uptime units from `--locale-uptime`; `None` keeps `1d 2h 3m`.
+    uptime_words: Option<&'static UptimeWords>,
+    /// Append the boot time, `(since 2024-06-01 08:14)`, to the uptime.
+    uptime_since: bool,
+    /// Leave the `Cmdline` line uncut by the terminal width.
+    full_cmdline: bool,
+    logo_colors: Vec<String>,
+    /// Escapes for the `logo_mask` color numbers; empty without color.
+    logo_mask_colors: Vec<String>,
+    logo: Logo,
+    /// Values may carry ANSI colors (text output to a terminal only).
+    color: bool,
+    /// One CPU line for every brand instead of one line per brand.
+    cpu_combined: bool,
+    /// One `Mem/Swap` line instead of `Memory` and `Swap`.
+    memory_swap_combined: bool,
+    /// `--show-swap`, or implied by `memory_swap_combined`.
+    show_swap: bool,
+    /// Most columns the fields may be spread over beside the logo.
+    columns: usize,
+    /// `[units]` overrides for the memory, swap, disk and VRAM sizes.
+    units: Units,
+    /// Usage levels colored yellow and red (with `color`).
+    thresholds: Thresholds,
+    /// Recent temperatures, oldest first, for the `--watch` trend; empty
+    /// otherwise.
+    cpu_temp_history: Vec<f64>,
+    gpu_temp_history: Vec<f64>,
+    /// Status colors from `--theme`; only set along with `color`.
+    palette: Option<&'static Palette>,
+    /// Escapes for the field labels and values, from `label_color` and
+    /// `value_color` or `--theme`; only set along with `color`.
+    label_color: Option<String>,
+    value_color: Option<String>,
+}
+
+impl RenderOptions {
+    fn shows(&self, field: Field) -> bool {
+        return self.fields.is_empty() || self.fields.contains(&field);
+    }
+
+    fn glyphs(&self) -> &'static Glyphs {
+        return if self.ascii_only { &ASCII_GLYPHS } else { &UNICODE_GLYPHS };
+    }
+}
+
+/// Seconds since boot. JSON gets both the raw number and the formatted
+/// string, so consumers never have to parse `12d 3h 4m` back.
+struct Uptime(usize);
+
+impl Serialize for Uptime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("uptime", &convert_unix_to_human_string(self.0))?;
+        map.serialize_entry("uptime_seconds", &self.0)?;
+        return map.end();
+    }
+}
+
+#[derive(Serialize)]
 struct OutputInfo<'a> {
+    /// When the info was collected; only set by `--watch`, whose JSON lines
+    /// need telling apart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<String>,
     username: String,
     hostname: String,
     os: String,
+    model: Option<String>,
     serial_number: String,
-    kernel: String,
-    uptime: usize,
-    cpu: HashMap<&'a str, CpuInfo>,
+    chassis: Option<String>,
+    /// Only read with `--show-firmware`.
+    firmware: Option<FirmwareInfo>,
+    kernel: Option<String>,
+    /// `YYYY-MM-DD` the running kernel was built; only read with
+    /// `--kernel-date`.
+    kernel_build_date: Option<String>,
+    /// Newest installed kernel when it's newer than the running one; only
+    /// looked for with `--check-kernel`.
+    newer_kernel: Option<String>,
+    /// Only read with `--show-cmdline`.
+    kernel_cmdline: Option<String>,
+    updated: Option<String>,
+    /// Only asked for with `--show-updates`.
+    pending_updates: Option<PendingUpdates>,
+    failed_units: Option<usize>,
+    /// `(threads, zombies)` across all processes.
+    thread_counts: Option<(usize, usize)>,
+    /// `(open, max)` file descriptors system-wide; only read with
+    /// `--show-fds`.
+    fd_counts: Option<(usize, usize)>,
+    /// Bits in the kernel entropy pool; only read with `--show-entropy`.
+    entropy_bits: Option<usize>,
+    /// Only looked up with `--show-pid`.
+    parent_process: Option<ParentProcess>,
+    /// Only looked up with `--show-session`.
+    session: Option<SessionInfo>,
+    /// The user of each login session; only looked up with `--show-users`.
+    users: Option<Vec<String>>,
+    /// Only looked up with `--show-top`.
+    top_processes: Option<TopProcesses>,
+    #[serde(flatten)]
+    uptime: Uptime,
+    /// By brand, in name order so the output doesn't vary between runs.
+    cpu: BTreeMap<&'a str, CpuInfo>,
+    /// Only measured with `--cpu-breakdown`, on Linux.
+    cpu_breakdown: Option<CpuBreakdown>,
+    core_counts: Option<CoreCounts>,
+    hybrid_cores: Option<HybridCores>,
+    /// CPUs' worth of bandwidth granted by the cgroup, when limited.
+    cpu_limit: Option<f64>,
+    /// CPUs this process may run on, per its affinity mask.
+    cpu_affinity: Option<usize>,
+    /// `KVM`, `Hyper-V` and so on when running in a VM, whose cores are
+    /// shown as vCPUs.
+    hypervisor: Option<String>,
+    cpu_temperature: Option<CpuTemperature>,
+    /// `(level, bytes)` per cache level; only read with `--show-cache`.
+    cpu_caches: Vec<(u8, u64)>,
+    /// Only read with `--show-topology`.
+    cpu_topology: Option<CpuTopology>,
+    virtualization: Option<String>,
+    governor: Option<String>,
     gpu: Vec<GpuInfo>,
+    /// Why GPUs couldn't be enumerated at all, noted on the `GPU` line with
+    /// `-v`.
+    #[serde(skip)]
+    gpu_unavailable: Option<String>,
+    /// Adapters wgpu enumerated across all backends, before software,
+    /// unknown and duplicate ones were dropped. Only kept with `-v`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_gpu_adapters: Option<usize>,
+    /// Primary GPU's temperature in °C, only read with `--show-gpu-temp`.
+    gpu_temperature: Option<f64>,
+    displays: Vec<DisplayInfo>,
+    audio: Option<String>,
+    /// Default CUPS queue; only looked up with `--show-printer`.
+    printer: Option<String>,
+    ipv4: Option<Ipv4Addr>,
+    ipv6: Option<Ipv6Addr>,
+    gateway: Option<Ipv4Addr>,
+    /// Only looked up with `--show-wifi`, since the SSID can give away where
+    /// the machine is.
+    wifi: Option<WifiInfo>,
     memory_used_mb: usize,
     memory_total_mb: usize,
+    swap_used_mb: usize,
+    swap_total_mb: usize,
+    /// The memory figures are the container's cgroup limit, not the host's.
+    memory_cgroup_limited: bool,
+    memory_pressure: Option<String>,
+    disks: Vec<DiskInfo>,
+    /// `(used, total)` inodes on `/`; only read with `--show-inodes`, and
+    /// left out for filesystems that allocate them on demand (btrfs).
+    root_inodes: Option<(u64, u64)>,
+    /// `LUKS2 (active)`, `none` and so on for `/`; only read with
+    /// `--show-encryption`.
+    encryption: Option<String>,
+    /// `enabled (weekly)` or `disabled` for `fstrim.timer`; only read with
+    /// `--show-storage-health`.
+    trim: Option<String>,
+    /// `rustc 1.80`-style entries, only probed with `--show-toolchains`.
+    toolchains: Vec<String>,
+    /// The repository the current directory is in; only looked up with
+    /// `--show-git`.
+    repo: Option<git::RepoInfo>,
+    battery: Option<BatteryInfo>,
+    /// Only looked up with `--show-power`; left out on machines without a
+    /// battery.
+    power: Option<PowerInfo>,
+    extra_lines: Vec<String>,
+    /// Why a field's collector produced nothing, keyed by field name. Only
+    /// present with `--include-errors`; fields that simply don't apply to
+    /// this machine aren't listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<BTreeMap<&'static str, String>>,
 }
 
 fn get_username() -> String {
     return whoami::username();
 }
 
-fn get_hostname() -> String {
-    return whoami::fallible::hostname().unwrap_or(String::from("unknown"));
+/// `DOMAIN\user` -> `user`. Domain-joined Windows accounts carry the
+/// prefix, which only adds noise to the header.
+fn strip_user_domain(username: &str) -> &str {
+    return username.rsplit_once('\\').map_or(username, |(_, user)| user);
+}
+
+/// Variable the shell or OS sets to the hostname.
+#[cfg(windows)]
+const HOSTNAME_VAR: &str = "COMPUTERNAME";
+#[cfg(not(windows))]
+const HOSTNAME_VAR: &str = "HOSTNAME";
+
+/// A named place the hostname can be read from.
+type HostnameSource = (&'static str, fn() -> Option<String>);
+
+/// `-vv` explanations already printed, so `--watch` and `--serve` don't
+/// repeat the same ones every tick.
+static EXPLAINED: std::sync::Mutex<Option<HashSet<String>>> = std::sync::Mutex::new(None);
+
+/// Prints a `-vv` explanation to stderr unless it was printed before.
+fn explain_once(message: String) {
+    let mut explained = EXPLAINED.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    if explained.get_or_insert_with(HashSet::new).insert(message.clone()) {
+        eprintln!("{}", message);
+    }
+}
+
+/// The first source that knows the hostname, so the header only says
+/// `unknown` when none of them does. `explain` reports which one answered.
+/// Windows hostnames are case-insensitive and reported in whatever case
+/// they were set up with, so they're normalized to lowercase.
+fn get_hostname(explain: bool) -> String {
+    let sources: [HostnameSource; 4] = [
+        ("whoami", || whoami::fallible::hostname().ok()),
+        (HOSTNAME_VAR, || std::env::var(HOSTNAME_VAR).ok()),
+        ("/etc/hostname", || std::fs::read_to_string("/etc/hostname").ok()),
+        ("gethostname", System::host_name),
+    ];
+    for (source, read) in sources {
+        let Some(hostname) = read().map(|hostname| hostname.trim().to_string()).filter(|hostname| !hostname.is_empty()) else {
+            continue;
+        };
+        if explain {
+            explain_once(format!("hostname: `{}` from {}", hostname, source));
+        }
+        return if cfg!(windows) { hostname.to_lowercase() } else { hostname };
+    }
+    if explain {
+        explain_once(String::from("hostname: no source knew it, showing `unknown`"));
+    }
+    return String::from("unknown");
+}
+
+/// `host.local` -> `host`. mDNS and DHCP domains make the full name long
+/// without telling the user much.
+fn short_hostname(hostname: &str) -> &str {
+    return hostname.split('.').next().unwrap_or(hostname);
 }
 
+#[cfg(windows)]
+fn get_os_name() -> String {
+    return windows::get_windows_version().unwrap_or_else(whoami::distro);
+}
+
+#[cfg(not(windows))]
 fn get_os_name() -> String {
     return whoami::distro();
 }
 
+#[cfg(target_os = "macos")]
+fn get_model() -> Option<String> {
+    return macos::get_mac_model();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn get_model() -> Option<String> {
+    return None;
+}
+
 fn get_serial_number() -> String {
     return Motherboard::new()
         .and_then(|x| x.serial_number())
         .unwrap_or("xxxxxxxxxx".to_string());
 }
 
-fn kernel() -> String {
-    return System::kernel_long_version();
-}
+/// Substrings of DMI vendor/product names that give away a virtual machine,
+/// whose chassis type is usually a meaningless "Other".
+#[cfg(any(target_os = "linux", windows))]
+const HYPERVISOR_SIGNATURES: [&str; 8] = [
+    "QEMU",
+    "KVM",
+    "VMware",
+    "VirtualBox",
+    "Virtual Machine",
+    "Xen",
+    "Parallels",
+    "Bochs",
+];
 
-fn get_uptime() -> usize {
-    return System::uptime() as usize;
+/// Groups the SMBIOS chassis codes into the handful of form factors people
+/// actually talk about. "Other" and "Unknown" map to `None`.
+#[cfg(any(target_os = "linux", windows))]
+fn chassis_type_name(code: u32) -> Option<&'static str> {
+    return match code {
+        3..=7 | 13 | 15 | 16 | 24 | 34..=36 => Some("Desktop"),
+        8..=10 | 14 | 31 | 32 => Some("Laptop"),
+        11 => Some("Handheld"),
+        17 | 23 | 25 | 28 | 29 => Some("Server"),
+        30 => Some("Tablet"),
+        _ => None,
+    };
 }
 
-fn get_cpu_info<'a>(sys: &'a System) -> HashMap<&'a str, CpuInfo> {
-    let mut cpu_info_map = HashMap::<&'a str, CpuInfo>::new();
-    for cpu in sys.cpus() {
-        let entry = cpu_info_map.entry(cpu.brand()).or_insert(CpuInfo {
-            num_cores: 0,
-            avg_usage: 0.0 as f64,
-            max_frequency_mhz: 0.0 as f64,
-        });
-        entry.num_cores += 1;
-        entry.avg_usage += cpu.cpu_usage() as f64;
-        if cpu.frequency() as f64 > entry.max_frequency_mhz {
-            entry.max_frequency_mhz = cpu.frequency() as f64;
-        }
-    }
-    for (_, val) in &mut cpu_info_map {
-        val.avg_usage /= val.num_cores as f64;
+#[cfg(any(target_os = "linux", windows))]
+fn get_chassis_type() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(windows)]
+    use windows as platform;
+
+    let identity = platform::get_dmi_system_identity();
+    if HYPERVISOR_SIGNATURES.iter().any(|signature| identity.contains(signature)) {
+        return Some(String::from("VM"));
     }
-    return cpu_info_map;
+    return chassis_type_name(platform::get_dmi_chassis_type()?).map(String::from);
 }
 
-fn get_gpu_info() -> Vec<GpuInfo> {
-    let mut instance_descriptor = InstanceDescriptor::default();
-    instance_descriptor.backends = Backends::all();
-    let instance = Instance::new(&instance_descriptor);
-    let adapters = instance.enumerate_adapters(Backends::all());
-    let mut gpu_infos = vec![];
-    for (idx, adapter) in adapters.iter().enumerate() {
-        let info = adapter.get_info();
-        if info.device_type == wgpu::DeviceType::Other || info.device_type == wgpu::DeviceType::Cpu {
-            continue;
-        }
-        gpu_infos.push(GpuInfo {
-            device_index: idx,
-            gpu_name: match info.device_type {
-                wgpu::DeviceType::IntegratedGpu => format!("{} (Integrated GPU)", info.name),
-                wgpu::DeviceType::DiscreteGpu => format!("{} (Discrete GPU)", info.name),
-                wgpu::DeviceType::VirtualGpu => format!("{} (Virtual GPU)", info.name),
-                wgpu::DeviceType::Cpu => format!("{} (Software Rasterizer)", info.name),
-                wgpu::DeviceType::Other => format!("{} (unknown gpu type)", info.name),
-            },
-        });
-    }
-    gpu_infos.sort_by(|x, y| x.device_index.cmp(&y.device_index));
-    return gpu_infos;
+#[cfg(not(any(target_os = "linux", windows)))]
+fn get_chassis_type() -> Option<String> {
+    return None;
 }
 
-fn get_used_memory(sys: &System) -> usize {
-    return sys.used_memory() as usize;
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn get_battery() -> Option<BatteryInfo> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(target_os = "macos")]
+    use macos as platform;
+    #[cfg(windows)]
+    use windows as platform;
+
+    let (percent, health_percent, cycle_count) = platform::get_battery()?;
+    return Some(BatteryInfo { percent, health_percent, cycle_count });
 }
 
-fn get_total_memory(sys: &System) -> usize {
-    return sys.total_memory() as usize;
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn get_battery() -> Option<BatteryInfo> {
+    return None;
 }
 
-fn convert_unix_to_human_string(unix_time: usize) -> String {
-    let duration = Duration::seconds(unix_time as i64);
-    let days = duration.num_days();
-    let hours = duration.num_hours() % 24;
-    let minutes = duration.num_minutes() % 60;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_power_source() -> Option<PowerInfo> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(target_os = "macos")]
+    use macos as platform;
 
-    if days > 0 {
-        return format!("{}d {}h {}m", days, hours, minutes);
-    } else if hours > 0 {
-        return format!("{}h {}m", hours, minutes);
-    } else {
-        return format!("{}m", minutes);
-    }
+    let (on_ac, adapter_watts) = platform::get_power_source()?;
+    return Some(PowerInfo { on_ac, adapter_watts });
 }
 
-fn print_all_info(output_info: &OutputInfo) {
-    let mut output_info_vec = vec![
-        format!("{}@{}", output_info.username, output_info.hostname),
-        format!("{}", "-".repeat(output_info.username.len() + output_info.hostname.len() + 1)),
-        format!("OS:        {}", output_info.os),
-        format!("Serial:    {}", output_info.serial_number),
-        format!("Kernel:    {}", output_info.kernel),
-        format!("Uptime:    {}", convert_unix_to_human_string(output_info.uptime)),
-    ];
-    for (cpu_brand, cpu_info) in &output_info.cpu {
-        output_info_vec.push(format!(
-            "CPU:       {} - {} cores, {:.2}% avg, {:.2} MHz (max)",
-            cpu_brand, cpu_info.num_cores, cpu_info.avg_usage, cpu_info.max_frequency_mhz
-        ));
-    }
-    for gpu_info in &output_info.gpu {
-        output_info_vec.push(format!(
-            "GPU {:.>3}:   {}",
-            gpu_info.device_index, gpu_info.gpu_name
-        ));
-    }
-    output_info_vec.push(format!(
-        "Memory:    {}/{} MB used",
-        output_info.memory_used_mb, output_info.memory_total_mb
-    ));
-    println!();
-    for (idx, line) in output_info_vec.iter().enumerate() {
-        if idx < LOGO_HEIGHT {
-            println!("{}{}", LOGO[idx], line);
-        } else {
-            println!("{}{}", " ".repeat(LOGO_WIDTH), line);
-        }
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn get_power_source() -> Option<PowerInfo> {
+    return None;
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn get_cpu_caches() -> Vec<(u8, u64)> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(target_os = "macos")]
+    use macos as platform;
+    #[cfg(windows)]
+    use windows as platform;
+
+    return platform::get_cpu_caches();
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn get_cpu_caches() -> Vec<(u8, u64)> {
+    return vec![];
+}
+
+#[cfg(target_os = "linux")]
+fn get_kernel_build_date() -> Option<String> {
+    return linux::get_kernel_build_date().map(|date| date.format("%Y-%m-%d").to_string());
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_kernel_build_date() -> Option<String> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_cpu_topology() -> Option<CpuTopology> {
+    let (sockets, numa_nodes, physical_cores) = linux::get_cpu_topology()?;
+    return Some(CpuTopology { sockets, numa_nodes, physical_cores });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cpu_topology() -> Option<CpuTopology> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_newer_installed_kernel() -> Option<String> {
+    return linux::get_newer_installed_kernel();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_newer_installed_kernel() -> Option<String> {
+    return None;
+}
+
+/// Some BSDs and minimal containers report an empty long version, so fall
+/// back to the short one and then to `uname`.
+fn kernel() -> Option<String> {
+    let non_empty = |value: String| {
+        let value = value.trim().to_string();
+        return if value.is_empty() { None } else { Some(value) };
+    };
+    return non_empty(System::kernel_long_version())
+        .or_else(|| System::kernel_version().and_then(non_empty))
+        .or_else(|| {
+            let output = std::process::Command::new("uname").arg("-sr").output().ok()?;
+            return non_empty(String::from_utf8_lossy(&output.stdout).into_owned());
+        });
+}
+
+/// Kernel build date and last package database change, as a hint of how
+/// stale the machine is. Only Linux exposes both in a parseable form.
+#[cfg(target_os = "linux")]
+fn get_update_info() -> Option<String> {
+    let mut parts = vec![];
+    if let Some(date) = linux::get_kernel_build_date() {
+        parts.push(format!("kernel built {}", date.format("%Y-%m-%d")));
     }
-    if output_info_vec.len() < LOGO_HEIGHT {
-        for i in output_info_vec.len()..LOGO_HEIGHT {
-            println!("{}", LOGO[i]);
-        }
+    if let Some(date) = linux::get_package_db_update_date() {
+        parts.push(format!("packages {}", date.format("%Y-%m-%d")));
+    }
+    if parts.is_empty() {
+        return None;
     }
-    println!();
+    return Some(parts.join(", "));
 }
 
-fn main() -> ExitCode {
-    if !sysinfo::IS_SUPPORTED_SYSTEM {
-        println!("System not supported. Aborting.");
-        return ExitCode::from(1);
-    }
+#[cfg(not(target_os = "linux"))]
+fn get_update_info() -> Option<String> {
+    return None;
+}
 
-    let mut sys = System::new_all();
-    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
-    sys.refresh_cpu_all();
+#[cfg(target_os = "linux")]
+fn get_pending_updates() -> Option<PendingUpdates> {
+    let (count, security) = linux::get_pending_updates()?;
+    return Some(PendingUpdates { count, security });
+}
 
-    let output_info = OutputInfo {
-        username: get_username(),
-        hostname: get_hostname(),
-        os: get_os_name(),
-        serial_number: get_serial_number(),
-        kernel: kernel(),
-        uptime: get_uptime(),
-        cpu: get_cpu_info(&sys),
-        gpu: get_gpu_info(),
-        memory_used_mb: get_used_memory(&sys) / 1024 / 1024,
-        memory_total_mb: get_total_memory(&sys) / 1024 / 1024,
+#[cfg(not(target_os = "linux"))]
+fn get_pending_updates() -> Option<PendingUpdates> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_failed_unit_count() -> Option<usize> {
+    return linux::get_failed_unit_count();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_failed_unit_count() -> Option<usize> {
+    return None;
+}
+
+/// The cpufreq governor, with the platform power profile when one is set.
+#[cfg(target_os = "linux")]
+fn get_cpu_governor() -> Option<String> {
+    let governor = linux::get_cpu_governor()?;
+    return match linux::get_power_profile() {
+        Some(profile) => Some(format!("{} (power profile: {})", governor, profile)),
+        None => Some(governor),
     };
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cpu_governor() -> Option<String> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_thread_counts() -> Option<(usize, usize)> {
+    return linux::get_thread_counts();
+}
 
-    print_all_info(&output_info);
+#[cfg(not(target_os = "linux"))]
+fn get_thread_counts() -> Option<(usize, usize)> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_firmware() -> Option<FirmwareInfo> {
+    let (uefi, secure_boot) = linux::get_firmware_mode();
+    return Some(FirmwareInfo { uefi, secure_boot });
+}
+
+#[cfg(windows)]
+fn get_firmware() -> Option<FirmwareInfo> {
+    let (uefi, secure_boot) = windows::get_firmware_mode()?;
+    return Some(FirmwareInfo { uefi, secure_boot });
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn get_firmware() -> Option<FirmwareInfo> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_kernel_cmdline() -> Option<String> {
+    return linux::get_kernel_cmdline();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_kernel_cmdline() -> Option<String> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_entropy_bits() -> Option<usize> {
+    return linux::get_entropy_avail();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_entropy_bits() -> Option<usize> {
+    return None;
+}
 
-    return ExitCode::from(0);
+#[cfg(target_os = "linux")]
+fn get_fd_counts() -> Option<(usize, usize)> {
+    return linux::get_fd_counts();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_fd_counts() -> Option<(usize, usize)> {
+    return None;
+}
+
+/// Looks up just our own process and its parent rather than every process,
+/// so it works with `--fast` too.
+fn get_parent_process() -> Option<ParentProcess> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    let parent = sys.process(pid)?.parent()?;
+    sys.refresh_processes(ProcessesToUpdate::Some(&[parent]), true);
+    let name = sys.process(parent)?.name().to_string_lossy().into_owned();
+    return Some(ParentProcess { pid: parent.as_u32(), name });
+}
+
+/// The session type from the variables SSH, RDP and desktop sessions set.
+fn session_kind(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    if ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"].into_iter().any(|name| var(name).is_some()) {
+        return Some(String::from("SSH (remote)"));
+    }
+    // Windows names the session `Console` or `RDP-Tcp#N`.
+    if let Some(session) = var("SESSIONNAME") {
+        return Some(String::from(if session.starts_with("RDP-") { "RDP (remote)" } else { "desktop" }));
+    }
+    return match var("XDG_SESSION_TYPE").as_deref() {
+        Some("tty") => Some(String::from("console")),
+        Some("x11") | Some("wayland") | Some("mir") => Some(format!("desktop ({})", var("XDG_SESSION_TYPE")?)),
+        _ if var("WAYLAND_DISPLAY").is_some() => Some(String::from("desktop (wayland)")),
+        _ if var("DISPLAY").is_some() => Some(String::from("desktop (x11)")),
+        _ => None,
+    };
+}
+
+#[cfg(unix)]
+fn is_privileged() -> Option<bool> {
+    // SAFETY: `geteuid` can't fail.
+    return Some(unsafe { libc::geteuid() } == 0);
+}
+
+/// The name uid 0 goes by, usually `root`.
+#[cfg(unix)]
+fn superuser_name() -> Option<String> {
+    // SAFETY: `passwd` is plain old data, all zeroes is a valid value.
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut result = std::ptr::null_mut();
+    // SAFETY: the strings `entry` points at live in `buffer`, which outlives
+    // them being read below.
+    let status = unsafe { libc::getpwuid_r(0, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+    if status != 0 || result.is_null() || entry.pw_name.is_null() {
+        return None;
+    }
+    // SAFETY: `pw_name` is a NUL-terminated string in `buffer`.
+    let name = unsafe { std::ffi::CStr::from_ptr(entry.pw_name) }.to_string_lossy().into_owned();
+    return if name.is_empty() { None } else { Some(name) };
+}
+
+#[cfg(not(unix))]
+fn superuser_name() -> Option<String> {
+    return None;
+}
+
+#[cfg(windows)]
+fn is_privileged() -> Option<bool> {
+    return windows::is_elevated();
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_privileged() -> Option<bool> {
+    return None;
+}
+
+fn get_session() -> Option<SessionInfo> {
+    let privileged = is_privileged();
+    let account = if privileged == Some(true) { superuser_name() } else { None };
+    let session = SessionInfo { kind: session_kind(|name| std::env::var(name).ok()), privileged, account };
+    return if session.kind.is_none() && session.privileged.is_none() { None } else { Some(session) };
+}
+
+/// Samples every process twice, a CPU update interval apart, since a
+/// single refresh can't tell CPU usage; the first only reads CPU times.
+/// Our own process is skipped.
+fn get_top_processes() -> Option<TopProcesses> {
+    let mut sys = System::new();
+    let cpu_only = sysinfo::ProcessRefreshKind::nothing().with_cpu();
+    sys.refresh_processes_specifics(ProcessesToUpdate::All, true, cpu_only);
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes_specifics(ProcessesToUpdate::All, true, cpu_only.with_memory());
+    let own_pid = sysinfo::get_current_pid().ok();
+    let mut busiest: Option<&sysinfo::Process> = None;
+    let mut largest: Option<&sysinfo::Process> = None;
+    // Linux lists threads as processes too; they'd split a process's usage.
+    for process in sys.processes().values().filter(|process| Some(process.pid()) != own_pid && process.thread_kind().is_none()) {
+        if busiest.is_none_or(|busiest| process.cpu_usage() > busiest.cpu_usage()) {
+            busiest = Some(process);
+        }
+        if largest.is_none_or(|largest| process.memory() > largest.memory()) {
+            largest = Some(process);
+        }
+    }
+    let (busiest, largest) = (busiest?, largest?);
+    return Some(TopProcesses {
+        cpu_name: busiest.name().to_string_lossy().into_owned(),
+        cpu_percent: busiest.cpu_usage(),
+        memory_name: largest.name().to_string_lossy().into_owned(),
+        memory_bytes: largest.memory(),
+    });
+}
+
+fn get_uptime() -> usize {
+    return System::uptime() as usize;
+}
+
+/// Intel brand strings end in the rated clock: `... CPU @ 3.70GHz`.
+fn parse_brand_base_mhz(brand: &str) -> Option<f64> {
+    let (_, rated) = brand.rsplit_once('@')?;
+    let rated = rated.trim();
+    if let Some(ghz) = rated.strip_suffix("GHz") {
+        return ghz.trim().parse::<f64>().ok().map(|ghz| ghz * 1000.0);
+    }
+    return rated.strip_suffix("MHz")?.trim().parse().ok();
+}
+
+#[cfg(target_os = "linux")]
+fn get_cpu_frequency_limits(cpu_index: usize) -> (Option<f64>, Option<f64>) {
+    return linux::get_cpu_frequency_limits(cpu_index);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cpu_frequency_limits(_cpu_index: usize) -> (Option<f64>, Option<f64>) {
+    return (None, None);
+}
+
+fn max_option(current: Option<f64>, candidate: Option<f64>) -> Option<f64> {
+    return match (current, candidate) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+}
+
+const DEFAULT_CPU_SAMPLES: usize = 3;
+const DEFAULT_TEMP_HISTORY: usize = 10;
+const DEFAULT_FREQUENCY_DECIMALS: usize = 2;
+/// `--serve` interval without `--watch`.
+const DEFAULT_SERVE_INTERVAL_SECS: usize = 2;
+/// How long `--serve` waits on a client that stopped reading before
+/// dropping it.
+const SERVE_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+const DEFAULT_COLLECTOR_TIMEOUT_MS: u64 = 1000;
+/// Longest `--cpu-samples` may stretch the CPU usage measurement.
+const MAX_CPU_SAMPLING: std::time::Duration = std::time::Duration::from_millis(900);
+
+/// Per-CPU usage over one sampling interval.
+trait CpuSampler {
+    fn sample(&mut self) -> Vec<f32>;
+}
+
+struct SysinfoSampler<'a>(&'a mut System);
+
+impl CpuSampler for SysinfoSampler<'_> {
+    fn sample(&mut self) -> Vec<f32> {
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        self.0.refresh_cpu_all();
+        return self.0.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+    }
+}
+
+/// `/proc/stat` deltas over the same interval sysinfo would wait.
+#[cfg(target_os = "linux")]
+struct ProcStatSampler(Vec<(u64, u64)>);
+
+#[cfg(target_os = "linux")]
+impl CpuSampler for ProcStatSampler {
+    fn sample(&mut self) -> Vec<f32> {
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        let times = linux::get_cpu_times().unwrap_or_default();
+        let usage = linux::cpu_usage_between(&self.0, &times);
+        self.0 = times;
+        return usage;
+    }
+}
+
+/// User, system and idle shares of two `/proc/stat` readings taken
+/// `samples` CPU update intervals apart, the window the usage is averaged
+/// over.
+#[cfg(target_os = "linux")]
+fn get_cpu_breakdown(samples: usize) -> Option<CpuBreakdown> {
+    let before = linux::get_cpu_state_times()?;
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL * samples as u32);
+    let (user_percent, system_percent, idle_percent) = linux::cpu_states_between(before, linux::get_cpu_state_times()?)?;
+    return Some(CpuBreakdown { user_percent, system_percent, idle_percent });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cpu_breakdown(_samples: usize) -> Option<CpuBreakdown> {
+    return None;
+}
+
+/// Per-CPU usage from the configured source, averaged over `samples`
+/// intervals.
+fn sample_cpu_usage(sys: &mut System, source: CpuUsageSource, samples: usize) -> Vec<f32> {
+    #[cfg(target_os = "linux")]
+    if source == CpuUsageSource::ProcStat
+        && let Some(times) = linux::get_cpu_times()
+    {
+        return average_cpu_usage(&mut ProcStatSampler(times), samples);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = source;
+    return average_cpu_usage(&mut SysinfoSampler(sys), samples);
+}
+
+/// Per-CPU mean of `samples` consecutive intervals. A single interval right
+/// after startup is often skewed by our own spawn, so a few are averaged.
+fn average_cpu_usage(sampler: &mut impl CpuSampler, samples: usize) -> Vec<f32> {
+    let samples = samples.max(1);
+    let mut totals: Vec<f32> = vec![];
+    for _ in 0..samples {
+        let usage = sampler.sample();
+        totals.resize(totals.len().max(usage.len()), 0.0);
+        for (total, usage) in totals.iter_mut().zip(usage) {
+            *total += usage;
+        }
+    }
+    return totals.into_iter().map(|total| total / samples as f32).collect();
+}
+
+/// `requested` capped so the sampling stays under `MAX_CPU_SAMPLING`.
+fn cpu_sample_count(requested: usize) -> usize {
+    let max = (MAX_CPU_SAMPLING.as_millis() / sysinfo::MINIMUM_CPU_UPDATE_INTERVAL.as_millis().max(1)) as usize;
+    return requested.clamp(1, max.max(1));
+}
+
+/// What `get_cpu_info` needs from one logical CPU. Kept apart from
+/// sysinfo's `Cpu`, which can't be constructed outside the crate.
+struct CpuReading<'a> {
+    brand: &'a str,
+    frequency_mhz: u64,
+}
+
+/// `usage` is the per-CPU usage, `None` when it wasn't sampled.
+fn get_cpu_info<'a>(sys: &'a System, usage: Option<&[f32]>) -> BTreeMap<&'a str, CpuInfo> {
+    let readings: Vec<CpuReading> = sys
+        .cpus()
+        .iter()
+        .map(|cpu| CpuReading { brand: cpu.brand(), frequency_mhz: cpu.frequency() })
+        .collect();
+    return aggregate_cpu_info(&readings, usage, get_cpu_frequency_limits);
+}
+
+/// Groups logical CPUs by brand, averaging their usage and keeping the
+/// highest clocks. `frequency_limits` maps a CPU index to its rated
+/// `(base, boost)` MHz.
+fn aggregate_cpu_info<'a>(
+    readings: &[CpuReading<'a>],
+    usage: Option<&[f32]>,
+    frequency_limits: impl Fn(usize) -> (Option<f64>, Option<f64>),
+) -> BTreeMap<&'a str, CpuInfo> {
+    let mut cpu_info_map = BTreeMap::<&'a str, CpuInfo>::new();
+    for (cpu_index, cpu) in readings.iter().enumerate() {
+        let entry = cpu_info_map.entry(cpu.brand).or_insert(CpuInfo {
+            num_cores: 0,
+            avg_usage: usage.map(|_| 0.0),
+            max_frequency_mhz: 0.0,
+            base_frequency_mhz: None,
+            boost_frequency_mhz: None,
+            codename: None,
+        });
+        entry.num_cores += 1;
+        let cpu_usage = usage.and_then(|usage| usage.get(cpu_index)).copied().unwrap_or(0.0);
+        entry.avg_usage = entry.avg_usage.map(|usage| usage + cpu_usage as f64);
+        if cpu.frequency_mhz as f64 > entry.max_frequency_mhz {
+            entry.max_frequency_mhz = cpu.frequency_mhz as f64;
+        }
+        let (base, boost) = frequency_limits(cpu_index);
+        entry.base_frequency_mhz = max_option(entry.base_frequency_mhz, base);
+        entry.boost_frequency_mhz = max_option(entry.boost_frequency_mhz, boost);
+    }
+    for (brand, val) in cpu_info_map.iter_mut() {
+        if val.base_frequency_mhz.is_none() {
+            val.base_frequency_mhz = parse_brand_base_mhz(brand);
+        }
+        val.codename = codename::cpu_codename(brand);
+    }
+    for val in cpu_info_map.values_mut() {
+        val.avg_usage = val.avg_usage.map(|usage| usage / val.num_cores as f64);
+    }
+    return cpu_info_map;
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_hybrid_cores() -> Option<HybridCores> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(target_os = "macos")]
+    use macos as platform;
+
+    let (performance, efficiency, threads) = platform::get_hybrid_core_counts()?;
+    return Some(HybridCores { performance, efficiency, threads });
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn get_hybrid_cores() -> Option<HybridCores> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_core_counts() -> Option<CoreCounts> {
+    let (configured, online) = linux::get_cpu_online_counts()?;
+    return Some(CoreCounts { configured, online });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_core_counts() -> Option<CoreCounts> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_cgroup_cpu_limit() -> Option<f64> {
+    return linux::get_cgroup_cpu_limit();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cgroup_cpu_limit() -> Option<f64> {
+    return None;
+}
+
+/// `VT-x`/`AMD-V` from the CPUID feature bits.
+#[cfg(target_arch = "x86_64")]
+fn get_cpu_virtualization() -> Option<&'static str> {
+    use std::arch::x86_64::__cpuid;
+    // Leaf 1 ECX bit 5 is VMX; extended leaf 0x8000_0001 ECX bit 2 is SVM.
+    if __cpuid(1).ecx & (1 << 5) != 0 {
+        return Some("VT-x");
+    }
+    if __cpuid(0x8000_0000).eax >= 0x8000_0001 && __cpuid(0x8000_0001).ecx & (1 << 2) != 0 {
+        return Some("AMD-V");
+    }
+    return None;
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn get_cpu_virtualization() -> Option<&'static str> {
+    return None;
+}
+
+/// Vendor signatures at CPUID leaf `0x4000_0000`, as hypervisors fill it.
+const CPUID_HYPERVISORS: [(&[u8; 12], &str); 9] = [
+    (b"KVMKVMKVM\0\0\0", "KVM"),
+    (b"TCGTCGTCGTCG", "QEMU"),
+    (b"Microsoft Hv", "Hyper-V"),
+    (b"VMwareVMware", "VMware"),
+    (b"XenVMMXenVMM", "Xen"),
+    (b"VBoxVBoxVBox", "VirtualBox"),
+    (b" lrpepyh  vr", "Parallels"),
+    (b"bhyve bhyve ", "bhyve"),
+    (b"ACRNACRNACRN", "ACRN"),
+];
+
+fn cpuid_hypervisor_name(signature: &[u8; 12]) -> &'static str {
+    return CPUID_HYPERVISORS
+        .iter()
+        .find(|(known, _)| *known == signature)
+        .map_or("VM", |(_, name)| name);
+}
+
+/// The hypervisor we run under, from the CPUID hypervisor bit (leaf 1 ECX
+/// bit 31). A Windows host with Hyper-V or VBS enabled runs in Hyper-V's
+/// root partition and sets it too; that partition may create others
+/// (leaf `0x4000_0003` EBX bit 0), so it's taken as the physical machine.
+#[cfg(target_arch = "x86_64")]
+fn get_cpuid_hypervisor() -> Option<&'static str> {
+    use std::arch::x86_64::__cpuid;
+    if __cpuid(1).ecx & (1 << 31) == 0 {
+        return None;
+    }
+    let leaf = __cpuid(0x4000_0000);
+    let mut signature = [0; 12];
+    for (chunk, register) in signature.chunks_mut(4).zip([leaf.ebx, leaf.ecx, leaf.edx]) {
+        chunk.copy_from_slice(&register.to_le_bytes());
+    }
+    let name = cpuid_hypervisor_name(&signature);
+    if name == "Hyper-V" && leaf.eax >= 0x4000_0003 && __cpuid(0x4000_0003).ebx & 1 != 0 {
+        return None;
+    }
+    return Some(name);
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn get_cpuid_hypervisor() -> Option<&'static str> {
+    return None;
+}
+
+/// CPUID where there is one, else the DMI names that mark a VM's chassis.
+#[cfg(any(target_os = "linux", windows))]
+fn get_hypervisor() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(windows)]
+    use windows as platform;
+
+    if let Some(name) = get_cpuid_hypervisor() {
+        return Some(name.to_string());
+    }
+    let identity = platform::get_dmi_system_identity();
+    return HYPERVISOR_SIGNATURES.iter().find(|signature| identity.contains(*signature)).map(|signature| signature.to_string());
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn get_hypervisor() -> Option<String> {
+    return get_cpuid_hypervisor().map(String::from);
+}
+
+/// The CPU advertises hardware virtualization. Only Linux says whether it's
+/// usable: `/dev/kvm` exists once KVM loaded, which fails when firmware has
+/// it disabled.
+fn get_virtualization() -> Option<String> {
+    let extension = get_cpu_virtualization()?;
+    if cfg!(target_os = "linux") {
+        let enabled = std::path::Path::new("/dev/kvm").exists();
+        return Some(format!("{} ({})", extension, if enabled { "enabled" } else { "no /dev/kvm" }));
+    }
+    return Some(extension.to_string());
+}
+
+#[cfg(target_os = "linux")]
+fn get_cpu_affinity() -> Option<usize> {
+    return linux::get_cpu_affinity_count();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cpu_affinity() -> Option<usize> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_proc_memory_usage() -> Option<(usize, usize)> {
+    let (total, available) = linux::get_meminfo()?;
+    return Some((total.saturating_sub(available) as usize, total as usize));
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_proc_memory_usage() -> Option<(usize, usize)> {
+    return None;
+}
+
+/// Memory as `(used, total)` bytes, reporting the cgroup's limit instead of
+/// the host's RAM when we're confined to less. The flag says which it is.
+/// In a cgroup, `MemoryBasis::Available` leaves out the page cache the
+/// kernel can reclaim, counting only the resident set.
+fn get_memory_usage(sys: &System, basis: MemoryBasis, source: MemorySource) -> (usize, usize, bool) {
+    let proc_usage = if source == MemorySource::ProcMeminfo { get_proc_memory_usage() } else { None };
+    let (used, total) = proc_usage.unwrap_or_else(|| (get_used_memory(sys, basis), get_total_memory(sys)));
+    return match sys.cgroup_limits() {
+        Some(limits) if (limits.total_memory as usize) < total => {
+            let used = match basis {
+                MemoryBasis::Used => limits.total_memory.saturating_sub(limits.free_memory),
+                MemoryBasis::Available => limits.rss.min(limits.total_memory),
+            };
+            (used as usize, limits.total_memory as usize, true)
+        }
+        _ => (used, total, false),
+    };
+}
+
+/// `16 cores`, or, first match wins: the cgroup's CPU quota, `16 cores
+/// (12 available)` under an affinity mask, `8 vCPUs (KVM)` in a VM or
+/// `16 cores (14 online)` when some are offlined. These are machine-wide,
+/// so they're only attributed when there's a single brand.
+fn format_core_count(output_info: &OutputInfo, cpu_info: &CpuInfo) -> String {
+    if output_info.cpu.len() != 1 {
+        return format!("{} cores", cpu_info.num_cores);
+    }
+    if let Some(limit) = output_info.cpu_limit.filter(|limit| *limit < cpu_info.num_cores as f64) {
+        let limit = format!("{:.1}", limit);
+        return format!("{} cores (cgroup limit)", limit.trim_end_matches(".0"));
+    }
+    if let Some(available) = output_info.cpu_affinity.filter(|available| *available < cpu_info.num_cores) {
+        return format!("{} cores ({} available)", cpu_info.num_cores, available);
+    }
+    if let Some(hypervisor) = &output_info.hypervisor {
+        let plural = if cpu_info.num_cores == 1 { "" } else { "s" };
+        return format!("{} vCPU{} ({})", cpu_info.num_cores, plural, hypervisor);
+    }
+    return match &output_info.core_counts {
+        Some(counts) if counts.online < counts.configured => {
+            format!("{} cores ({} online)", counts.configured, counts.online)
+        }
+        _ => format_cores(cpu_info.num_cores, output_info.hybrid_cores.as_ref()),
+    };
+}
+
+/// `8P + 4E cores` when the core types cover all `num_cores` logical CPUs,
+/// else the flat count.
+fn format_cores(num_cores: usize, hybrid: Option<&HybridCores>) -> String {
+    return match hybrid {
+        Some(hybrid) if hybrid.threads == num_cores => {
+            format!("{}P + {}E cores", hybrid.performance, hybrid.efficiency)
+        }
+        _ => format!("{} cores", num_cores),
+    };
+}
+
+/// Sensor labels that usually mean "the CPU", best first. `Package id`
+/// (Intel coretemp) and `Tctl`/`Tdie` (AMD k10temp) cover the whole die;
+/// the rest are fallbacks seen on laptops and ARM boards.
+const CPU_SENSOR_PREFERENCE: [&str; 7] = ["package id", "tctl", "tdie", "cpu", "coretemp", "k10temp", "soc"];
+
+/// Picks the sensor to report as the CPU temperature. A configured label
+/// (matched case-insensitively as a substring) is used exclusively, so a
+/// typo shows nothing rather than some other sensor's reading.
+fn select_cpu_sensor(labels: &[&str], preferred: Option<&str>) -> Option<usize> {
+    let lowered: Vec<String> = labels.iter().map(|label| label.to_lowercase()).collect();
+    if let Some(preferred) = preferred {
+        let preferred = preferred.to_lowercase();
+        return lowered.iter().position(|label| label.contains(&preferred));
+    }
+    return CPU_SENSOR_PREFERENCE
+        .iter()
+        .find_map(|wanted| lowered.iter().position(|label| label.contains(wanted)));
+}
+
+fn get_cpu_temperature(preferred: Option<&str>) -> Option<CpuTemperature> {
+    let components = Components::new_with_refreshed_list();
+    let sensors: Vec<(&str, f32)> = components
+        .list()
+        .iter()
+        .filter_map(|component| Some((component.label(), component.temperature()?)))
+        .collect();
+    let labels: Vec<&str> = sensors.iter().map(|(label, _)| *label).collect();
+    let (sensor, celsius) = sensors[select_cpu_sensor(&labels, preferred)?];
+    return Some(CpuTemperature {
+        sensor: sensor.to_string(),
+        celsius,
+    });
+}
+
+/// Lower values sort first: discrete cards are what users usually care about.
+fn gpu_type_priority(device_type: wgpu::DeviceType) -> u8 {
+    return match device_type {
+        wgpu::DeviceType::DiscreteGpu => 0,
+        wgpu::DeviceType::IntegratedGpu => 1,
+        wgpu::DeviceType::VirtualGpu => 2,
+        wgpu::DeviceType::Cpu => 3,
+        wgpu::DeviceType::Other => 4,
+    };
+}
+
+fn gpu_type_label(device_type: wgpu::DeviceType) -> &'static str {
+    return match device_type {
+        wgpu::DeviceType::IntegratedGpu => "Integrated GPU",
+        wgpu::DeviceType::DiscreteGpu => "Discrete GPU",
+        wgpu::DeviceType::VirtualGpu => "Virtual GPU",
+        wgpu::DeviceType::Cpu => "Software Rasterizer",
+        wgpu::DeviceType::Other => "unknown gpu type",
+    };
+}
+
+/// Vendor names adapters start with, longest first.
+const GPU_VENDOR_PREFIXES: [&str; 6] = ["Advanced Micro Devices, Inc.", "NVIDIA Corporation", "AMD/ATI", "NVIDIA", "Intel", "AMD"];
+
+/// `name` with the noise `style` drops: `NVIDIA GeForce(R) RTX 4070
+/// Laptop GPU/PCIe/SSE2` is `NVIDIA GeForce RTX 4070 Laptop GPU` when light
+/// and `GeForce RTX 4070 Laptop` when short.
+fn normalize_gpu_name(name: &str, style: GpuNameStyle) -> String {
+    if style == GpuNameStyle::Raw {
+        return name.to_string();
+    }
+    let mut name = name.trim_end_matches("/SSE2").trim_end_matches("/PCIe").to_string();
+    for mark in ["(R)", "(r)", "(TM)", "(tm)", "®", "™"] {
+        name = name.replace(mark, " ");
+    }
+    let mut name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if style == GpuNameStyle::Short {
+        let vendor = GPU_VENDOR_PREFIXES.iter().find(|vendor| name.starts_with(&format!("{} ", vendor)));
+        if let Some(vendor) = vendor {
+            name = name[vendor.len() + 1..].to_string();
+        }
+        if let Some(rest) = name.strip_suffix(" GPU") {
+            name = rest.to_string();
+        }
+    }
+    return name;
+}
+
+fn compare_gpus(x: &GpuInfo, y: &GpuInfo, sort: GpuSort) -> Ordering {
+    let by_name = || x.gpu_name.cmp(&y.gpu_name);
+    return match sort {
+        GpuSort::Type => gpu_type_priority(x.device_type)
+            .cmp(&gpu_type_priority(y.device_type))
+            .then_with(by_name),
+        GpuSort::Index => x.device_index.cmp(&y.device_index),
+        GpuSort::Name => by_name().then_with(|| x.device_index.cmp(&y.device_index)),
+        // Biggest first; cards with unknown VRAM go last.
+        GpuSort::Vram => y.vram_mb.cmp(&x.vram_mb).then_with(by_name),
+    };
+}
+
+#[cfg(target_os = "linux")]
+fn get_gpu_vram_mb(info: &wgpu::AdapterInfo) -> Option<usize> {
+    return linux::get_gpu_vram_bytes(info.vendor, info.device).map(|bytes| bytes_to_mb(bytes as usize));
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_gpu_vram_mb(_info: &wgpu::AdapterInfo) -> Option<usize> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_gpu_vram_used_mb(info: &wgpu::AdapterInfo) -> Option<usize> {
+    return linux::get_gpu_vram_used_bytes(info.vendor, info.device).map(|bytes| bytes_to_mb(bytes as usize));
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_gpu_vram_used_mb(_info: &wgpu::AdapterInfo) -> Option<usize> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_gpu_pci_bus_ids(info: &wgpu::AdapterInfo) -> Vec<String> {
+    return linux::get_gpu_pci_bus_ids(info.vendor, info.device);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_gpu_pci_bus_ids(_info: &wgpu::AdapterInfo) -> Vec<String> {
+    return vec![];
+}
+
+/// What identifies a card across backends, which each list it again: its
+/// PCI address, else its PCI ids and position among identical cards, else
+/// its name.
+fn gpu_identity(info: &wgpu::AdapterInfo, pci_bus_id: Option<&str>, nth: usize) -> String {
+    if let Some(pci_bus_id) = pci_bus_id {
+        return pci_bus_id.to_string();
+    }
+    if info.device != 0 {
+        return format!("{:04x}:{:04x}#{}", info.vendor, info.device, nth);
+    }
+    return info.name.clone();
+}
+
+/// Renumbers `device_index` by PCI address, which unlike the enumeration
+/// order survives reboots. GPUs without one follow, by name.
+fn assign_stable_indices(gpu_infos: &mut [GpuInfo]) {
+    gpu_infos.sort_by(|x, y| {
+        return (x.pci_bus_id.is_none(), &x.pci_bus_id, &x.gpu_name, x.device_index)
+            .cmp(&(y.pci_bus_id.is_none(), &y.pci_bus_id, &y.gpu_name, y.device_index));
+    });
+    for (index, gpu_info) in gpu_infos.iter_mut().enumerate() {
+        gpu_info.device_index = index;
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_gpu_power_watts(info: &wgpu::AdapterInfo) -> Option<f64> {
+    return linux::get_gpu_power_watts(info.vendor, info.device);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_gpu_power_watts(_info: &wgpu::AdapterInfo) -> Option<f64> {
+    return None;
+}
+
+/// `OpenGL 4.6` / `OpenGL ES 3.2` from a `GL_VERSION` string such as
+/// `4.6 (Core Profile) Mesa 23.0.4`.
+fn parse_gl_version(version: &str) -> Option<String> {
+    let (api, rest) = match version.strip_prefix("OpenGL ES ") {
+        Some(rest) => ("OpenGL ES", rest),
+        None => ("OpenGL", version),
+    };
+    let number = rest.split_whitespace().next()?;
+    let mut parts = number.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    return Some(format!("{} {}.{}", api, major, minor));
+}
+
+/// Vulkan's version comes from the device properties behind wgpu's HAL; GL's
+/// from the `GL_VERSION` string wgpu's GL backend reads from its own context.
+/// Other backends don't report one.
+fn get_gpu_api_version(adapter: &wgpu::Adapter, info: &wgpu::AdapterInfo) -> Option<String> {
+    return match info.backend {
+        wgpu::Backend::Vulkan => get_vulkan_api_version(adapter),
+        wgpu::Backend::Gl => parse_gl_version(&info.driver_info),
+        _ => None,
+    };
+}
+
+#[cfg(any(target_os = "linux", windows))]
+fn get_vulkan_api_version(adapter: &wgpu::Adapter) -> Option<String> {
+    // SAFETY: the HAL adapter is only read from, while `adapter` is alive.
+    let hal_adapter = unsafe { adapter.as_hal::<wgpu::hal::api::Vulkan>() }?;
+    let version = hal_adapter.physical_device_capabilities().properties().api_version;
+    return Some(format!("Vulkan {}.{}", (version >> 22) & 0x7f, (version >> 12) & 0x3ff));
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn get_vulkan_api_version(_adapter: &wgpu::Adapter) -> Option<String> {
+    return None;
+}
+
+/// Temperature of the primary GPU without enumerating adapters: the boot
+/// card's sensor on Linux, else the first GPU `nvidia-smi` lists.
+fn get_gpu_temperature() -> Option<f64> {
+    #[cfg(target_os = "linux")]
+    if let Some(celsius) = linux::get_gpu_temperature() {
+        return Some(celsius);
+    }
+    return nvidia::query(&["temperature.gpu"]).first().and_then(|gpu| nvidia::parse_value(gpu.values.first()));
+}
+
+/// `explain` (`-vv`) reports every adapter wgpu enumerated on stderr, and
+/// why it was kept or dropped. Also returns how many adapters wgpu
+/// enumerated before any were dropped. A backend that panics while loading
+/// fails the collector thread, which is reported like any other failure.
+fn get_gpu_info(sort: GpuSort, live: bool, api_versions: bool, explain: bool) -> (Vec<GpuInfo>, usize) {
+    let instance_descriptor = InstanceDescriptor {
+        backends: Backends::all(),
+        ..Default::default()
+    };
+    let adapters = Instance::new(&instance_descriptor).enumerate_adapters(Backends::all());
+    if adapters.is_empty() && explain {
+        explain_once(String::from("gpu: no adapters from any backend, no graphics backend could be loaded"));
+    }
+    let mut nvidia_gpus = if live { nvidia::query(&["power.draw", "memory.used", "memory.total"]) } else { vec![] };
+    // Bus ids already handed to an adapter of the same backend and model.
+    let mut claimed_bus_ids: HashMap<(wgpu::Backend, u32, u32), usize> = HashMap::new();
+    let mut gpu_infos = vec![];
+    let mut identities = HashSet::new();
+    for (idx, adapter) in adapters.iter().enumerate() {
+        let info = adapter.get_info();
+        let claimed = claimed_bus_ids.entry((info.backend, info.vendor, info.device)).or_insert(0);
+        let nth = *claimed;
+        *claimed += 1;
+        let pci_bus_id = get_gpu_pci_bus_ids(&info).get(nth).cloned();
+        let dropped = if info.device_type == wgpu::DeviceType::Other || info.device_type == wgpu::DeviceType::Cpu {
+            Some("dropped, not a hardware GPU")
+        } else if !identities.insert(gpu_identity(&info, pci_bus_id.as_deref(), nth)) {
+            Some("dropped, already listed by another backend")
+        } else {
+            None
+        };
+        if explain {
+            explain_once(format!("gpu: adapter {} `{}` ({:?}, {:?}): {}", idx, info.name, info.backend, info.device_type, dropped.unwrap_or("kept")));
+        }
+        if dropped.is_some() {
+            continue;
+        }
+        let mut vram_mb = get_gpu_vram_mb(&info);
+        let (mut power_watts, mut vram_used_mb) = (None, None);
+        if live {
+            (power_watts, vram_used_mb) = match info.vendor {
+                nvidia::VENDOR_ID => match nvidia::claim(&mut nvidia_gpus, info.device) {
+                    Some(gpu) => {
+                        // `nvidia-smi` reports memory in MiB.
+                        let mb = |index: usize| nvidia::parse_value(gpu.values.get(index)).map(|mb| mb as usize);
+                        vram_mb = vram_mb.or_else(|| mb(2));
+                        (nvidia::parse_value(gpu.values.first()), mb(1))
+                    }
+                    None => (None, None),
+                },
+                _ => (get_gpu_power_watts(&info), get_gpu_vram_used_mb(&info)),
+            };
+        }
+        gpu_infos.push(GpuInfo {
+            device_index: idx,
+            pci_bus_id,
+            vram_mb,
+            vram_used_mb,
+            power_watts,
+            api_version: if api_versions { get_gpu_api_version(adapter, &info) } else { None },
+            gpu_name: info.name,
+            device_type: info.device_type,
+        });
+    }
+    assign_stable_indices(&mut gpu_infos);
+    gpu_infos.sort_by(|x, y| compare_gpus(x, y, sort));
+    return (gpu_infos, adapters.len());
+}
+
+/// Collects the monitors from inside the first event loop callback, which
+/// is the only place winit hands out an `ActiveEventLoop`.
+#[derive(Default)]
+struct MonitorProbe {
+    displays: Option<Vec<DisplayInfo>>,
+}
+
+impl ApplicationHandler for MonitorProbe {
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, _cause: StartCause) {
+        if self.displays.is_some() {
+            return;
+        }
+        let primary = event_loop.primary_monitor();
+        self.displays = Some(
+            event_loop
+                .available_monitors()
+                .map(|monitor| {
+                    let color = monitor.name().and_then(|name| get_display_color(&name));
+                    return DisplayInfo {
+                        width: monitor.size().width,
+                        height: monitor.size().height,
+                        refresh_hz: monitor
+                            .refresh_rate_millihertz()
+                            .filter(|millihertz| *millihertz > 0)
+                            .map(|millihertz| millihertz as f64 / 1000.0),
+                        bit_depth: color.and_then(|(bit_depth, _)| bit_depth),
+                        hdr: color.map(|(_, hdr)| hdr),
+                        primary: primary.as_ref() == Some(&monitor),
+                    };
+                })
+                .collect(),
+        );
+        event_loop.exit();
+    }
+
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn window_event(&mut self, _event_loop: &ActiveEventLoop, _window_id: WindowId, _event: WindowEvent) {}
+}
+
+/// Bits per color and HDR support of the monitor on a connector, as winit
+/// names it.
+#[cfg(target_os = "linux")]
+fn get_display_color(connector: &str) -> Option<(Option<u8>, bool)> {
+    return linux::get_display_color(connector);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_display_color(_connector: &str) -> Option<(Option<u8>, bool)> {
+    return None;
+}
+
+/// Connected monitors. Empty without a display server (SSH, containers).
+/// Wayland doesn't tell clients which output is primary.
+fn get_resolution() -> Vec<DisplayInfo> {
+    // winit allows a single event loop per process, so a repeated lookup
+    // (`--repeat`) reuses the first answer.
+    static DISPLAYS: std::sync::OnceLock<Vec<DisplayInfo>> = std::sync::OnceLock::new();
+    return DISPLAYS.get_or_init(probe_displays).clone();
+}
+
+fn probe_displays() -> Vec<DisplayInfo> {
+    if cfg!(target_os = "linux") && std::env::var_os("WAYLAND_DISPLAY").is_none() && std::env::var_os("DISPLAY").is_none() {
+        return vec![];
+    }
+    let Ok(mut event_loop) = EventLoop::new() else {
+        return vec![];
+    };
+    let mut probe = MonitorProbe::default();
+    event_loop.pump_app_events(Some(std::time::Duration::ZERO), &mut probe);
+    return probe.displays.unwrap_or_default();
+}
+
+#[cfg(target_os = "linux")]
+fn get_audio_server(sys: &System) -> Option<String> {
+    let process_names: Vec<String> = sys
+        .processes()
+        .values()
+        .map(|process| process.name().to_string_lossy().into_owned())
+        .collect();
+    return linux::get_audio_server(&process_names);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_audio_server(_sys: &System) -> Option<String> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_logged_in_users() -> Option<Vec<String>> {
+    return linux::get_logged_in_users();
+}
+
+/// The user in the first column of each `who` line.
+#[cfg(target_os = "macos")]
+fn parse_who(output: &str) -> Vec<String> {
+    return output.lines().filter_map(|line| line.split_whitespace().next()).map(String::from).collect();
+}
+
+/// macOS keeps utmpx in a different layout, so `who` reads it.
+#[cfg(target_os = "macos")]
+fn get_logged_in_users() -> Option<Vec<String>> {
+    let output = std::process::Command::new("who").env("LC_ALL", "C").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return Some(parse_who(&String::from_utf8_lossy(&output.stdout)));
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn get_logged_in_users() -> Option<Vec<String>> {
+    return None;
+}
+
+/// The queue name from `lpstat -d`'s `system default destination: NAME`.
+/// CUPS prints `no system default destination` when there's none.
+#[cfg(any(unix, test))]
+fn parse_default_printer(output: &str) -> Option<String> {
+    let name = output.lines().find_map(|line| line.strip_prefix("system default destination:"))?.trim();
+    return if name.is_empty() { None } else { Some(name.to_string()) };
+}
+
+/// The default CUPS printer. Runs on a collector thread, so a wedged
+/// `lpstat` only costs the collector timeout.
+#[cfg(unix)]
+fn get_default_printer() -> Option<String> {
+    let output = std::process::Command::new("lpstat").arg("-d").env("LC_ALL", "C").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return parse_default_printer(&String::from_utf8_lossy(&output.stdout));
+}
+
+#[cfg(not(unix))]
+fn get_default_printer() -> Option<String> {
+    return None;
+}
+
+/// Local address the OS would use to reach `remote`. Connecting a UDP
+/// socket only picks a route; nothing is sent.
+fn route_source_addr(local: &str, remote: &str) -> Option<IpAddr> {
+    let socket = UdpSocket::bind(local).ok()?;
+    socket.connect(remote).ok()?;
+    return Some(socket.local_addr().ok()?.ip());
+}
+
+fn get_primary_ipv4() -> Option<Ipv4Addr> {
+    return match route_source_addr("0.0.0.0:0", "192.0.2.1:9")? {
+        IpAddr::V4(addr) if !addr.is_unspecified() && !addr.is_loopback() => Some(addr),
+        _ => None,
+    };
+}
+
+#[cfg(target_os = "linux")]
+fn get_default_gateway() -> Option<Ipv4Addr> {
+    return linux::get_default_gateway();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_default_gateway() -> Option<Ipv4Addr> {
+    return None;
+}
+
+#[cfg(any(target_os = "linux", windows))]
+fn get_wifi() -> Option<WifiInfo> {
+    #[cfg(target_os = "linux")]
+    use linux as platform;
+    #[cfg(windows)]
+    use windows as platform;
+
+    let (ssid, signal_dbm) = platform::get_wifi()?;
+    return Some(WifiInfo { ssid, signal_dbm });
+}
+
+/// macOS only gives away the SSID without CoreWLAN.
+#[cfg(target_os = "macos")]
+fn get_wifi() -> Option<WifiInfo> {
+    return Some(WifiInfo { ssid: macos::get_wifi_ssid()?, signal_dbm: None });
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn get_wifi() -> Option<WifiInfo> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_encryption() -> Option<String> {
+    return linux::get_root_encryption();
+}
+
+#[cfg(target_os = "macos")]
+fn get_encryption() -> Option<String> {
+    return macos::get_filevault_status();
+}
+
+#[cfg(windows)]
+fn get_encryption() -> Option<String> {
+    return windows::get_bitlocker_status();
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn get_encryption() -> Option<String> {
+    return None;
+}
+
+#[cfg(target_os = "linux")]
+fn get_fstrim_status() -> Option<String> {
+    return linux::get_fstrim_status();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_fstrim_status() -> Option<String> {
+    return None;
+}
+
+/// Global unicast (`2000::/3`), so link-local and unique-local addresses
+/// are never picked.
+fn is_global_ipv6(addr: &Ipv6Addr) -> bool {
+    return addr.segments()[0] & 0xe000 == 0x2000;
+}
+
+#[cfg(target_os = "linux")]
+fn get_ipv6_unstable_addrs() -> Vec<Ipv6Addr> {
+    return linux::get_ipv6_unstable_addrs();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_ipv6_unstable_addrs() -> Vec<Ipv6Addr> {
+    return vec![];
+}
+
+/// The routed global address when it's a stable one, else any stable global
+/// address, falling back to a temporary one.
+fn get_primary_ipv6() -> Option<Ipv6Addr> {
+    let unstable = get_ipv6_unstable_addrs();
+    let networks = Networks::new_with_refreshed_list();
+    let mut global: Vec<Ipv6Addr> = networks
+        .values()
+        .flat_map(|data| data.ip_networks())
+        .filter_map(|network| match network.addr {
+            IpAddr::V6(addr) if is_global_ipv6(&addr) => Some(addr),
+            _ => None,
+        })
+        .collect();
+    if let Some(IpAddr::V6(routed)) = route_source_addr("[::]:0", "[2001:db8::1]:9")
+        && is_global_ipv6(&routed)
+    {
+        global.insert(0, routed);
+    }
+    return global
+        .iter()
+        .find(|addr| !unstable.contains(addr))
+        .or(global.first())
+        .copied();
+}
+
+fn get_used_memory(sys: &System, basis: MemoryBasis) -> usize {
+    return match basis {
+        MemoryBasis::Used => sys.used_memory() as usize,
+        MemoryBasis::Available => sys.total_memory().saturating_sub(sys.available_memory()) as usize,
+    };
+}
+
+fn get_total_memory(sys: &System) -> usize {
+    return sys.total_memory() as usize;
+}
+
+fn get_used_swap(sys: &System) -> usize {
+    return sys.used_swap() as usize;
+}
+
+fn get_total_swap(sys: &System) -> usize {
+    return sys.total_swap() as usize;
+}
+
+/// Matches `text` against a shell-style glob where `*` matches any run of
+/// characters and `?` matches exactly one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    return pattern[p..].iter().all(|&c| c == '*');
+}
+
+/// `normal`, `warning` or `critical`: whether the system is stalling on
+/// memory (reclaim and swap), which a used percentage can't tell.
+#[cfg(target_os = "linux")]
+fn get_memory_pressure(explain: bool) -> Option<String> {
+    // Stall percentages at which pressure counts as a warning (`some`) or
+    // critical (`full`).
+    const WARNING_SOME: f64 = 10.0;
+    const CRITICAL_FULL: f64 = 10.0;
+    let (some, full) = linux::get_memory_pressure()?;
+    if explain {
+        explain_once(format!("pressure: PSI memory avg10 some={:.2}% full={:.2}%", some, full));
+    }
+    let tier = if full >= CRITICAL_FULL {
+        "critical"
+    } else if some >= WARNING_SOME {
+        "warning"
+    } else {
+        "normal"
+    };
+    return Some(String::from(tier));
+}
+
+#[cfg(target_os = "macos")]
+fn get_memory_pressure(_explain: bool) -> Option<String> {
+    return match macos::get_memory_pressure_level()? {
+        1 => Some(String::from("normal")),
+        2 => Some(String::from("warning")),
+        4 => Some(String::from("critical")),
+        _ => None,
+    };
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn get_memory_pressure(_explain: bool) -> Option<String> {
+    return None;
+}
+
+/// Network and pseudo filesystems sysinfo may still list; they're left out
+/// unless `disk_include` names them.
+const NON_PHYSICAL_FILE_SYSTEMS: [&str; 9] = [
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "9p", "fuse.sshfs", "overlay", "squashfs",
+];
+
+/// `(used, total)` inodes of the filesystem mounted at `mount_point`.
+/// `None` when it reports no inode limit.
+#[cfg(unix)]
+// `fsfilcnt_t` is 64-bit on Linux but 32-bit on macOS.
+#[allow(clippy::useless_conversion)]
+fn get_inode_counts(mount_point: &str) -> Option<(u64, u64)> {
+    let path = std::ffi::CString::new(mount_point).ok()?;
+    // SAFETY: `statvfs` is plain data, and the call only writes into it.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a valid C string that outlives the call.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let total = u64::from(stat.f_files);
+    if total == 0 {
+        return None;
+    }
+    return Some((total.saturating_sub(u64::from(stat.f_ffree)), total));
+}
+
+#[cfg(not(unix))]
+fn get_inode_counts(_mount_point: &str) -> Option<(u64, u64)> {
+    return None;
+}
+
+/// Mounted filesystems sorted by mount point. With `include` globs only
+/// matching mount points are kept; otherwise every physical one is.
+fn get_disks(include: &[String]) -> Vec<DiskInfo> {
+    let mut disks: Vec<DiskInfo> = sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .map(|disk| DiskInfo {
+            mount_point: disk.mount_point().to_string_lossy().into_owned(),
+            file_system: disk.file_system().to_string_lossy().into_owned(),
+            used_bytes: disk.total_space().saturating_sub(disk.available_space()),
+            total_bytes: disk.total_space(),
+            // sysinfo reads `queue/rotational` on Linux and the seek
+            // penalty on Windows.
+            rotational: match disk.kind() {
+                sysinfo::DiskKind::HDD => Some(true),
+                sysinfo::DiskKind::SSD => Some(false),
+                sysinfo::DiskKind::Unknown(_) => None,
+            },
+            nvme: disk.name().to_string_lossy().contains("nvme"),
+        })
+        .filter(|disk| {
+            if include.is_empty() {
+                return !NON_PHYSICAL_FILE_SYSTEMS.contains(&disk.file_system.as_str());
+            }
+            return include.iter().any(|pattern| glob_match(pattern, &disk.mount_point));
+        })
+        .collect();
+    disks.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    disks.dedup_by(|a, b| a.mount_point == b.mount_point);
+    return disks;
+}
+
+/// Only variables matching one of the user's explicit patterns are listed,
+/// since the environment routinely carries secrets.
+fn get_env_collectors(patterns: &[String]) -> Vec<Box<dyn Collector>> {
+    if patterns.is_empty() {
+        return vec![];
+    }
+    let mut keys: Vec<String> = std::env::vars_os()
+        .filter_map(|(key, _)| key.into_string().ok())
+        .filter(|key| patterns.iter().any(|pattern| glob_match(pattern, key)))
+        .collect();
+    keys.sort();
+    return keys
+        .into_iter()
+        .map(|key| Box::new(EnvVarCollector::new(key)) as Box<dyn Collector>)
+        .collect();
+}
+
+/// Human-readable size with the largest unit that keeps the number >= 1.
+/// Megabytes are shown whole, larger units with one decimal, rounded half
+/// up (so 15.96 GB is `16 GB`, not `15.9 GB`).
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["GB", "TB", "PB"];
+    let mb = bytes as f64 / 1024.0 / 1024.0;
+    if mb.round() < 1024.0 {
+        return format!("{} MB", mb.round() as u64);
+    }
+    let mut value = mb / 1024.0;
+    let mut unit = 0;
+    // Compare the rounded value so 1023.97 GB becomes `1 TB`, not `1024 GB`.
+    while (value * 10.0).round() / 10.0 >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let formatted = format!("{:.1}", (value * 10.0).round() / 10.0);
+    return format!("{} {}", formatted.trim_end_matches(".0"), UNITS[unit]);
+}
+
+/// `1.2M`-style count with a one-letter metric suffix, for inode counts.
+fn format_count(count: u64) -> String {
+    const SCALES: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "K")];
+    for (scale, suffix) in SCALES {
+        let value = (count as f64 / scale * 10.0).round() / 10.0;
+        if value >= 1.0 {
+            return format!("{}{}", format!("{:.1}", value).trim_end_matches(".0"), suffix);
+        }
+    }
+    return count.to_string();
+}
+
+/// `bytes` as a number of `unit`s: megabytes whole, larger units with one
+/// decimal, rounded like `format_bytes`.
+fn format_in_unit(bytes: u64, unit: SizeUnit, grouping: Option<Locale>) -> String {
+    let value = bytes as f64 / unit.bytes() as f64;
+    if matches!(unit, SizeUnit::Mb | SizeUnit::Mib) {
+        return group_digits(value.round() as usize, grouping);
+    }
+    let tenths = (value * 10.0).round() as usize;
+    let whole = group_digits(tenths / 10, grouping);
+    if tenths.is_multiple_of(10) {
+        return whole;
+    }
+    return format!("{}{}{}", whole, grouping.map_or(".", |locale| locale.decimal()), tenths % 10);
+}
+
+/// `format_bytes`, or the size in `unit` when the config fixes one.
+fn format_bytes_as(bytes: u64, unit: Option<SizeUnit>, grouping: Option<Locale>) -> String {
+    return match unit {
+        Some(unit) => format!("{} {}", format_in_unit(bytes, unit, grouping), unit.suffix()),
+        None => format_bytes(bytes),
+    };
+}
+
+/// Bytes to whole megabytes, rounded half up rather than truncated.
+fn bytes_to_mb(bytes: usize) -> usize {
+    const MB: usize = 1024 * 1024;
+    return (bytes + MB / 2) / MB;
+}
+
+/// Collapses runs of identical GPUs (same name, type and VRAM) into
+/// `(first, count)` pairs. Relies on `get_gpu_info` having sorted them.
+fn group_gpus(gpus: &[GpuInfo]) -> Vec<(&GpuInfo, usize)> {
+    let mut groups: Vec<(&GpuInfo, usize)> = vec![];
+    for gpu in gpus {
+        match groups.last_mut() {
+            Some((first, count))
+                if first.gpu_name == gpu.gpu_name
+                    && first.device_type == gpu.device_type
+                    && first.vram_mb == gpu.vram_mb
+                    && first.vram_used_mb == gpu.vram_used_mb
+                    && first.power_watts == gpu.power_watts
+                    && first.api_version == gpu.api_version =>
+            {
+                *count += 1;
+            }
+            _ => groups.push((gpu, 1)),
+        }
+    }
+    return groups;
+}
+
+/// `--max-gpus`: the first `max` GPU lines, and how many GPUs the dropped
+/// lines stood for.
+fn cap_gpu_groups(mut groups: Vec<(&GpuInfo, usize)>, max: Option<usize>) -> (Vec<(&GpuInfo, usize)>, usize) {
+    let Some(max) = max.filter(|max| *max < groups.len()) else {
+        return (groups, 0);
+    };
+    let hidden = groups.split_off(max).iter().map(|(_, count)| count).sum();
+    return (groups, hidden);
+}
+
+/// `VRAM:` summary across all GPUs, for multi-GPU machines only.
+fn vram_summary(gpus: &[GpuInfo], unit: Option<SizeUnit>, grouping: Option<Locale>) -> Option<String> {
+    if gpus.len() < 2 {
+        return None;
+    }
+    let known: Vec<usize> = gpus.iter().filter_map(|gpu| gpu.vram_mb).collect();
+    if known.is_empty() {
+        return None;
+    }
+    let total = format_bytes_as(known.iter().sum::<usize>() as u64 * 1024 * 1024, unit, grouping);
+    let unknown = gpus.len() - known.len();
+    if unknown > 0 {
+        return Some(format!("{} total ({} GPUs, {} unknown)", total, gpus.len(), unknown));
+    }
+    return Some(format!("{} total ({} GPUs)", total, gpus.len()));
+}
+
+/// Locale for `--group-digits`, from the usual `LC_ALL` > `LC_NUMERIC` > `LANG`
+/// chain. `de_DE.UTF-8` is tried as `de-DE`, then `de`, then English.
+fn detect_number_locale() -> Locale {
+    let name = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default();
+    let name = name.split(['.', '@']).next().unwrap_or("").replace('_', "-");
+    let language = name.split('-').next().unwrap_or("");
+    return Locale::from_name(&name)
+        .or_else(|_| Locale::from_name(language))
+        .unwrap_or(Locale::en);
+}
+
+fn group_digits(value: usize, locale: Option<Locale>) -> String {
+    return match locale {
+        Some(locale) => value.to_formatted_string(&locale),
+        None => value.to_string(),
+    };
+}
+
+/// `L1 512K, L2 8M, L3 32M`: whole mebibytes as `M`, anything else as `K`.
+fn format_cpu_caches(caches: &[(u8, u64)]) -> String {
+    const MIB: u64 = 1024 * 1024;
+    return caches
+        .iter()
+        .map(|(level, bytes)| match bytes % MIB {
+            0 => format!("L{} {}M", level, bytes / MIB),
+            _ => format!("L{} {}K", level, bytes / 1024),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+}
+
+/// `2 sockets, 2 NUMA nodes, 48 cores`, without the nodes when the kernel
+/// doesn't report them.
+fn format_topology(topology: &CpuTopology) -> String {
+    let plural = |count: usize, noun: &str| format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" });
+    let mut parts = vec![plural(topology.sockets, "socket")];
+    if let Some(nodes) = topology.numa_nodes {
+        parts.push(plural(nodes, "NUMA node"));
+    }
+    parts.push(plural(topology.physical_cores, "core"));
+    return parts.join(", ");
+}
+
+/// Memory/swap usage in the style picked by `memory_display`, in `unit`
+/// when the config fixes one.
+fn format_usage(used_mb: usize, total_mb: usize, unit: Option<SizeUnit>, opts: &RenderOptions) -> String {
+    let mb = |value: usize| format_bytes_as(value as u64 * 1024 * 1024, unit, opts.digit_grouping);
+    let free_mb = total_mb.saturating_sub(used_mb);
+    return match opts.memory_display {
+        MemoryDisplay::Used => match unit {
+            Some(unit) => format!(
+                "{}/{} {} used",
+                format_in_unit(used_mb as u64 * 1024 * 1024, unit, opts.digit_grouping),
+                format_in_unit(total_mb as u64 * 1024 * 1024, unit, opts.digit_grouping),
+                unit.suffix()
+            ),
+            None => format!(
+                "{}/{} MB used",
+                group_digits(used_mb, opts.digit_grouping),
+                group_digits(total_mb, opts.digit_grouping)
+            ),
+        },
+        MemoryDisplay::Free => format!("{} free of {}", mb(free_mb), mb(total_mb)),
+        MemoryDisplay::Both => format!("{} used, {} free of {}", mb(used_mb), mb(free_mb), mb(total_mb)),
+    };
+}
+
+/// `15/32 GB`: used and total with the unit written once when both
+/// `format_bytes` picks agree, else `512 MB/32 GB`.
+fn format_compact_usage(used_mb: usize, total_mb: usize, unit: Option<SizeUnit>, grouping: Option<Locale>) -> String {
+    let used = format_bytes_as(used_mb as u64 * 1024 * 1024, unit, grouping);
+    let total = format_bytes_as(total_mb as u64 * 1024 * 1024, unit, grouping);
+    return match (used.split_once(' '), total.split_once(' ')) {
+        (Some((used_value, used_unit)), Some((_, total_unit))) if used_unit == total_unit => format!("{}/{}", used_value, total),
+        _ => format!("{}/{}", used, total),
+    };
+}
+
+/// `--memory-swap-combined`: `Mem/Swap: 15/32 GB | 1/8 GB`, or just the
+/// memory under its usual label when there's no swap. Each half is only
+/// there when its field is picked; `memory_display` styles other than
+/// `used` are written out as on the separate lines.
+fn push_memory_swap(fields: &mut Vec<(String, String)>, output_info: &OutputInfo, opts: &RenderOptions) {
+    let usage = |used_mb: usize, total_mb: usize, unit: Option<SizeUnit>| {
+        let text = match opts.memory_display {
+            MemoryDisplay::Used => format_compact_usage(used_mb, total_mb, unit, opts.digit_grouping),
+            MemoryDisplay::Free | MemoryDisplay::Both => format_usage(used_mb, total_mb, unit, opts),
+        };
+        return (text, usage_percent(used_mb, total_mb));
+    };
+    let memory = opts.shows(Field::Memory).then(|| {
+        let (mut memory, percent) = usage(output_info.memory_used_mb, output_info.memory_total_mb, opts.units.memory);
+        if output_info.memory_cgroup_limited {
+            memory.push_str(" (cgroup limit)");
+        }
+        return color_usage(memory, percent, opts);
+    });
+    let swap = (opts.show_swap && output_info.swap_total_mb > 0).then(|| {
+        let (swap, percent) = usage(output_info.swap_used_mb, output_info.swap_total_mb, opts.units.swap);
+        return color_usage(swap, percent, opts);
+    });
+    match (memory, swap) {
+        (Some(memory), Some(swap)) if opts.shows(Field::Swap) => {
+            push_field_as(fields, Field::Memory, "Mem/Swap", format!("{} | {}", memory, swap), opts);
+        }
+        (Some(memory), _) => push_field(fields, Field::Memory, memory, opts),
+        (None, swap) if opts.show_swap => push_optional(fields, Field::Swap, swap, opts),
+        (None, _) => {}
+    }
+}
+
+/// Wraps `text` in yellow or red when `percent` reaches the configured
+/// thresholds. Plain unless colors are on.
+fn color_usage(text: String, percent: f64, opts: &RenderOptions) -> String {
+    if !opts.color {
+        return text;
+    }
+    let color = if percent >= opts.thresholds.critical {
+        color::RED
+    } else if percent >= opts.thresholds.warn {
+        color::YELLOW
+    } else {
+        return text;
+    };
+    return format!("{}{}{}", color, text, color::RESET);
+}
+
+fn usage_percent(used: usize, total: usize) -> f64 {
+    return if total == 0 { 0.0 } else { used as f64 / total as f64 * 100.0 };
+}
+
+/// `23 available (4 security)`, red while security updates are pending.
+fn format_pending_updates(updates: &PendingUpdates, opts: &RenderOptions) -> String {
+    if updates.count == 0 {
+        return String::from("up to date");
+    }
+    let mut value = format!("{} available", updates.count);
+    match updates.security {
+        Some(security) if security > 0 => {
+            value.push_str(&format!(" ({} security)", security));
+            if opts.color {
+                value = format!("{}{}{}", color::RED, value, color::RESET);
+            }
+        }
+        _ => {}
+    }
+    return value;
+}
+
+/// One bar per sample, scaled between the lowest and highest of them. The
+/// scale spans at least `min_span`, so sensor noise of a degree or two
+/// stays flat instead of filling the whole height.
+fn sparkline(samples: &[f64], min_span: f64, bars: &[char; 8]) -> String {
+    let low = samples.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max).max(low + min_span);
+    return samples
+        .iter()
+        .map(|sample| bars[(((sample - low) / (high - low)) * 7.0).round().clamp(0.0, 7.0) as usize])
+        .collect();
+}
+
+/// ` ▁▂▃▅▇` after a temperature once `--watch` has seen a couple of ticks.
+fn temperature_trend(history: &[f64], opts: &RenderOptions) -> String {
+    if history.len() < 2 {
+        return String::new();
+    }
+    return format!(" {}", sparkline(history, 5.0, &opts.glyphs().spark));
+}
+
+/// `ashwin-fetch (main, 3 ahead, 1 behind)`; a detached HEAD shows its
+/// commit instead of a branch, and counts that are 0 are left out.
+fn format_repo(repo: &git::RepoInfo) -> String {
+    let mut details = vec![if repo.detached { format!("detached at {}", repo.head) } else { repo.head.clone() }];
+    details.extend(repo.ahead.filter(|ahead| *ahead > 0).map(|ahead| format!("{} ahead", ahead)));
+    details.extend(repo.behind.filter(|behind| *behind > 0).map(|behind| format!("{} behind", behind)));
+    return format!("{} ({})", repo.name, details.join(", "));
+}
+
+/// Entropy pool size under which `--show-entropy` turns red.
+const LOW_ENTROPY_BITS: usize = 256;
+
+/// More CPU brands than this are summed up on one line, so machines that
+/// report a brand per core don't print a line per core.
+const MAX_CPU_LINES: usize = 4;
+
+/// `--cpu-combined`: `Cortex-A78 x4 + Cortex-A55 x4, 12.50% avg`, biggest
+/// cluster first. The usage is averaged over every core.
+fn format_combined_cpus(cpus: &BTreeMap<&str, CpuInfo>) -> String {
+    let mut clusters: Vec<(&&str, &CpuInfo)> = cpus.iter().collect();
+    clusters.sort_by(|(a_brand, a), (b_brand, b)| b.num_cores.cmp(&a.num_cores).then_with(|| a_brand.cmp(b_brand)));
+    let mut names: Vec<String> = clusters
+        .iter()
+        .take(MAX_CPU_LINES)
+        .map(|(brand, info)| format!("{} x{}", brand, info.num_cores))
+        .collect();
+    if clusters.len() > MAX_CPU_LINES {
+        let rest = &clusters[MAX_CPU_LINES..];
+        let rest_cores: usize = rest.iter().map(|(_, info)| info.num_cores).sum();
+        names.push(format!("{} more x{}", rest.len(), rest_cores));
+    }
+    let cores: usize = clusters.iter().map(|(_, info)| info.num_cores).sum();
+    let usage: Option<f64> = clusters
+        .iter()
+        .map(|(_, info)| info.avg_usage.map(|usage| usage * info.num_cores as f64))
+        .sum();
+    return match usage {
+        Some(usage) if cores > 0 => format!("{}, {:.2}% avg", names.join(" + "), usage / cores as f64),
+        _ => format!("{}, --% avg", names.join(" + ")),
+    };
+}
+
+/// `5.00 GHz`, or `800 MHz` for slow clocks with `FrequencyUnit::Auto`.
+/// MHz are whole; `decimals` applies to GHz.
+fn format_frequency(mhz: f64, unit: FrequencyUnit, decimals: usize) -> String {
+    let ghz = match unit {
+        FrequencyUnit::Auto => mhz >= 1000.0,
+        FrequencyUnit::Mhz => false,
+        FrequencyUnit::Ghz => true,
+    };
+    return if ghz { format!("{:.*} GHz", decimals, mhz / 1000.0) } else { format!("{:.0} MHz", mhz) };
+}
+
+/// Rated clocks when known, otherwise the highest clock observed this run.
+/// `None` when neither is known, as under `--deterministic`.
+fn format_cpu_frequency(cpu_info: &CpuInfo, opts: &RenderOptions) -> Option<String> {
+    let format = |mhz| format_frequency(mhz, opts.frequency_unit, opts.frequency_decimals);
+    return match (cpu_info.base_frequency_mhz, cpu_info.boost_frequency_mhz) {
+        (Some(base), Some(boost)) => Some(format!("{} base / {} boost", format(base), format(boost))),
+        (Some(base), None) => Some(format!("{} base", format(base))),
+        (None, Some(boost)) => Some(format!("{} boost", format(boost))),
+        (None, None) if cpu_info.max_frequency_mhz > 0.0 => Some(format!("{} (max)", format(cpu_info.max_frequency_mhz))),
+        (None, None) => None,
+    };
+}
+
+/// Singular and plural duration words for `--locale-uptime`.
+#[derive(Debug)]
+struct UptimeWords {
+    day: (&'static str, &'static str),
+    hour: (&'static str, &'static str),
+    minute: (&'static str, &'static str),
+}
+
+/// Languages `--lang` accepts, by ISO 639-1 code.
+const UPTIME_WORDS: [(&str, UptimeWords); 7] = [
+    ("en", UptimeWords { day: ("day", "days"), hour: ("hour", "hours"), minute: ("minute", "minutes") }),
+    ("de", UptimeWords { day: ("Tag", "Tage"), hour: ("Stunde", "Stunden"), minute: ("Minute", "Minuten") }),
+    ("es", UptimeWords { day: ("día", "días"), hour: ("hora", "horas"), minute: ("minuto", "minutos") }),
+    ("fr", UptimeWords { day: ("jour", "jours"), hour: ("heure", "heures"), minute: ("minute", "minutes") }),
+    ("it", UptimeWords { day: ("giorno", "giorni"), hour: ("ora", "ore"), minute: ("minuto", "minuti") }),
+    ("nl", UptimeWords { day: ("dag", "dagen"), hour: ("uur", "uur"), minute: ("minuut", "minuten") }),
+    ("pt", UptimeWords { day: ("dia", "dias"), hour: ("hora", "horas"), minute: ("minuto", "minutos") }),
+];
+
+/// Words for a language code such as `de`, `de_DE.UTF-8` or `pt-BR`.
+fn uptime_words(language: &str) -> Option<&'static UptimeWords> {
+    let code = language.split(['_', '-', '.', '@']).next().unwrap_or("").to_ascii_lowercase();
+    return UPTIME_WORDS.iter().find(|(name, _)| *name == code).map(|(_, words)| words);
+}
+
+/// `--lang`, else the language of the `LC_ALL` > `LC_MESSAGES` > `LANG`
+/// chain, falling back to English for languages without a table.
+fn resolve_uptime_words(lang: Option<&str>) -> &'static UptimeWords {
+    let detected = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()));
+    return lang
+        .map(String::from)
+        .or(detected)
+        .and_then(|language| uptime_words(&language))
+        .unwrap_or(&UPTIME_WORDS[0].1);
+}
+
+/// Like `convert_unix_to_human_string`, with whole words: `3 Tage, 2 Stunden, 5 Minuten`.
+fn convert_unix_to_localized_string(unix_time: usize, words: &UptimeWords) -> String {
+    let duration = Duration::seconds(unix_time as i64);
+    let days = duration.num_days();
+    let hours = duration.num_hours() % 24;
+    let minutes = duration.num_minutes() % 60;
+    let word = |count: i64, (singular, plural): (&str, &str)| format!("{} {}", count, if count == 1 { singular } else { plural });
+
+    let mut parts = vec![];
+    if days > 0 {
+        parts.push(word(days, words.day));
+    }
+    if days > 0 || hours > 0 {
+        parts.push(word(hours, words.hour));
+    }
+    parts.push(word(minutes, words.minute));
+    return parts.join(", ");
+}
+
+fn convert_unix_to_human_string(unix_time: usize) -> String {
+    let duration = Duration::seconds(unix_time as i64);
+    let days = duration.num_days();
+    let hours = duration.num_hours() % 24;
+    let minutes = duration.num_minutes() % 60;
+
+    if days > 0 {
+        return format!("{}d {}h {}m", days, hours, minutes);
+    } else if hours > 0 {
+        return format!("{}h {}m", hours, minutes);
+    } else {
+        return format!("{}m", minutes);
+    }
+}
+
+/// When the machine booted, `uptime` seconds before `now`, in `now`'s
+/// timezone.
+fn boot_time_string<Tz: chrono::TimeZone>(now: chrono::DateTime<Tz>, uptime: usize) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    return (now - Duration::seconds(uptime as i64)).format("%Y-%m-%d %H:%M").to_string();
+}
+
+/// `--width 0` turns width handling off, otherwise fall back to the real
+/// terminal, if there is one.
+fn resolve_width(width_override: Option<usize>) -> Option<usize> {
+    return match width_override {
+        Some(0) => None,
+        Some(width) => Some(width),
+        None => terminal_size::terminal_size().map(|(width, _)| width.0 as usize),
+    };
+}
+
+/// Cuts `text` to at most `max_width` terminal columns, marking the cut with
+/// `ellipsis` when there is room for it. Color escapes take no columns; a
+/// cut through a colored value resets the color so it can't run on.
+fn truncate_to_width(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if color::strip_ansi(text).width() <= max_width {
+        return text.to_string();
+    }
+    let ellipsis = if ellipsis.width() <= max_width { ellipsis } else { "" };
+    let budget = max_width - ellipsis.width();
+    let mut truncated = String::new();
+    let mut used = 0;
+    let mut colored = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI: parameters up to a final byte in `@..=~`.
+            truncated.push(c);
+            if let Some(next) = chars.next() {
+                truncated.push(next);
+                if next == '[' {
+                    for c in chars.by_ref() {
+                        truncated.push(c);
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+            }
+            colored = true;
+            continue;
+        }
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > budget {
+            break;
+        }
+        used += char_width;
+        truncated.push(c);
+    }
+    if colored {
+        truncated.push_str(color::RESET);
+    }
+    truncated.push_str(ellipsis);
+    return truncated;
+}
+
+/// A `dumb` terminal (emacs shell, serial consoles) can't be trusted with
+/// anything beyond ASCII.
+fn detect_ascii_only() -> bool {
+    return std::env::var("TERM").is_ok_and(|term| term == "dumb");
+}
+
+/// Corners, horizontal and vertical edge for `--border`, in that order.
+fn border_chars(style: BorderStyle, ascii_only: bool) -> Option<[&'static str; 6]> {
+    return match style {
+        BorderStyle::None => None,
+        _ if ascii_only => Some(["+", "+", "+", "+", "-", "|"]),
+        BorderStyle::Rounded => Some(["╭", "╮", "╰", "╯", "─", "│"]),
+        BorderStyle::Square => Some(["┌", "┐", "└", "┘", "─", "│"]),
+    };
+}
+
+/// Column at which values start: wide enough for the longest label plus a
+/// space, and never narrower than the classic layout. An explicit width from
+/// the config wins, as long as every label still fits.
+fn resolve_label_width(fields: &[(String, String)], configured: Option<usize>) -> usize {
+    let longest = fields.iter().map(|(label, _)| label.width() + 2).max().unwrap_or(0);
+    return match configured {
+        Some(width) => width.max(longest),
+        None => longest.max(DEFAULT_LABEL_WIDTH),
+    };
+}
+
+/// The single place deciding whether an optional field is shown. `None`
+/// means its collector found nothing (see "Empty fields" in the README);
+/// such lines are dropped unless `--show-empty` asks for a placeholder.
+fn push_optional(fields: &mut Vec<(String, String)>, field: Field, value: Option<String>, opts: &RenderOptions) {
+    push_optional_as(fields, field, field.label(), value, opts);
+}
+
+/// `push_optional` for fields split over several lines, such as IPv4/IPv6.
+fn push_optional_as(fields: &mut Vec<(String, String)>, field: Field, label: &str, value: Option<String>, opts: &RenderOptions) {
+    if !opts.shows(field) {
+        return;
+    }
+    match value {
+        Some(value) => fields.push((label.to_string(), value)),
+        None if opts.show_empty => fields.push((label.to_string(), String::from("N/A"))),
+        None => {}
+    }
+}
+
+fn push_field(fields: &mut Vec<(String, String)>, field: Field, value: String, opts: &RenderOptions) {
+    push_field_as(fields, field, field.label(), value, opts);
+}
+
+/// `push_field` under a label of its own, such as `Mem/Swap`.
+fn push_field_as(fields: &mut Vec<(String, String)>, field: Field, label: &str, value: String, opts: &RenderOptions) {
+    if opts.shows(field) {
+        fields.push((label.to_string(), value));
+    }
+}
+
+/// Labelled info lines, in display order, and the index of the one
+/// `--full-cmdline` keeps from being cut to the terminal width.
+fn build_fields(output_info: &OutputInfo, opts: &RenderOptions) -> (Vec<(String, String)>, Option<usize>) {
+    let mut fields: Vec<(String, String)> = vec![];
+    let mut untruncated = None;
+    push_field(&mut fields, Field::Os, output_info.os.clone(), opts);
+    push_optional(&mut fields, Field::Model, output_info.model.clone(), opts);
+    push_field(&mut fields, Field::Serial, output_info.serial_number.clone(), opts);
+    push_optional(&mut fields, Field::Chassis, output_info.chassis.clone(), opts);
+    if let Some(firmware) = &output_info.firmware {
+        let value = match (firmware.uefi, firmware.secure_boot) {
+            (true, Some(true)) => String::from("UEFI (Secure Boot on)"),
+            (true, Some(false)) => String::from("UEFI (Secure Boot off)"),
+            (true, None) => String::from("UEFI"),
+            (false, _) => String::from("BIOS (legacy)"),
+        };
+        push_field(&mut fields, Field::Firmware, value, opts);
+    }
+    let kernel = output_info.kernel.as_ref().map(|kernel| {
+        let mut value = kernel.clone();
+        if let Some(date) = &output_info.kernel_build_date {
+            value.push_str(&format!(" (built {})", date));
+        }
+        if let Some(newer) = &output_info.newer_kernel {
+            value.push_str(&format!(" ({} installed {} reboot needed)", newer, opts.glyphs().dash));
+        }
+        return value;
+    });
+    push_optional(&mut fields, Field::Kernel, kernel, opts);
+    if let Some(cmdline) = &output_info.kernel_cmdline {
+        if opts.full_cmdline && opts.shows(Field::Cmdline) {
+            untruncated = Some(fields.len());
+        }
+        push_field(&mut fields, Field::Cmdline, cmdline.clone(), opts);
+    }
+    // Opt-in, so never padded with a placeholder.
+    if let Some(updated) = &output_info.updated {
+        push_field(&mut fields, Field::Updated, updated.clone(), opts);
+    }
+    if let Some(updates) = &output_info.pending_updates {
+        push_field(&mut fields, Field::Updates, format_pending_updates(updates, opts), opts);
+    }
+    let uptime = match opts.uptime_words {
+        Some(words) => convert_unix_to_localized_string(output_info.uptime.0, words),
+        None => convert_unix_to_human_string(output_info.uptime.0),
+    };
+    let uptime = if opts.uptime_since {
+        format!("{} (since {})", uptime, boot_time_string(chrono::Local::now(), output_info.uptime.0))
+    } else {
+        uptime
+    };
+    push_field(&mut fields, Field::Uptime, uptime, opts);
+    if let Some(count) = output_info.failed_units {
+        let mut value = format!("{} unit{}", count, if count == 1 { "" } else { "s" });
+        if count == 0 && opts.color {
+            let good = opts.palette.map_or(color::GREEN, |palette| palette.good);
+            value = format!("{}{}{}", good, value, color::RESET);
+        }
+        push_field(&mut fields, Field::FailedUnits, value, opts);
+    }
+    if let Some((threads, zombies)) = output_info.thread_counts {
+        let mut value = group_digits(threads, opts.digit_grouping);
+        if zombies > 0 {
+            value.push_str(&format!(" ({} zombie)", zombies));
+        }
+        push_field(&mut fields, Field::Threads, value, opts);
+    }
+    if let Some((open, max)) = output_info.fd_counts {
+        let value = format!("{} / {}", group_digits(open, opts.digit_grouping), group_digits(max, opts.digit_grouping));
+        push_field(&mut fields, Field::Fds, color_usage(value, usage_percent(open, max), opts), opts);
+    }
+    if let Some(bits) = output_info.entropy_bits {
+        let value = format!("{} bits", bits);
+        // Below this, blocking reads of `/dev/random` may stall.
+        let value = if bits < LOW_ENTROPY_BITS && opts.color { format!("{}{}{}", color::RED, value, color::RESET) } else { value };
+        push_field(&mut fields, Field::Entropy, value, opts);
+    }
+    if let Some(parent) = &output_info.parent_process {
+        push_field(&mut fields, Field::ShellPid, format!("{} ({})", parent.pid, parent.name), opts);
+    }
+    if let Some(session) = &output_info.session {
+        let privileged = session.privileged.map(|privileged| match privileged {
+            true if cfg!(windows) => "admin",
+            true => session.account.as_deref().unwrap_or("superuser"),
+            false => "user",
+        });
+        let parts: Vec<&str> = session.kind.as_deref().into_iter().chain(privileged).collect();
+        push_field(&mut fields, Field::Session, parts.join(", "), opts);
+    }
+    if let Some(users) = &output_info.users {
+        let mut value = format!("{} logged in", users.len());
+        if opts.verbose > 0 && !users.is_empty() {
+            let mut names = users.clone();
+            names.sort();
+            names.dedup();
+            value.push_str(&format!(" ({})", names.join(", ")));
+        }
+        push_field(&mut fields, Field::Users, value, opts);
+    }
+    if let Some(top) = &output_info.top_processes {
+        let busiest = format!("{} ({:.0}%)", top.cpu_name, top.cpu_percent);
+        push_optional_as(&mut fields, Field::Top, &format!("{} CPU", Field::Top.label()), Some(busiest), opts);
+        let largest = format!("{} ({})", top.memory_name, format_bytes(top.memory_bytes));
+        push_optional_as(&mut fields, Field::Top, &format!("{} Mem", Field::Top.label()), Some(largest), opts);
+    }
+    // System-wide, so it only goes on the first CPU line.
+    let mut breakdown = output_info.cpu_breakdown.as_ref().map(|breakdown| {
+        format!(
+            " ({:.0}u {:.0}s {:.0}i)",
+            breakdown.user_percent, breakdown.system_percent, breakdown.idle_percent
+        )
+    });
+    let cpu_combined = (opts.cpu_combined || output_info.cpu.len() > MAX_CPU_LINES) && output_info.cpu.len() > 1;
+    if cpu_combined {
+        let value = format!("{}{}", format_combined_cpus(&output_info.cpu), breakdown.take().unwrap_or_default());
+        push_field(&mut fields, Field::Cpu, value, opts);
+    }
+    for (cpu_brand, cpu_info) in output_info.cpu.iter().filter(|_| !cpu_combined) {
+        push_field(
+            &mut fields,
+            Field::Cpu,
+            format!(
+                "{}{} - {}, {} avg{}{}",
+                cpu_brand,
+                cpu_info.codename.map(|name| format!(" ({})", name)).unwrap_or_default(),
+                format_core_count(output_info, cpu_info),
+                match cpu_info.avg_usage {
+                    Some(usage) => format!("{:.2}%", usage),
+                    None => String::from("--%"),
+                },
+                breakdown.take().unwrap_or_default(),
+                format_cpu_frequency(cpu_info, opts).map(|frequency| format!(", {}", frequency)).unwrap_or_default()
+            ),
+            opts,
+        );
+    }
+    let cpu_temperature = output_info.cpu_temperature.as_ref().map(|temperature| {
+        let mut value = format!("{:.0}{}C", temperature.celsius, opts.glyphs().degree);
+        if opts.verbose > 0 {
+            value.push_str(&format!(" ({})", temperature.sensor));
+        }
+        value + &temperature_trend(&opts.cpu_temp_history, opts)
+    });
+    push_optional(&mut fields, Field::CpuTemp, cpu_temperature, opts);
+    if !output_info.cpu_caches.is_empty() {
+        push_field(&mut fields, Field::Cache, format_cpu_caches(&output_info.cpu_caches), opts);
+    }
+    if let Some(topology) = &output_info.cpu_topology {
+        push_field(&mut fields, Field::Topology, format_topology(topology), opts);
+    }
+    if let Some(virtualization) = &output_info.virtualization {
+        push_field(&mut fields, Field::Virt, virtualization.clone(), opts);
+    }
+    if let Some(governor) = &output_info.governor {
+        push_field(&mut fields, Field::Governor, governor.clone(), opts);
+    }
+    if output_info.gpu.is_empty() {
+        let unavailable = output_info.gpu_unavailable.as_ref().filter(|_| opts.verbose > 0);
+        push_optional(&mut fields, Field::Gpu, unavailable.map(|reason| format!("unavailable ({})", reason)), opts);
+    }
+    let gpu_groups = if !opts.shows(Field::Gpu) {
+        vec![]
+    } else if opts.gpu_list || output_info.gpu.len() <= GPU_GROUP_THRESHOLD {
+        output_info.gpu.iter().map(|gpu| (gpu, 1)).collect()
+    } else {
+        group_gpus(&output_info.gpu)
+    };
+    let (gpu_groups, hidden_gpus) = cap_gpu_groups(gpu_groups, opts.max_gpus);
+    for (gpu_info, count) in gpu_groups {
+        if opts.verbose > 1 && count > 1 {
+            explain_once(format!(
+                "gpu: {} identical `{}` adapters grouped into one line (--gpu-list shows each)",
+                count, gpu_info.gpu_name
+            ));
+        }
+        let details = match gpu_info.vram_mb {
+            Some(vram_mb) => format!(
+                "{}, {}{}",
+                gpu_type_label(gpu_info.device_type),
+                format_bytes_as(vram_mb as u64 * 1024 * 1024, opts.units.vram, opts.digit_grouping),
+                if count > 1 { " each" } else { "" }
+            ),
+            None => gpu_type_label(gpu_info.device_type).to_string(),
+        };
+        let gpu_name = normalize_gpu_name(&gpu_info.gpu_name, opts.gpu_name_style);
+        let name = if count > 1 { format!("{}x {}", count, gpu_name) } else { gpu_name };
+        let mut value = format!("{} ({})", name, details);
+        if let Some(power_watts) = gpu_info.power_watts {
+            value.push_str(&format!(" - {:.0}W", power_watts));
+        }
+        if let (Some(used_mb), Some(total_mb)) = (gpu_info.vram_used_mb, gpu_info.vram_mb.filter(|_| count == 1)) {
+            let percent = usage_percent(used_mb, total_mb);
+            let amounts = match opts.units.vram {
+                Some(unit) => format!(
+                    "{}/{} {}",
+                    format_in_unit(used_mb as u64 * 1024 * 1024, unit, opts.digit_grouping),
+                    format_in_unit(total_mb as u64 * 1024 * 1024, unit, opts.digit_grouping),
+                    unit.suffix()
+                ),
+                None => format!(
+                    "{}/{} MB",
+                    group_digits(used_mb, opts.digit_grouping),
+                    group_digits(total_mb, opts.digit_grouping)
+                ),
+            };
+            value.push_str(&format!(" - {} VRAM {}", amounts, color_usage(format!("({:.0}%)", percent), percent, opts)));
+        }
+        if let Some(api_version) = &gpu_info.api_version {
+            value.push_str(&format!(" - {}", api_version));
+        }
+        if let Some(pci_bus_id) = gpu_info.pci_bus_id.as_ref().filter(|_| opts.verbose > 0 && count == 1) {
+            value.push_str(&format!(" [{}]", pci_bus_id));
+        }
+        fields.push((format!("{} {:.>3}", Field::Gpu.label(), gpu_info.device_index + opts.gpu_index_base), value));
+    }
+    if hidden_gpus > 0 {
+        fields.push((Field::Gpu.label().to_string(), format!("{} and {} more", opts.glyphs().ellipsis, hidden_gpus)));
+    }
+    // How the GPU lines came about, for when they look wrong.
+    if let Some(raw) = output_info.raw_gpu_adapters.filter(|_| opts.verbose > 0 && opts.shows(Field::Gpu)) {
+        fields.push((String::from("Adapters"), format!("{} raw / {} unique", raw, output_info.gpu.len())));
+    }
+    push_optional(&mut fields, Field::Vram, vram_summary(&output_info.gpu, opts.units.vram, opts.digit_grouping), opts);
+    if let Some(celsius) = output_info.gpu_temperature {
+        let value = format!("{:.0}{}C{}", celsius, opts.glyphs().degree, temperature_trend(&opts.gpu_temp_history, opts));
+        push_field(&mut fields, Field::GpuTemp, value, opts);
+    }
+    if output_info.displays.is_empty() {
+        push_optional(&mut fields, Field::Displays, None, opts);
+    } else {
+        push_field(&mut fields, Field::Displays, output_info.displays.len().to_string(), opts);
+    }
+    let displays = if opts.shows(Field::Displays) { output_info.displays.as_slice() } else { &[] };
+    for (index, display) in displays.iter().enumerate() {
+        let mut value = format!("{}x{}", display.width, display.height);
+        if let Some(refresh_hz) = display.refresh_hz {
+            value.push_str(&format!(" @ {:.0}Hz", refresh_hz));
+        }
+        let color: Vec<String> = [
+            display.bit_depth.map(|bits| format!("{}-bit", bits)),
+            display.hdr.filter(|hdr| *hdr).map(|_| String::from("HDR")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !color.is_empty() {
+            value.push_str(&format!(", {}", color.join(" ")));
+        }
+        if display.primary {
+            value.push_str(" (primary)");
+        }
+        fields.push((format!("Display {}", index + 1), value));
+    }
+    push_optional(&mut fields, Field::Audio, output_info.audio.clone(), opts);
+    if let Some(printer) = &output_info.printer {
+        push_field(&mut fields, Field::Printer, format!("{} (default)", printer), opts);
+    }
+    if let Some(ip) = opts.ip {
+        let (ipv4_label, ipv6_label) = if ip == IpMode::Both { ("IPv4", "IPv6") } else { ("IP", "IP") };
+        if ip.v4() {
+            push_optional_as(&mut fields, Field::Ip, ipv4_label, output_info.ipv4.map(|addr| addr.to_string()), opts);
+        }
+        if ip.v6() {
+            push_optional_as(&mut fields, Field::Ip, ipv6_label, output_info.ipv6.map(|addr| addr.to_string()), opts);
+        }
+    }
+    if let Some(gateway) = output_info.gateway {
+        push_field(&mut fields, Field::Gateway, gateway.to_string(), opts);
+    }
+    if let Some(wifi) = &output_info.wifi {
+        let value = match wifi.signal_dbm {
+            Some(dbm) => format!("{} ({} dBm)", wifi.ssid, dbm),
+            None => wifi.ssid.clone(),
+        };
+        push_field(&mut fields, Field::Wifi, value, opts);
+    }
+    let battery = output_info.battery.as_ref().map(|battery| {
+        let mut value = format!("{:.0}%", battery.percent);
+        let details: Vec<String> = [
+            battery.health_percent.map(|health| format!("health {:.0}%", health)),
+            battery.cycle_count.map(|cycles| format!("{} cycle{}", cycles, if cycles == 1 { "" } else { "s" })),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !details.is_empty() {
+            value.push_str(&format!(" ({})", details.join(", ")));
+        }
+        return value;
+    });
+    push_optional(&mut fields, Field::Battery, battery, opts);
+    if let Some(power) = &output_info.power {
+        let value = match (power.on_ac, power.adapter_watts) {
+            (true, Some(watts)) => format!("AC ({}W adapter)", watts),
+            (true, None) => String::from("AC"),
+            (false, _) => String::from("Battery"),
+        };
+        push_field(&mut fields, Field::Power, value, opts);
+    }
+    if opts.memory_swap_combined {
+        push_memory_swap(&mut fields, output_info, opts);
+    } else {
+        let mut memory = format_usage(output_info.memory_used_mb, output_info.memory_total_mb, opts.units.memory, opts);
+        if output_info.memory_cgroup_limited {
+            memory.push_str(" (cgroup limit)");
+        }
+        let memory_percent = usage_percent(output_info.memory_used_mb, output_info.memory_total_mb);
+        push_field(&mut fields, Field::Memory, color_usage(memory, memory_percent, opts), opts);
+        if opts.show_swap {
+            let swap = (output_info.swap_total_mb > 0).then(|| {
+                let swap = format_usage(output_info.swap_used_mb, output_info.swap_total_mb, opts.units.swap, opts);
+                return color_usage(swap, usage_percent(output_info.swap_used_mb, output_info.swap_total_mb), opts);
+            });
+            push_optional(&mut fields, Field::Swap, swap, opts);
+        }
+    }
+    if let Some(pressure) = &output_info.memory_pressure {
+        let value = match pressure.as_str() {
+            "critical" if opts.color => format!("{}{}{}", color::RED, pressure, color::RESET),
+            "warning" if opts.color => format!("{}{}{}", color::YELLOW, pressure, color::RESET),
+            _ => pressure.clone(),
+        };
+        push_field(&mut fields, Field::Pressure, value, opts);
+    }
+    if !output_info.toolchains.is_empty() {
+        push_field(&mut fields, Field::Toolchains, output_info.toolchains.join(", "), opts);
+    }
+    if let Some(repo) = &output_info.repo {
+        push_field(&mut fields, Field::Repo, format_repo(repo), opts);
+    }
+    if output_info.disks.is_empty() {
+        push_optional(&mut fields, Field::Disk, None, opts);
+    } else if opts.shows(Field::Disk) {
+        // Mount points, used and total sizes each line up in a column.
+        let sizes: Vec<(String, String)> = output_info
+            .disks
+            .iter()
+            .map(|disk| (format_bytes_as(disk.used_bytes, opts.units.disk, opts.digit_grouping), format_bytes_as(disk.total_bytes, opts.units.disk, opts.digit_grouping)))
+            .collect();
+        let mount_width = output_info.disks.iter().map(|disk| disk.mount_point.width()).max().unwrap_or(0);
+        let used_width = sizes.iter().map(|(used, _)| used.len()).max().unwrap_or(0);
+        let total_width = sizes.iter().map(|(_, total)| total.len()).max().unwrap_or(0);
+        for (disk, (used, total)) in output_info.disks.iter().zip(sizes) {
+            let percent = disk.used_bytes as f64 / disk.total_bytes as f64 * 100.0;
+            let padding = mount_width - disk.mount_point.width();
+            let mut value = format!(
+                "{}{}  {:>used_width$} / {:>total_width$} {}",
+                disk.mount_point,
+                " ".repeat(padding),
+                used,
+                total,
+                color_usage(format!("({:.0}%)", percent), percent, opts),
+                used_width = used_width,
+                total_width = total_width
+            );
+            if let Some(storage_type) = disk.storage_type() {
+                value.push_str(&format!(" ({})", storage_type));
+            }
+            fields.push((Field::Disk.label().to_string(), value));
+        }
+    }
+    if let Some((used, total)) = output_info.root_inodes {
+        let percent = used as f64 / total as f64 * 100.0;
+        let value = format!("{}/{} {}", format_count(used), format_count(total), color_usage(format!("({:.0}%)", percent), percent, opts));
+        push_optional_as(&mut fields, Field::Inodes, &format!("{} (/)", Field::Inodes.label()), Some(value), opts);
+    }
+    if let Some(encryption) = &output_info.encryption {
+        push_field(&mut fields, Field::Encryption, encryption.clone(), opts);
+    }
+    if let Some(trim) = &output_info.trim {
+        push_field(&mut fields, Field::Trim, trim.clone(), opts);
+    }
+    return (fields, untruncated);
+}
+
+/// `--deterministic`: zeroes or drops everything that changes from one run
+/// to the next on an idle machine, so two runs print the same bytes. What's
+/// left is the hardware and software configuration.
+fn freeze_volatile_fields(output_info: &mut OutputInfo) {
+    output_info.timestamp = None;
+    output_info.uptime = Uptime(0);
+    for cpu in output_info.cpu.values_mut() {
+        cpu.avg_usage = None;
+        cpu.max_frequency_mhz = 0.0;
+    }
+    output_info.cpu_breakdown = None;
+    output_info.cpu_temperature = None;
+    output_info.gpu_temperature = None;
+    for gpu in &mut output_info.gpu {
+        gpu.vram_used_mb = None;
+        gpu.power_watts = None;
+    }
+    output_info.memory_used_mb = 0;
+    output_info.swap_used_mb = 0;
+    output_info.memory_pressure = None;
+    for disk in &mut output_info.disks {
+        disk.used_bytes = 0;
+    }
+    output_info.root_inodes = output_info.root_inodes.map(|(_, total)| (0, total));
+    if let Some(battery) = &mut output_info.battery {
+        battery.percent = 0.0;
+    }
+    if let Some(wifi) = &mut output_info.wifi {
+        wifi.signal_dbm = None;
+    }
+    output_info.thread_counts = None;
+    output_info.fd_counts = None;
+    output_info.entropy_bits = None;
+    output_info.parent_process = None;
+    output_info.top_processes = None;
+    // Whether a collector beat the timeout depends on the machine's load.
+    if let Some(errors) = &mut output_info.errors {
+        errors.retain(|_, reason| reason != "timed out" && reason != "still running");
+    }
+}
+
+/// `user@host`, shortened according to `--fqdn` and `--keep-domain`.
+fn header(output_info: &OutputInfo, opts: &RenderOptions) -> String {
+    let hostname = if opts.fqdn {
+        output_info.hostname.as_str()
+    } else {
+        short_hostname(&output_info.hostname)
+    };
+    let username = if opts.keep_domain || opts.verbose > 0 {
+        output_info.username.as_str()
+    } else {
+        strip_user_domain(&output_info.username)
+    };
+    return format!("{}@{}", username, hostname);
+}
+
+/// The config `--dump-config` prints: command-line overrides applied and
+/// defaults filled in, so every key shows the value in effect.
+fn effective_config(options: &cli::Options, mut config: config::Config) -> config::Config {
+    config.os_name = options.os_name.clone().or(config.os_name);
+    config.fields_file = options.fields_file.clone().map(PathBuf::from).or(config.fields_file);
+    config.gap = Some(config.gap.unwrap_or(logo::GAP));
+    config.header_position = Some(if options.no_header {
+        HeaderPosition::None
+    } else {
+        options.header_position.or(config.header_position).unwrap_or_default()
+    });
+    config.frequency_unit = Some(options.frequency_unit.or(config.frequency_unit).unwrap_or_default());
+    config.gpu_index_base = Some(options.gpu_index_base.or(config.gpu_index_base).unwrap_or(0));
+    config.collector_timeout_ms = Some(config.collector_timeout_ms.unwrap_or(DEFAULT_COLLECTOR_TIMEOUT_MS));
+    config.toolchains = Some(config.toolchains.unwrap_or_else(|| toolchain::DEFAULT_TOOLCHAINS.map(String::from).to_vec()));
+    if options.memory_from_proc {
+        config.memory_source = MemorySource::ProcMeminfo;
+    }
+    config.memory_swap_combined |= options.memory_swap_combined;
+    config.uptime_since |= options.uptime_since;
+    config.show_timings |= options.timings;
+    config.show_git |= options.show_git;
+    config.cache |= options.cache;
+    return config;
+}
+
+fn load_logo(config: &config::Config) -> Result<Logo, String> {
+    let logo = match &config.logo_file {
+        Some(path) => Logo::load(path).map_err(|err| format!("logo_file: {}", err))?,
+        None => Logo::default(),
+    };
+    let Some(path) = &config.logo_mask else {
+        return Ok(logo);
+    };
+    let mask = std::fs::read_to_string(path).map_err(|err| format!("logo_mask: {}: {}", path.display(), err))?;
+    return logo.with_mask(&mask).map_err(|err| format!("logo_mask: {}: {}", path.display(), err));
+}
+
+/// `row` in its row color, with characters the logo mask picks out in their
+/// mask color instead.
+fn logo_row(row: &str, color: Option<&String>, mask: Option<&[u8]>, mask_colors: &[String]) -> String {
+    let mask_color = |index: usize| {
+        let number = *mask?.get(index)?;
+        return mask_colors.get((number as usize).checked_sub(1)?);
+    };
+    if mask_colors.is_empty() || mask.is_none() {
+        return match color {
+            Some(color) => format!("{}{}{}", color, row, color::RESET),
+            None => row.to_string(),
+        };
+    }
+    let base = color.map_or(color::RESET, String::as_str);
+    let mut output = String::from(base);
+    let mut current = None;
+    for (index, c) in row.chars().enumerate() {
+        let wanted = mask_color(index);
+        if wanted != current {
+            output.push_str(wanted.map_or(base, String::as_str));
+            current = wanted;
+        }
+        output.push(c);
+    }
+    output.push_str(color::RESET);
+    return output;
+}
+
+/// `line` with the label (up to `label_end`) and the value in their own
+/// colors. Values that color part of themselves (usage thresholds) end
+/// with a reset, after which the value color is picked up again.
+fn color_field_line(line: &str, label_end: usize, label_color: Option<&str>, value_color: Option<&str>) -> String {
+    let (label, value) = line.split_at(label_end);
+    let label = match label_color {
+        Some(color) => format!("{}{}{}", color, label, color::RESET),
+        None => label.to_string(),
+    };
+    let value = match value_color {
+        Some(color) => format!("{}{}{}", color, value.replace(color::RESET, &format!("{}{}", color::RESET, color)), color::RESET),
+        None => value.to_string(),
+    };
+    return label + &value;
+}
+
+/// `--theme auto` falls back to the dark palette when the background can't
+/// be determined.
+fn resolve_palette(theme: Theme) -> &'static Palette {
+    let light = match theme {
+        Theme::Dark => false,
+        Theme::Light => true,
+        Theme::Auto => color::background_is_light().unwrap_or(false),
+    };
+    return if light { &color::LIGHT_PALETTE } else { &color::DARK_PALETTE };
+}
+
+/// The on-disk cache of slow, static fields, with `cache = true` or
+/// `--cache`. Loaded once per process and kept up to date in memory.
+fn load_field_cache(options: &cli::Options, config: &config::Config) -> Option<FieldCache> {
+    return (options.cache || config.cache).then(|| FieldCache::load(config.cache_ttl, System::boot_time()));
+}
+
+/// The `--theme` palette when the output is colored. Resolved once per
+/// process, since `--theme auto` queries the terminal.
+fn theme_palette(options: &cli::Options) -> Option<&'static Palette> {
+    let color = options.format == OutputFormat::Text && color::color_enabled();
+    return options.theme.filter(|_| color).map(resolve_palette);
+}
+
+/// Escape prefixes for the logo rows: the OS's brand colors with
+/// `--distro-colors`, else the configured gradient. `palette` is the
+/// already resolved `--theme`.
+fn resolve_logo_colors(options: &cli::Options, config: &config::Config, logo: &Logo, os: &str, palette: Option<&Palette>) -> Vec<String> {
+    if options.distro_colors {
+        let accent = palette.unwrap_or(&color::DARK_PALETTE).label;
+        return color::distro_logo_colors(os, accent, logo.height());
+    }
+    return color::logo_row_colors(config.logo_gradient(), logo.height());
+}
+
+/// `--logo-only`: just the (colored) logo, without its trailing padding.
+fn render_logo(logo: &Logo, colors: &[String], mask_colors: &[String]) -> String {
+    let mut output = String::new();
+    for (idx, row) in logo.rows().iter().enumerate() {
+        output.push_str(&logo_row(row.trim_end(), colors.get(idx), logo.mask_row(idx), mask_colors));
+        output.push('\n');
+    }
+    return output;
+}
+
+/// `header_separator` repeated to `width` columns, never past it when the
+/// character is double-width. `--ascii-only` keeps `-`.
+fn header_underline(width: usize, opts: &RenderOptions) -> String {
+    let separator = match &opts.header_separator {
+        Some(separator) if !opts.ascii_only => separator.as_str(),
+        _ => "-",
+    };
+    return separator.repeat(width / separator.width().max(1));
+}
+
+/// Builds the whole logo + info block so it can be written in one go.
+fn render(output_info: &OutputInfo, opts: &RenderOptions) -> String {
+    let (fields, untruncated) = build_fields(output_info, opts);
+
+    let label_width = resolve_label_width(&fields, opts.label_width);
+    let mut output_info_vec = vec![];
+    // Byte length of each line's `Label:`, colored after truncation.
+    let mut label_ends = vec![];
+    let header_lines = match opts.header_position {
+        HeaderPosition::None => vec![],
+        _ => {
+            let header = header(output_info, opts);
+            let underline = header_underline(header.width(), opts);
+            vec![header, underline]
+        }
+    };
+    if opts.header_position == HeaderPosition::Top {
+        output_info_vec.extend(header_lines.iter().cloned());
+        label_ends.extend([0, 0]);
+    }
+    let untruncated = untruncated.map(|idx| idx + output_info_vec.len());
+    for (label, value) in fields {
+        label_ends.push(label.len() + 1);
+        output_info_vec.push(format!("{:<width$}{}", format!("{}:", label), value, width = label_width));
+    }
+    if opts.shows(Field::Extra) {
+        output_info_vec.extend(output_info.extra_lines.iter().cloned());
+    }
+    if opts.header_position == HeaderPosition::Bottom {
+        label_ends.resize(output_info_vec.len(), 0);
+        output_info_vec.extend(header_lines.iter().cloned());
+        label_ends.extend([0, 0]);
+    }
+    let border = border_chars(opts.border, opts.ascii_only);
+    let border_width = if border.is_some() { 4 } else { 0 };
+    let info_width = opts.width.map(|width| width.saturating_sub(opts.logo.width() + border_width));
+    if let Some(info_width) = info_width {
+        for (idx, line) in output_info_vec.iter_mut().enumerate() {
+            if Some(idx) != untruncated {
+                *line = truncate_to_width(line, info_width, opts.glyphs().ellipsis);
+            }
+        }
+    }
+    // Colored after truncation, which counts escape codes as text.
+    if let Some(color) = &opts.header_separator_color {
+        let underline = match opts.header_position {
+            HeaderPosition::Top => Some(1),
+            HeaderPosition::Bottom => output_info_vec.len().checked_sub(1),
+            HeaderPosition::None => None,
+        };
+        if let Some(line) = underline.and_then(|idx| output_info_vec.get_mut(idx)) {
+            *line = format!("{}{}{}", color, line, color::RESET);
+        }
+    }
+    if opts.label_color.is_some() || opts.value_color.is_some() {
+        for (line, &end) in output_info_vec.iter_mut().zip(&label_ends) {
+            if end > 0 && line.is_char_boundary(end) && line.len() >= end {
+                *line = color_field_line(line, end, opts.label_color.as_deref(), opts.value_color.as_deref());
+            }
+        }
+    }
+    if opts.columns > 1 {
+        let mut field_lines = output_info_vec.split_off(if opts.header_position == HeaderPosition::Top { 2 } else { 0 });
+        let footer = field_lines.split_off(field_lines.len() - if opts.header_position == HeaderPosition::Bottom { 2 } else { 0 });
+        output_info_vec.extend(arrange_columns(field_lines, opts.columns, info_width));
+        output_info_vec.extend(footer);
+    }
+    // Each row with its display width, which escape codes don't count towards.
+    let rows: Vec<(String, usize)> = (0..output_info_vec.len().max(opts.logo.height()))
+        .map(|idx| {
+            let logo = match opts.logo.rows().get(idx) {
+                Some(row) => logo_row(row, opts.logo_colors.get(idx), opts.logo.mask_row(idx), &opts.logo_mask_colors),
+                None => " ".repeat(opts.logo.width()),
+            };
+            let info = output_info_vec.get(idx).map(String::as_str).unwrap_or("");
+            (format!("{}{}", logo, info), opts.logo.width() + color::strip_ansi(info).width())
+        })
+        .collect();
+    let mut output = String::new();
+    if opts.padding {
+        output.push('\n');
+    }
+    match border {
+        Some([top_left, top_right, bottom_left, bottom_right, horizontal, vertical]) => {
+            let inner_width = rows.iter().map(|(_, width)| *width).max().unwrap_or(0) + 2;
+            output.push_str(&format!("{}{}{}\n", top_left, horizontal.repeat(inner_width), top_right));
+            for (row, width) in &rows {
+                let pad = " ".repeat(inner_width - 2 - width);
+                output.push_str(&format!("{} {}{} {}\n", vertical, row, pad, vertical));
+            }
+            output.push_str(&format!("{}{}{}\n", bottom_left, horizontal.repeat(inner_width), bottom_right));
+        }
+        None => {
+            for (row, _) in &rows {
+                output.push_str(row);
+                output.push('\n');
+            }
+        }
+    }
+    if opts.padding {
+        output.push('\n');
+    }
+    return output;
+}
+
+/// Spaces between `--columns` columns.
+const COLUMN_GAP: usize = 3;
+
+/// `--columns`: spreads `lines` over up to `columns` columns, filled top to
+/// bottom and padded to their widest line. Falls back to fewer columns (down
+/// to one) when they wouldn't fit in `max_width`.
+fn arrange_columns(lines: Vec<String>, columns: usize, max_width: Option<usize>) -> Vec<String> {
+    for count in (2..=columns.min(lines.len())).rev() {
+        let chunks: Vec<&[String]> = lines.chunks(lines.len().div_ceil(count)).collect();
+        let widths: Vec<usize> = chunks
+            .iter()
+            .map(|chunk| chunk.iter().map(|line| color::strip_ansi(line).width()).max().unwrap_or(0))
+            .collect();
+        let total = widths.iter().sum::<usize>() + COLUMN_GAP * (chunks.len() - 1);
+        if max_width.is_some_and(|max_width| total > max_width) {
+            continue;
+        }
+        return (0..chunks[0].len())
+            .map(|row| {
+                let cells: Vec<&String> = chunks.iter().filter_map(|chunk| chunk.get(row)).collect();
+                let mut line = String::new();
+                for (column, cell) in cells.iter().enumerate() {
+                    line.push_str(cell);
+                    if column + 1 < cells.len() {
+                        line.push_str(&" ".repeat(widths[column] - color::strip_ansi(cell).width() + COLUMN_GAP));
+                    }
+                }
+                return line;
+            })
+            .collect();
+    }
+    return lines;
+}
+
+/// Machine-readable output: no logo, no padding, always newline-terminated.
+fn render_json(output_info: &OutputInfo, compact: bool) -> String {
+    let mut json = if compact {
+        serde_json::to_string(output_info)
+    } else {
+        serde_json::to_string_pretty(output_info)
+    }
+    .expect("OutputInfo always serializes");
+    json.push('\n');
+    return json;
+}
+
+/// A heading and a two-column table for pasting into issue trackers. The
+/// logo is left out.
+fn render_markdown(output_info: &OutputInfo, opts: &RenderOptions) -> String {
+    let escape = |text: &str| text.replace('|', "\\|");
+    let heading = format!("## {}\n", header(output_info, opts));
+    let mut output = if opts.header_position == HeaderPosition::Top { format!("{}\n", heading) } else { String::new() };
+    output.push_str("| Field | Value |\n| --- | --- |\n");
+    for (label, value) in table_rows(output_info, opts) {
+        output.push_str(&format!("| {} | {} |\n", escape(&label), escape(&value)));
+    }
+    if opts.header_position == HeaderPosition::Bottom {
+        output.push_str(&format!("\n{}", heading));
+    }
+    return output;
+}
+
+/// The built-in fields followed by the extra lines, split back into label
+/// and value, for the table-shaped formats.
+fn table_rows(output_info: &OutputInfo, opts: &RenderOptions) -> Vec<(String, String)> {
+    let extra_fields = output_info.extra_lines.iter().map(|line| match line.split_once(": ") {
+        Some((label, value)) => (label.to_string(), value.to_string()),
+        None => (String::new(), line.clone()),
+    });
+    let extra_fields = extra_fields.filter(|_| opts.shows(Field::Extra));
+    return build_fields(output_info, opts).0.into_iter().chain(extra_fields).collect();
+}
+
+/// `--format table`: the header, then `Field` and `Value` columns padded to
+/// the widest entry. No logo, so it suits wide terminals and screenshots.
+fn render_table(output_info: &OutputInfo, opts: &RenderOptions) -> String {
+    let rows = table_rows(output_info, opts);
+    let label_width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0).max("Field".len());
+    let value_width = rows.iter().map(|(_, value)| value.width()).max().unwrap_or(0).max("Value".len());
+    let pad = |text: &str| format!("{}{}", text, " ".repeat(label_width - text.width()));
+    let mut output = if opts.header_position == HeaderPosition::Top { format!("{}\n\n", header(output_info, opts)) } else { String::new() };
+    output.push_str(&format!("{}  Value\n", pad("Field")));
+    output.push_str(&format!("{}  {}\n", "-".repeat(label_width), "-".repeat(value_width)));
+    for (label, value) in rows {
+        output.push_str(&format!("{}  {}\n", pad(&label), value));
+    }
+    if opts.header_position == HeaderPosition::Bottom {
+        output.push_str(&format!("\n{}\n", header(output_info, opts)));
+    }
+    return output;
+}
+
+/// `value` in single quotes, safe to `eval` in any POSIX shell.
+fn shell_quote(value: &str) -> String {
+    return format!("'{}'", value.replace('\'', "'\\''"));
+}
+
+/// `FETCH_` plus `label` in upper case with anything but letters and digits
+/// turned into `_`, e.g. `CPU Temp` becomes `FETCH_CPU_TEMP`.
+fn env_var_name(label: &str) -> String {
+    let mut name = String::from("FETCH");
+    for word in label.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        name.push('_');
+        name.push_str(&word.to_ascii_uppercase());
+    }
+    return name;
+}
+
+/// `--format env`: one `FETCH_LABEL='value'` line per field, for `eval` or
+/// `source`. Repeated labels get `_2`, `_3` and so on. No logo.
+fn render_env(output_info: &OutputInfo, opts: &RenderOptions) -> String {
+    let mut rows = table_rows(output_info, opts);
+    if opts.header_position != HeaderPosition::None {
+        rows.insert(0, (String::from("Header"), header(output_info, opts)));
+    }
+    let mut seen = HashMap::<String, usize>::new();
+    let mut output = String::new();
+    for (label, value) in rows {
+        let name = env_var_name(if label.is_empty() { "Extra" } else { &label });
+        let count = seen.entry(name.clone()).or_insert(0);
+        *count += 1;
+        let name = if *count > 1 { format!("{}_{}", name, count) } else { name };
+        output.push_str(&format!("{}={}\n", name, shell_quote(&value)));
+    }
+    return output;
+}
+
+/// A line after the output. JSON and env output stay parseable; footers go
+/// to stderr instead.
+fn push_footer(output: &mut String, line: &str, format: OutputFormat) {
+    if matches!(format, OutputFormat::Json | OutputFormat::Env) {
+        eprintln!("{}", line);
+    } else {
+        output.push_str(line);
+        output.push('\n');
+    }
+}
+
+/// The versions of the data-source crates this binary was built against,
+/// read from `Cargo.lock` by `build.rs`.
+fn dependency_versions() -> String {
+    return format!(
+        "Built with sysinfo {}, wgpu {}, whoami {}",
+        env!("DEP_VERSION_SYSINFO"),
+        env!("DEP_VERSION_WGPU"),
+        env!("DEP_VERSION_WHOAMI"),
+    );
+}
+
+fn print_all_info(output: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(output.as_bytes())?;
+    return stdout.flush();
+}
+
+/// Waits for an optional collector, recording in `errors` (when
+/// `--include-errors` asked for it) why it produced nothing.
+fn wait_for<T>(
+    pending: Option<Pending<T>>,
+    field: Field,
+    timeout: std::time::Duration,
+    errors: &mut Option<BTreeMap<&'static str, String>>,
+) -> Option<T> {
+    return wait_for_key(pending, field.name(), timeout, errors);
+}
+
+/// `wait_for` for a collector that only adds to a field, so its error gets
+/// a key of its own rather than reading as the whole field missing.
+fn wait_for_key<T>(
+    pending: Option<Pending<T>>,
+    key: &'static str,
+    timeout: std::time::Duration,
+    errors: &mut Option<BTreeMap<&'static str, String>>,
+) -> Option<T> {
+    return match pending?.wait_result(timeout) {
+        Ok(value) => Some(value),
+        Err(reason) => {
+            if let Some(errors) = errors {
+                errors.insert(key, reason);
+            }
+            None
+        }
+    };
+}
+
+/// Collects every field once and renders it in `--format`, plus each
+/// `--emit` format (without colors) in order. Also returns how long the
+/// collection took, for `--repeat`.
+fn collect_and_render(
+    options: &cli::Options,
+    config: &config::Config,
+    logo: &Logo,
+    palette: Option<&'static Palette>,
+    mut cache: Option<&mut FieldCache>,
+    mut history: Option<&mut TemperatureHistory>,
+) -> (String, Vec<String>, std::time::Duration) {
+    let started = std::time::Instant::now();
+    // Collectors that can block on drivers, sensors or child processes run
+    // on their own threads while the CPU is sampled, and are skipped with a
+    // warning if they take longer than the timeout.
+    let timeout = std::time::Duration::from_millis(config.collector_timeout_ms.unwrap_or(DEFAULT_COLLECTOR_TIMEOUT_MS));
+    // Slow, static fields are taken from the on-disk cache while it's fresh.
+    if let Some(cache) = cache.as_deref_mut() {
+        cache.start_run();
+    }
+    let cached_chassis: Option<String> = cache.as_deref().filter(|_| !options.fast).and_then(|cache| cache.get(Field::Chassis));
+    let chassis = (!options.fast && cached_chassis.is_none()).then(|| collector::spawn("chassis", get_chassis_type));
+    let updated = options.show_updated.then(|| collector::spawn("update info", get_update_info));
+    // Package managers often take longer than the timeout; the query then
+    // finishes after the output is printed and fills the cache for the next run.
+    let pending_updates = options.show_updates.then(|| collector::spawn_finished_at_exit("pending updates", get_pending_updates));
+    let failed_units = options.show_failed_units.then(|| collector::spawn("failed units", get_failed_unit_count));
+    let battery = collector::spawn("battery", get_battery);
+    // A stale network mount can block the filesystem stats indefinitely.
+    let disks = {
+        let include = config.disk_include.clone();
+        collector::spawn("disks", move || get_disks(&include))
+    };
+    let root_inodes = options.show_inodes.then(|| collector::spawn("root inodes", || get_inode_counts("/")));
+    let toolchains: Vec<_> = if options.show_toolchains {
+        let binaries = config.toolchains.clone().unwrap_or_else(|| toolchain::DEFAULT_TOOLCHAINS.map(String::from).to_vec());
+        binaries
+            .into_iter()
+            .map(|binary| collector::spawn(&format!("{} toolchain probe", binary), move || toolchain::probe(&binary)))
+            .collect()
+    } else {
+        vec![]
+    };
+    let repo = (options.show_git || config.show_git).then(|| collector::spawn("git repository", git::get_repo_info));
+    // Measured alongside the usage sampling rather than after it.
+    let cpu_breakdown = (options.cpu_breakdown && !options.fast && !options.no_refresh).then(|| {
+        let samples = cpu_sample_count(options.cpu_samples.unwrap_or(DEFAULT_CPU_SAMPLES));
+        collector::spawn("CPU breakdown", move || get_cpu_breakdown(samples))
+    });
+    let governor = options.show_governor.then(|| collector::spawn("CPU governor", get_cpu_governor));
+    let printer = options.show_printer.then(|| collector::spawn("default printer", get_default_printer));
+    let wifi = options.show_wifi.then(|| collector::spawn("Wi-Fi", get_wifi));
+    let power = options.show_power.then(|| collector::spawn("power source", get_power_source));
+    let encryption = options.show_encryption.then(|| collector::spawn("disk encryption", get_encryption));
+    let trim = options.show_storage_health.then(|| collector::spawn("fstrim timer", get_fstrim_status));
+    let top_processes = options.show_top.then(|| collector::spawn("top processes", get_top_processes));
+    let session = options.show_session.then(|| collector::spawn("session", get_session));
+    let users = options.show_users.then(|| collector::spawn("logged-in users", get_logged_in_users));
+    let gpu_temperature = options.show_gpu_temp.then(|| collector::spawn("GPU temperature", get_gpu_temperature));
+    let cpu_temperature = (!options.fast).then(|| {
+        let sensor = config.cpu_temp_sensor.clone();
+        collector::spawn("CPU temperature", move || get_cpu_temperature(sensor.as_deref()))
+    });
+    // Live stats and API versions aren't worth caching, and would go stale.
+    let gpu_cacheable = !options.fast && !options.gpu_live && !options.show_api_versions;
+    // `-v` and `-vv` report on the enumeration, so they don't share its result.
+    let cached_gpu: Option<(Vec<GpuInfo>, usize)> =
+        cache.as_deref().filter(|_| gpu_cacheable).and_then(|cache| cache.get_variant(Field::Gpu, options.verbose));
+    let gpu_from_cache = cached_gpu.is_some();
+    let gpu = (!options.fast && !gpu_from_cache).then(|| {
+        let (sort, live, verbose) = (options.sort_gpus_by, options.gpu_live, options.verbose);
+        let api_versions = options.show_api_versions || options.gpu_live;
+        collector::spawn("GPU enumeration", move || get_gpu_info(sort, live, api_versions, verbose > 1))
+    });
+    let mut extra_collectors = get_env_collectors(&options.show_env);
+    for extra in &config.extra {
+        extra_collectors.push(Box::new(CommandCollector::new(extra)));
+    }
+    let extra_lines = collector::spawn_lines(extra_collectors);
+
+    // `--fast` skips the CPU usage sampling interval and only loads what the
+    // remaining fields need, instead of every process on the system.
+    let (sys, cpu_usage) = if options.fast {
+        let sys = System::new_with_specifics(
+            RefreshKind::nothing()
+                .with_cpu(CpuRefreshKind::nothing().with_frequency())
+                .with_memory(MemoryRefreshKind::everything()),
+        );
+        (sys, None)
+    } else if options.no_refresh {
+        // A single refresh has nothing to diff against, so any usage figure
+        // would be meaningless; it's reported as unavailable instead.
+        (System::new_all(), None)
+    } else {
+        let mut sys = System::new_all();
+        let samples = cpu_sample_count(options.cpu_samples.unwrap_or(DEFAULT_CPU_SAMPLES));
+        let usage = sample_cpu_usage(&mut sys, config.cpu_usage_source, samples);
+        (sys, Some(usage))
+    };
+
+    let memory_source = if options.memory_from_proc { MemorySource::ProcMeminfo } else { config.memory_source };
+    let (memory_used, memory_total, memory_cgroup_limited) = get_memory_usage(&sys, config.memory_basis, memory_source);
+    let mut errors = options.include_errors.then(BTreeMap::new);
+    let gpu = match cached_gpu {
+        Some((mut gpus, raw_adapters)) => {
+            gpus.sort_by(|x, y| compare_gpus(x, y, options.sort_gpus_by));
+            Some((gpus, raw_adapters))
+        }
+        None => wait_for(gpu, Field::Gpu, timeout, &mut errors),
+    };
+    // An empty list is more likely a driver that wasn't up yet than no GPU.
+    if let (Some(cache), Some(gpus)) = (cache.as_deref_mut().filter(|_| gpu_cacheable && !gpu_from_cache), gpu.as_ref().filter(|(gpus, _)| !gpus.is_empty())) {
+        cache.put_variant(Field::Gpu, options.verbose, gpus);
+    }
+    let chassis = cached_chassis.or_else(|| {
+        let chassis = wait_for(chassis, Field::Chassis, timeout, &mut errors).flatten();
+        if let (Some(cache), Some(chassis)) = (cache.as_deref_mut(), &chassis) {
+            cache.put(Field::Chassis, chassis);
+        }
+        return chassis;
+    });
+    let (gpu, raw_gpu_adapters, gpu_unavailable) = match gpu {
+        // No adapter at all, not even a software one, means the graphics
+        // libraries (e.g. the Vulkan loader) are missing, as on minimal
+        // containers. That's worth a note with `-v`, but it isn't an error.
+        Some((gpus, 0)) => (gpus, Some(0).filter(|_| options.verbose > 0), Some(String::from("no graphics backend"))),
+        Some((gpus, raw_adapters)) => (gpus, Some(raw_adapters).filter(|_| options.verbose > 0), None),
+        None => (vec![], None, None),
+    };
+    let mut output_info = OutputInfo {
+        timestamp: options.watch.map(|_| chrono::Local::now().to_rfc3339()),
+        username: get_username(),
+        hostname: get_hostname(options.verbose > 0),
+        os: options
+            .os_name
+            .clone()
+            .or_else(|| config.os_name.clone())
+            .unwrap_or_else(|| cache::cached(cache.as_deref_mut(), Field::Os, get_os_name)),
+        model: cache::cached(cache.as_deref_mut(), Field::Model, get_model),
+        serial_number: cache::cached(cache.as_deref_mut(), Field::Serial, get_serial_number),
+        chassis,
+        firmware: if options.show_firmware { get_firmware() } else { None },
+        kernel: cache::cached(cache.as_deref_mut(), Field::Kernel, kernel),
+        kernel_build_date: if options.kernel_date { get_kernel_build_date() } else { None },
+        newer_kernel: if options.check_kernel { get_newer_installed_kernel() } else { None },
+        kernel_cmdline: if options.show_cmdline || options.full_cmdline { get_kernel_cmdline() } else { None },
+        updated: wait_for(updated, Field::Updated, timeout, &mut errors).flatten(),
+        pending_updates: wait_for(pending_updates, Field::Updates, timeout, &mut errors).flatten(),
+        failed_units: wait_for(failed_units, Field::FailedUnits, timeout, &mut errors).flatten(),
+        thread_counts: if options.show_threads { get_thread_counts() } else { None },
+        fd_counts: if options.show_fds { get_fd_counts() } else { None },
+        entropy_bits: if options.show_entropy { get_entropy_bits() } else { None },
+        parent_process: if options.show_pid { get_parent_process() } else { None },
+        session: wait_for(session, Field::Session, timeout, &mut errors).flatten(),
+        users: wait_for(users, Field::Users, timeout, &mut errors).flatten(),
+        uptime: Uptime(get_uptime()),
+        cpu: get_cpu_info(&sys, cpu_usage.as_deref()),
+        cpu_breakdown: wait_for_key(cpu_breakdown, "cpu-breakdown", timeout, &mut errors).flatten(),
+        core_counts: get_core_counts(),
+        hybrid_cores: get_hybrid_cores(),
+        cpu_limit: get_cgroup_cpu_limit(),
+        cpu_affinity: get_cpu_affinity(),
+        hypervisor: get_hypervisor(),
+        cpu_temperature: wait_for(cpu_temperature, Field::CpuTemp, timeout, &mut errors).flatten(),
+        cpu_caches: if options.show_cache { get_cpu_caches() } else { vec![] },
+        cpu_topology: if options.show_topology { get_cpu_topology() } else { None },
+        virtualization: if options.show_virt { get_virtualization() } else { None },
+        governor: wait_for(governor, Field::Governor, timeout, &mut errors).flatten(),
+        gpu,
+        gpu_unavailable,
+        raw_gpu_adapters,
+        gpu_temperature: wait_for(gpu_temperature, Field::GpuTemp, timeout, &mut errors).flatten(),
+        displays: if options.fast { vec![] } else { get_resolution() },
+        audio: get_audio_server(&sys),
+        printer: wait_for(printer, Field::Printer, timeout, &mut errors).flatten(),
+        top_processes: wait_for(top_processes, Field::Top, timeout, &mut errors).flatten(),
+        ipv4: if options.show_ip && options.ip.v4() { get_primary_ipv4() } else { None },
+        ipv6: if options.show_ip && options.ip.v6() { get_primary_ipv6() } else { None },
+        gateway: if options.show_network { get_default_gateway() } else { None },
+        wifi: wait_for(wifi, Field::Wifi, timeout, &mut errors).flatten(),
+        memory_used_mb: bytes_to_mb(memory_used),
+        memory_total_mb: bytes_to_mb(memory_total),
+        swap_used_mb: bytes_to_mb(get_used_swap(&sys)),
+        swap_total_mb: bytes_to_mb(get_total_swap(&sys)),
+        memory_cgroup_limited,
+        memory_pressure: if options.show_pressure { get_memory_pressure(options.verbose > 1) } else { None },
+        disks: wait_for(Some(disks), Field::Disk, timeout, &mut errors).unwrap_or_default(),
+        root_inodes: wait_for(root_inodes, Field::Inodes, timeout, &mut errors).flatten(),
+        encryption: wait_for(encryption, Field::Encryption, timeout, &mut errors).flatten(),
+        trim: wait_for(trim, Field::Trim, timeout, &mut errors).flatten(),
+        battery: wait_for(Some(battery), Field::Battery, timeout, &mut errors).flatten(),
+        power: wait_for(power, Field::Power, timeout, &mut errors).flatten(),
+        toolchains: toolchains.into_iter().filter_map(|pending| pending.wait(timeout).flatten()).collect(),
+        repo: wait_for(repo, Field::Repo, timeout, &mut errors).flatten(),
+        extra_lines: collector::wait_lines(extra_lines, timeout),
+        errors,
+    };
+    if let Some(cache) = cache {
+        cache.save();
+    }
+    let collection_time = started.elapsed();
+    if options.deterministic {
+        freeze_volatile_fields(&mut output_info);
+    }
+    if let Some(history) = history.as_deref_mut() {
+        history.record(output_info.cpu_temperature.as_ref().map(|temperature| temperature.celsius as f64), output_info.gpu_temperature);
+    }
+
+    let color = options.format == OutputFormat::Text && color::color_enabled();
+    let render_options = RenderOptions {
+        padding: !options.no_padding,
+        header_position: if options.no_header {
+            HeaderPosition::None
+        } else {
+            options.header_position.or(config.header_position).unwrap_or_default()
+        },
+        header_separator: config.header_separator.clone(),
+        header_separator_color: config.header_separator_color().filter(|_| color).map(color::Rgb::fg),
+        fqdn: options.fqdn,
+        keep_domain: options.keep_domain,
+        width: resolve_width(options.width),
+        label_width: config.label_width,
+        gpu_list: options.gpu_list,
+        gpu_index_base: options.gpu_index_base.or(config.gpu_index_base).unwrap_or(0),
+        max_gpus: options.max_gpus,
+        ascii_only: options.ascii_only || detect_ascii_only(),
+        verbose: options.verbose,
+        memory_display: config.memory_display,
+        gpu_name_style: config.gpu_name_style,
+        frequency_unit: options.frequency_unit.or(config.frequency_unit).unwrap_or_default(),
+        frequency_decimals: config.frequency_decimals.unwrap_or(DEFAULT_FREQUENCY_DECIMALS),
+        border: options.border,
+        fields: if options.fields.is_empty() { options.fields_preset.clone() } else { options.fields.clone() },
+        show_empty: options.show_empty,
+        ip: options.show_ip.then_some(options.ip),
+        digit_grouping: if options.group_digits { Some(detect_number_locale()) } else { None },
+        uptime_since: options.uptime_since || config.uptime_since,
+        full_cmdline: options.full_cmdline,
+        uptime_words: (options.locale_uptime || options.lang.is_some()).then(|| resolve_uptime_words(options.lang.as_deref())),
+        logo_colors: resolve_logo_colors(options, config, logo, &output_info.os, palette),
+        logo_mask_colors: color::logo_mask_colors(config.logo_mask_colors()),
+        logo: logo.clone(),
+        color,
+        cpu_temp_history: history.as_deref().map(|history| history.cpu.iter().copied().collect()).unwrap_or_default(),
+        gpu_temp_history: history.as_deref().map(|history| history.gpu.iter().copied().collect()).unwrap_or_default(),
+        palette,
+        label_color: config.label_color().map(color::Rgb::fg).or(palette.map(|palette| palette.label.to_string())).filter(|_| color),
+        value_color: config.value_color().map(color::Rgb::fg).or(palette.map(|palette| palette.value.to_string())).filter(|_| color),
+        thresholds: config.thresholds,
+        units: config.units,
+        cpu_combined: options.cpu_combined,
+        memory_swap_combined: options.memory_swap_combined || config.memory_swap_combined,
+        show_swap: options.show_swap || options.memory_swap_combined || config.memory_swap_combined,
+        columns: options.columns.unwrap_or(1),
+    };
+    // `--watch` streams JSON as one object per line.
+    let mut output = render_format(options.format, &output_info, &render_options, options.json_compact || options.watch.is_some());
+    if options.qr {
+        match render_qr(&output_info, render_options.ascii_only) {
+            Some(code) => push_footer(&mut output, &code, options.format),
+            None => eprintln!("ashwin-fetch: too much to fit in a QR code"),
+        }
+    }
+    let emitted = options
+        .emit
+        .iter()
+        .map(|(format, _)| color::strip_ansi(&render_format(*format, &output_info, &render_options, options.json_compact)))
+        .collect();
+    return (output, emitted, collection_time);
+}
+
+/// `--qr`: key fields as compact JSON in a QR code. The ASCII version draws
+/// two characters per module, so it only gets the OS, CPU and memory to keep
+/// it narrow.
+fn render_qr(output_info: &OutputInfo, ascii_only: bool) -> Option<String> {
+    let mut cpu: Vec<&str> = output_info.cpu.keys().copied().collect();
+    cpu.sort();
+    let summary = QrSummary {
+        os: &output_info.os,
+        kernel: output_info.kernel.as_deref().filter(|_| !ascii_only),
+        cpu,
+        gpu: if ascii_only { vec![] } else { output_info.gpu.iter().map(|gpu| gpu.gpu_name.as_str()).collect() },
+        memory_mb: output_info.memory_total_mb,
+        uptime_seconds: (!ascii_only).then_some(output_info.uptime.0),
+    };
+    let code = qrcode::QrCode::with_error_correction_level(serde_json::to_string(&summary).ok()?, qrcode::EcLevel::L).ok()?;
+    // Light modules are the drawn ones, like `qrencode -t UTF8`, so the code
+    // reads right on the usual light-on-dark terminal.
+    return Some(if ascii_only {
+        code.render::<&str>().dark_color("  ").light_color("##").build()
+    } else {
+        use qrcode::render::unicode::Dense1x2;
+        code.render::<Dense1x2>().dark_color(Dense1x2::Light).light_color(Dense1x2::Dark).build()
+    });
+}
+
+fn render_format(format: OutputFormat, output_info: &OutputInfo, opts: &RenderOptions, json_compact: bool) -> String {
+    return match format {
+        OutputFormat::Text => render(output_info, opts),
+        OutputFormat::Json => render_json(output_info, json_compact),
+        OutputFormat::Markdown => render_markdown(output_info, opts),
+        OutputFormat::Table => render_table(output_info, opts),
+        OutputFormat::Env => render_env(output_info, opts),
+    };
+}
+
+/// Escapes that turn the screen showing `previous` into `current` by
+/// rewriting only the lines that differ, for `--watch-changes`. `None` when
+/// the line count changed, or the output doesn't fit in the terminal's
+/// `rows` (it scrolled, so the rows no longer line up), and the screen has
+/// to be redrawn.
+fn changed_lines(previous: &str, current: &str, rows: Option<usize>) -> Option<String> {
+    let (previous, current): (Vec<&str>, Vec<&str>) = (previous.lines().collect(), current.lines().collect());
+    if previous.len() != current.len() || rows.is_some_and(|rows| current.len() >= rows) {
+        return None;
+    }
+    let mut update = String::new();
+    for (row, (old, new)) in previous.iter().zip(&current).enumerate() {
+        if old != new {
+            // Move to the row, write it, and clear whatever the old line had past its end.
+            update.push_str(&format!("\x1b[{};1H{}\x1b[K", row + 1, new));
+        }
+    }
+    update.push_str(&format!("\x1b[{};1H", current.len() + 1));
+    return Some(update);
+}
+
+/// Recent CPU and GPU temperatures, kept across `--watch` ticks for the
+/// trend sparklines.
+struct TemperatureHistory {
+    length: usize,
+    cpu: VecDeque<f64>,
+    gpu: VecDeque<f64>,
+}
+
+impl Debug for TemperatureHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TemperatureHistory")
+            .field("length", &self.length)
+            .field("cpu", &self.cpu)
+            .field("gpu", &self.gpu)
+            .finish()
+    }
+}
+
+impl TemperatureHistory {
+    fn new(length: usize) -> TemperatureHistory {
+        return TemperatureHistory { length, cpu: VecDeque::new(), gpu: VecDeque::new() };
+    }
+
+    /// Adds this tick's readings, dropping the oldest past `length`. A
+    /// sensor that didn't answer this tick keeps its earlier samples.
+    fn record(&mut self, cpu: Option<f64>, gpu: Option<f64>) {
+        for (samples, reading) in [(&mut self.cpu, cpu), (&mut self.gpu, gpu)] {
+            samples.extend(reading);
+            while samples.len() > self.length {
+                samples.pop_front();
+            }
+        }
+    }
+}
+
+/// `--watch`: collects and prints every `interval` until interrupted or
+/// the reader goes away. Text redraws the screen (or just the changed lines
+/// with `--watch-changes`, unless the terminal was resized); JSON appends a
+/// line.
+fn watch(options: &cli::Options, config: &config::Config, logo: &Logo, interval: std::time::Duration) -> ExitCode {
+    let mut previous: Option<(String, Option<(terminal_size::Width, terminal_size::Height)>)> = None;
+    let mut history = TemperatureHistory::new(config.temp_history.unwrap_or(DEFAULT_TEMP_HISTORY));
+    let palette = theme_palette(options);
+    let mut cache = load_field_cache(options, config);
+    loop {
+        let (rendered, _, _) = collect_and_render(options, config, logo, palette, cache.as_mut(), Some(&mut history));
+        let size = terminal_size::terminal_size();
+        let frame = if options.format == OutputFormat::Json {
+            rendered.clone()
+        } else {
+            let update = previous
+                .as_ref()
+                .filter(|(_, previous_size)| options.watch_changes && *previous_size == size)
+                .and_then(|(previous, _)| changed_lines(previous, &rendered, size.map(|(_, height)| height.0 as usize)));
+            update.unwrap_or_else(|| format!("\x1b[2J\x1b[H{}", rendered))
+        };
+        let frame = if options.crlf { frame.replace('\n', "\r\n") } else { frame };
+        match print_all_info(&frame) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return ExitCode::from(0),
+            Err(err) => {
+                eprintln!("ashwin-fetch: {}", err);
+                return ExitCode::from(1);
+            }
+        }
+        previous = Some((rendered, size));
+        std::thread::sleep(interval);
+    }
+}
+
+/// Binds `path`, replacing a socket file left behind by a server that's gone
+/// (connecting is refused) but not one that's still serving, or anything
+/// that isn't a socket.
+#[cfg(unix)]
+fn bind_socket(path: &str) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+
+    match UnixListener::bind(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::AddrInUse => {
+            let is_socket = std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket());
+            let refused = UnixStream::connect(path).is_err_and(|err| err.kind() == std::io::ErrorKind::ConnectionRefused);
+            if !is_socket || !refused {
+                return Err(err);
+            }
+            std::fs::remove_file(path)?;
+            return UnixListener::bind(path);
+        }
+        result => return result,
+    }
+}
+
+/// The socket `--serve` listens on, for the signal handler to remove.
+#[cfg(unix)]
+static SERVE_SOCKET: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+
+/// Removes the socket file and exits; `--serve` only stops on a signal.
+#[cfg(unix)]
+extern "C" fn remove_socket_and_exit(signal: libc::c_int) {
+    if let Some(path) = SERVE_SOCKET.get() {
+        // SAFETY: `unlink` is async-signal-safe and the path is a valid C string.
+        unsafe { libc::unlink(path.as_ptr()) };
+    }
+    // SAFETY: `_exit` is async-signal-safe and skips `atexit` handlers.
+    unsafe { libc::_exit(128 + signal) };
+}
+
+/// Removes the socket file when `serve` returns on an error.
+#[cfg(unix)]
+struct SocketFile<'a>(&'a str);
+
+#[cfg(unix)]
+impl Drop for SocketFile<'_> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.0);
+    }
+}
+
+/// Starts a thread writing the lines sent to it to `client`, beginning with
+/// `latest`, so one slow reader doesn't hold up the others. The sender
+/// fails once the client hung up or stopped reading for
+/// `SERVE_WRITE_TIMEOUT`.
+#[cfg(unix)]
+fn serve_client(mut client: std::os::unix::net::UnixStream, latest: Option<std::sync::Arc<str>>) -> Option<std::sync::mpsc::Sender<std::sync::Arc<str>>> {
+    if client.set_nonblocking(false).is_err() || client.set_write_timeout(Some(SERVE_WRITE_TIMEOUT)).is_err() {
+        return None;
+    }
+    let (sender, receiver) = std::sync::mpsc::channel::<std::sync::Arc<str>>();
+    if let Some(line) = latest {
+        let _ = sender.send(line);
+    }
+    std::thread::spawn(move || {
+        for line in receiver {
+            if client.write_all(line.as_bytes()).is_err() {
+                return;
+            }
+        }
+    });
+    return Some(sender);
+}
+
+/// `--serve`: like `--watch` with JSON, but the lines go to every client of
+/// a Unix socket, so a status bar can keep one process instead of spawning
+/// one per tick. Nothing is collected while no one is connected, and a new
+/// client gets the latest line right away. Clients that hang up or stop
+/// reading are dropped. The socket file is removed on exit.
+#[cfg(unix)]
+fn serve(options: &cli::Options, config: &config::Config, logo: &Logo, path: &str, interval: std::time::Duration) -> ExitCode {
+    let listener = match bind_socket(path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("ashwin-fetch: can't listen on {}: {}", path, err);
+            return ExitCode::from(1);
+        }
+    };
+    let _socket_file = SocketFile(path);
+    if let Ok(path) = std::ffi::CString::new(path) {
+        let _ = SERVE_SOCKET.set(path);
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+            // SAFETY: the handler only calls async-signal-safe functions.
+            unsafe { libc::signal(signal, remove_socket_and_exit as extern "C" fn(libc::c_int) as libc::sighandler_t) };
+        }
+    }
+    let mut clients: Vec<std::sync::mpsc::Sender<std::sync::Arc<str>>> = vec![];
+    let mut cache = load_field_cache(options, config);
+    let mut latest: Option<std::sync::Arc<str>> = None;
+    let mut next_tick = Instant::now();
+    loop {
+        // Block for the first client; after that, pick up newcomers between ticks.
+        if listener.set_nonblocking(!clients.is_empty()).is_err() {
+            return ExitCode::from(1);
+        }
+        while let Ok((client, _)) = listener.accept() {
+            clients.extend(serve_client(client, latest.clone()));
+            if listener.set_nonblocking(true).is_err() {
+                return ExitCode::from(1);
+            }
+        }
+        let now = Instant::now();
+        if now < next_tick {
+            std::thread::sleep((next_tick - now).min(std::time::Duration::from_millis(100)));
+            continue;
+        }
+        next_tick = now + interval;
+        // JSON only, so there's no palette to resolve.
+        let (line, _, _) = collect_and_render(options, config, logo, None, cache.as_mut(), None);
+        let line: std::sync::Arc<str> = line.into();
+        clients.retain(|client| client.send(std::sync::Arc::clone(&line)).is_ok());
+        latest = Some(line);
+    }
+}
+
+#[cfg(not(unix))]
+fn serve(_options: &cli::Options, _config: &config::Config, _logo: &Logo, _path: &str, _interval: std::time::Duration) -> ExitCode {
+    eprintln!("ashwin-fetch: --serve needs Unix domain sockets, which this platform doesn't have");
+    return ExitCode::from(1);
+}
+
+/// `min / median / max` of the `--repeat` collection times.
+fn timing_summary(timings: &[std::time::Duration]) -> String {
+    let mut sorted = timings.to_vec();
+    sorted.sort();
+    return format!(
+        "{} runs, collection min {:.1?} / median {:.1?} / max {:.1?}",
+        sorted.len(),
+        sorted[0],
+        sorted[sorted.len() / 2],
+        sorted[sorted.len() - 1]
+    );
+}
+
+fn main() -> ExitCode {
+    let mut options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("ashwin-fetch: {}\n\n{}", err, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if options.help {
+        print!("{}", cli::USAGE);
+        return ExitCode::from(0);
+    }
+
+    // Before loading the config, so a broken one can still be located.
+    if options.show_paths {
+        print!("{}", config::show_paths(options.profile.as_deref()));
+        return ExitCode::from(0);
+    }
+
+    if let Some((old, new)) = &options.compare {
+        return match compare::compare_files(old, new) {
+            Ok(diff) => {
+                print!("{}", diff);
+                ExitCode::from(0)
+            }
+            Err(err) => {
+                eprintln!("ashwin-fetch: {}", err);
+                ExitCode::from(1)
+            }
+        };
+    }
+
+    if (options.watch.is_some() || options.serve.is_some()) && !options.emit.is_empty() {
+        eprintln!("ashwin-fetch: --emit can't be combined with --watch or --serve");
+        return ExitCode::from(1);
+    }
+
+    if let Some(lang) = options.lang.as_deref().filter(|lang| uptime_words(lang).is_none()) {
+        let known: Vec<&str> = UPTIME_WORDS.iter().map(|(name, _)| *name).collect();
+        eprintln!("ashwin-fetch: unknown --lang `{}` (expected one of {})", lang, known.join(", "));
+        return ExitCode::from(1);
+    }
+
+    if options.fields_json {
+        println!("{}", serde_json::to_string_pretty(&field::registry()).unwrap());
+        return ExitCode::from(0);
+    }
+
+    if options.color_test {
+        print!("{}", color::color_test());
+        return ExitCode::from(0);
+    }
+
+    if options.show_terminal_caps {
+        print!("{}", color::terminal_caps());
+        return ExitCode::from(0);
+    }
+
+    let config = match config::load_config(options.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("ashwin-fetch: invalid config: {}", err);
+            return ExitCode::from(1);
+        }
+    };
+
+    if options.dump_config {
+        print!("{}", config::dump_config(&effective_config(&options, config)));
+        return ExitCode::from(0);
+    }
+
+    // `--fields` wins over `--fields-file`, which wins over `fields_file`.
+    let fields_file = options.fields_file.clone().map(PathBuf::from).or(config.fields_file.clone());
+    if let Some(path) = fields_file.filter(|_| options.fields.is_empty()) {
+        match field::load_field_file(&path) {
+            Ok(fields) => options.fields = fields,
+            Err(err) => {
+                eprintln!("ashwin-fetch: {}", err);
+                return ExitCode::from(1);
+            }
+        }
+    }
+    if options.fields.is_empty() {
+        for field in options.fields_preset.clone() {
+            options.opt_in(field);
+        }
+    }
+
+    let logo = match load_logo(&config) {
+        Ok(mut logo) => {
+            if let Some(scale) = options.logo_scale {
+                logo = logo.scaled(scale);
+            }
+            if let Some(gap) = config.gap {
+                logo = logo.with_gap(gap);
+            }
+            match options.logo_width {
+                Some(width) => logo.with_width(width),
+                None => logo,
+            }
+        }
+        Err(err) => {
+            eprintln!("ashwin-fetch: invalid {}", err);
+            return ExitCode::from(1);
+        }
+    };
+
+    if options.logo_only {
+        let os = options.os_name.clone().or_else(|| config.os_name.clone()).unwrap_or_else(get_os_name);
+        let colors = resolve_logo_colors(&options, &config, &logo, &os, theme_palette(&options));
+        print!("{}", render_logo(&logo, &colors, &color::logo_mask_colors(config.logo_mask_colors())));
+        return ExitCode::from(0);
+    }
+
+    if options.raw_uptime {
+        println!("{}", get_uptime());
+        return ExitCode::from(0);
+    }
+
+    if let Some(threshold) = options.uptime_gt {
+        let uptime = get_uptime();
+        let passed = uptime > threshold;
+        if options.verbose > 0 {
+            println!(
+                "uptime {} is {} {}",
+                convert_unix_to_human_string(uptime),
+                if passed { "over" } else { "not over" },
+                convert_unix_to_human_string(threshold),
+            );
+        }
+        return ExitCode::from(if passed { 0 } else { 1 });
+    }
+
+    if !sysinfo::IS_SUPPORTED_SYSTEM {
+        println!("System not supported. Aborting.");
+        return ExitCode::from(1);
+    }
+
+    if let Some(path) = options.serve.take() {
+        // One compact, timestamped object per line, as `--watch` streams them.
+        options.format = OutputFormat::Json;
+        options.json_compact = true;
+        let seconds = *options.watch.get_or_insert(DEFAULT_SERVE_INTERVAL_SECS);
+        return serve(&options, &config, &logo, &path, std::time::Duration::from_secs(seconds.max(1) as u64));
+    }
+
+    if let Some(seconds) = options.watch {
+        return watch(&options, &config, &logo, std::time::Duration::from_secs(seconds.max(1) as u64));
+    }
+
+    if options.render_image.is_some() {
+        color::force_color();
+    }
+
+    // Runs alongside collection; a cache miss waits on the network.
+    let update_check = options.check_updates.then(|| std::thread::spawn(update::update_notice));
+
+    // Only the last run is printed; `--repeat` is for timing the collectors.
+    let runs = options.repeat.unwrap_or(1).max(1);
+    let palette = theme_palette(&options);
+    let mut cache = load_field_cache(&options, &config);
+    let mut timings = vec![];
+    let mut output = String::new();
+    let mut emitted = vec![];
+    for run in 1..=runs {
+        let (rendered, emitted_formats, collection_time) = collect_and_render(&options, &config, &logo, palette, cache.as_mut(), None);
+        if options.repeat.is_some() && options.verbose > 0 {
+            eprintln!("run {}: collected in {:.1?}", run, collection_time);
+        }
+        timings.push(collection_time);
+        output = rendered;
+        emitted = emitted_formats;
+    }
+    for ((_, path), content) in options.emit.iter().zip(&emitted) {
+        if let Err(err) = std::fs::write(path, content) {
+            eprintln!("ashwin-fetch: can't write {}: {}", path, err);
+            return ExitCode::from(1);
+        }
+    }
+    if options.repeat.is_some() {
+        eprintln!("{}", timing_summary(&timings));
+    }
+    if (options.timings || config.show_timings) && !options.deterministic {
+        let footer = match timings.last().map_or(0, |time| time.as_millis()) {
+            0 => String::from("Collected in <1ms"),
+            millis => format!("Collected in {}ms", millis),
+        };
+        if options.format == OutputFormat::Text && color::color_enabled() {
+            push_footer(&mut output, &format!("{}{}{}", color::DIM, footer, color::RESET), options.format);
+        } else {
+            push_footer(&mut output, &footer, options.format);
+        }
+    }
+    if options.verbose > 1 {
+        push_footer(&mut output, &dependency_versions(), options.format);
+    }
+    if let Some(notice) = update_check.and_then(|check| check.join().ok()).flatten() {
+        push_footer(&mut output, &notice, options.format);
+    }
+    if let Some(path) = &options.render_image {
+        if let Err(err) = std::fs::write(path, svg::render_svg(&output)) {
+            eprintln!("ashwin-fetch: can't write {}: {}", path, err);
+            return ExitCode::from(1);
+        }
+        collector::finish_background();
+        return ExitCode::from(0);
+    }
+    // Every renderer ends lines with `\n`, on Windows too, so redirected
+    // output parses the same everywhere. `--crlf` opts into `\r\n`.
+    let output = if options.crlf { output.replace('\n', "\r\n") } else { output };
+    let printed = print_all_info(&output);
+    collector::finish_background();
+    match printed {
+        Ok(()) => return ExitCode::from(0),
+        // The reader went away (e.g. `ashwin-fetch | head`); that's not a failure.
+        Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return ExitCode::from(0),
+        Err(err) => {
+            eprintln!("ashwin-fetch: {}", err);
+            return ExitCode::from(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedSampler(Vec<Vec<f32>>);
+
+    impl CpuSampler for FixedSampler {
+        fn sample(&mut self) -> Vec<f32> {
+            return self.0.remove(0);
+        }
+    }
+
+    const MB: u64 = 1024 * 1024;
+    const GB: u64 = 1024 * MB;
+
+    #[test]
+    fn frequencies_pick_a_unit_by_magnitude() {
+        assert_eq!(format_frequency(5000.0, FrequencyUnit::Auto, 2), "5.00 GHz");
+        assert_eq!(format_frequency(3228.0, FrequencyUnit::Auto, 1), "3.2 GHz");
+        assert_eq!(format_frequency(800.0, FrequencyUnit::Auto, 2), "800 MHz");
+        assert_eq!(format_frequency(3228.0, FrequencyUnit::Mhz, 2), "3228 MHz");
+        assert_eq!(format_frequency(800.0, FrequencyUnit::Ghz, 2), "0.80 GHz");
+    }
+
+    #[test]
+    fn counts_get_a_metric_suffix() {
+        assert_eq!(format_count(512), "512");
+        assert_eq!(format_count(1_234_567), "1.2M");
+        assert_eq!(format_count(6_500_000), "6.5M");
+        assert_eq!(format_count(999_960), "1M");
+        assert_eq!(format_count(2_000), "2K");
+    }
+
+    #[test]
+    fn format_bytes_rounds_half_up() {
+        assert_eq!(format_bytes(15 * GB + 921 * MB), "15.9 GB");
+        assert_eq!(format_bytes(15 * GB + 947 * MB), "15.9 GB");
+        assert_eq!(format_bytes(15 * GB + 973 * MB), "16 GB");
+        assert_eq!(format_bytes(GB + GB / 20 + 1), "1.1 GB");
+        assert_eq!(format_bytes(GB + GB / 20 - 1), "1 GB");
+    }
+
+    #[test]
+    fn format_bytes_carries_into_the_next_unit() {
+        assert_eq!(format_bytes(MB * 1023 + MB / 2), "1 GB");
+        assert_eq!(format_bytes(MB * 1023 + MB / 2 - 1), "1023 MB");
+        assert_eq!(format_bytes(1024 * GB - 10 * MB), "1 TB");
+        assert_eq!(format_bytes(512 * MB), "512 MB");
+    }
+
+    #[test]
+    fn bytes_to_mb_rounds_half_up() {
+        assert_eq!(bytes_to_mb(MB as usize / 2 - 1), 0);
+        assert_eq!(bytes_to_mb(MB as usize / 2), 1);
+        assert_eq!(bytes_to_mb(15 * MB as usize + 900 * 1024), 16);
+    }
+
+    #[test]
+    fn cpu_usage_is_averaged_per_cpu() {
+        let mut sampler = FixedSampler(vec![vec![90.0, 0.0], vec![10.0, 20.0], vec![20.0, 40.0]]);
+        assert_eq!(average_cpu_usage(&mut sampler, 3), [40.0, 20.0]);
+    }
+
+    #[test]
+    fn cpu_usage_takes_at_least_one_sample() {
+        let mut sampler = FixedSampler(vec![vec![12.5]]);
+        assert_eq!(average_cpu_usage(&mut sampler, 0), [12.5]);
+    }
+
+    #[test]
+    fn cpu_sampling_stays_under_a_second() {
+        assert!(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL * cpu_sample_count(100) as u32 <= MAX_CPU_SAMPLING);
+    }
+
+    fn reading(brand: &str, frequency_mhz: u64) -> CpuReading<'_> {
+        return CpuReading { brand, frequency_mhz };
+    }
+
+    #[test]
+    fn cpu_info_groups_by_brand() {
+        let readings = [reading("P-core", 4800), reading("E-core", 3600), reading("P-core", 5200), reading("E-core", 3400)];
+        let usage = [10.0, 50.0, 30.0, 70.0];
+        let cpu_info = aggregate_cpu_info(&readings, Some(&usage), |_| (None, None));
+        assert_eq!(cpu_info.len(), 2);
+        assert_eq!(cpu_info["P-core"].num_cores, 2);
+        assert_eq!(cpu_info["P-core"].avg_usage, Some(20.0));
+        assert_eq!(cpu_info["P-core"].max_frequency_mhz, 5200.0);
+        assert_eq!(cpu_info["E-core"].avg_usage, Some(60.0));
+        assert_eq!(cpu_info["E-core"].max_frequency_mhz, 3600.0);
+    }
+
+    #[test]
+    fn cpu_info_without_usage_samples() {
+        let readings = [reading("CPU", 2000), reading("CPU", 2000)];
+        let cpu_info = aggregate_cpu_info(&readings, None, |_| (None, None));
+        assert_eq!(cpu_info["CPU"].avg_usage, None);
+    }
+
+    #[test]
+    fn cpu_info_keeps_the_highest_rated_clocks() {
+        let readings = [reading("Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz", 800), reading("Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz", 900)];
+        let cpu_info = aggregate_cpu_info(&readings, None, |index| (None, Some(4500.0 + index as f64 * 100.0)));
+        let cpu = &cpu_info["Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz"];
+        // No cpufreq base clock, so it comes from the brand string.
+        assert_eq!(cpu.base_frequency_mhz, Some(3200.0));
+        assert_eq!(cpu.boost_frequency_mhz, Some(4600.0));
+    }
+
+    #[test]
+    fn uptime_formatting_boundaries() {
+        assert_eq!(convert_unix_to_human_string(0), "0m");
+        assert_eq!(convert_unix_to_human_string(59), "0m");
+        assert_eq!(convert_unix_to_human_string(60), "1m");
+        assert_eq!(convert_unix_to_human_string(3599), "59m");
+        assert_eq!(convert_unix_to_human_string(3600), "1h 0m");
+        assert_eq!(convert_unix_to_human_string(86399), "23h 59m");
+        assert_eq!(convert_unix_to_human_string(86400), "1d 0h 0m");
+        assert_eq!(convert_unix_to_human_string(86400 + 3660), "1d 1h 1m");
+    }
+
+    #[test]
+    fn uptime_spelled_out_in_the_locale() {
+        let german = uptime_words("de_DE.UTF-8").unwrap();
+        assert_eq!(convert_unix_to_localized_string(86400 + 3660, german), "1 Tag, 1 Stunde, 1 Minute");
+        assert_eq!(convert_unix_to_localized_string(3 * 86400 + 7500, german), "3 Tage, 2 Stunden, 5 Minuten");
+        assert_eq!(convert_unix_to_localized_string(120, uptime_words("fr").unwrap()), "2 minutes");
+        assert!(uptime_words("xx").is_none());
+    }
+
+    #[test]
+    fn gpu_names_lose_their_noise() {
+        let name = "NVIDIA GeForce(R) RTX 4070 Laptop GPU/PCIe/SSE2";
+        assert_eq!(normalize_gpu_name(name, GpuNameStyle::Raw), name);
+        assert_eq!(normalize_gpu_name(name, GpuNameStyle::Light), "NVIDIA GeForce RTX 4070 Laptop GPU");
+        assert_eq!(normalize_gpu_name(name, GpuNameStyle::Short), "GeForce RTX 4070 Laptop");
+        assert_eq!(normalize_gpu_name("Intel(R) UHD Graphics 630", GpuNameStyle::Short), "UHD Graphics 630");
+        assert_eq!(normalize_gpu_name("AMD Radeon RX 7900 XTX (RADV NAVI31)", GpuNameStyle::Short), "Radeon RX 7900 XTX (RADV NAVI31)");
+        assert_eq!(normalize_gpu_name("Apple M1 Pro", GpuNameStyle::Short), "Apple M1 Pro");
+    }
+
+    #[test]
+    fn session_kind_from_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            return move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string());
+        };
+        assert_eq!(session_kind(env(&[("SSH_CONNECTION", "10.0.0.2 52144 10.0.0.1 22"), ("DISPLAY", ":0")])).as_deref(), Some("SSH (remote)"));
+        assert_eq!(session_kind(env(&[("SESSIONNAME", "RDP-Tcp#3")])).as_deref(), Some("RDP (remote)"));
+        assert_eq!(session_kind(env(&[("XDG_SESSION_TYPE", "wayland")])).as_deref(), Some("desktop (wayland)"));
+        assert_eq!(session_kind(env(&[("XDG_SESSION_TYPE", "tty")])).as_deref(), Some("console"));
+        assert_eq!(session_kind(env(&[("SSH_TTY", "")])), None);
+    }
+
+    #[test]
+    fn boot_time_is_uptime_before_now() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-06-13T11:30:00+02:00").unwrap();
+        assert_eq!(boot_time_string(now, 12 * 86400 + 3 * 3600 + 16 * 60), "2024-06-01 08:14");
+    }
+
+    #[test]
+    fn temperature_sparkline_spans_the_samples() {
+        let bars = UNICODE_GLYPHS.spark;
+        assert_eq!(sparkline(&[40.0, 45.0, 50.0, 55.0, 61.0], 5.0, &bars), "▁▃▄▆█");
+        // Within the minimum span, noise stays low.
+        assert_eq!(sparkline(&[52.0, 53.0, 52.0], 5.0, &bars), "▁▂▁");
+        let mut history = TemperatureHistory::new(3);
+        for celsius in [50.0, 51.0, 52.0, 53.0] {
+            history.record(Some(celsius), None);
+        }
+        assert_eq!(history.cpu, [51.0, 52.0, 53.0]);
+        assert!(history.gpu.is_empty());
+    }
+
+    #[test]
+    fn labels_and_values_are_colored_apart() {
+        let line = format!("Memory: 15 GB / {}32 GB{}", color::RED, color::RESET);
+        let colored = color_field_line(&line, 7, Some("<l>"), Some("<v>"));
+        let expected = format!("<l>Memory:{}<v> 15 GB / {}32 GB{}<v>{}", color::RESET, color::RED, color::RESET, color::RESET);
+        assert_eq!(colored, expected);
+        assert_eq!(color_field_line("OS: Arch", 3, Some("<l>"), None), format!("<l>OS:{} Arch", color::RESET));
+    }
+
+    /// Nothing detected but the basics, for render tests to fill in.
+    fn output_info() -> OutputInfo<'static> {
+        return OutputInfo {
+            timestamp: None,
+            username: String::from("user"),
+            hostname: String::from("host"),
+            os: String::from("Arch Linux"),
+            model: None,
+            serial_number: String::new(),
+            chassis: None,
+            firmware: None,
+            kernel: None,
+            kernel_build_date: None,
+            newer_kernel: None,
+            kernel_cmdline: None,
+            updated: None,
+            pending_updates: None,
+            failed_units: None,
+            thread_counts: None,
+            fd_counts: None,
+            entropy_bits: None,
+            parent_process: None,
+            session: None,
+            users: None,
+            top_processes: None,
+            uptime: Uptime(3600),
+            cpu: BTreeMap::new(),
+            cpu_breakdown: None,
+            core_counts: None,
+            hybrid_cores: None,
+            cpu_limit: None,
+            cpu_affinity: None,
+            hypervisor: None,
+            cpu_temperature: None,
+            cpu_caches: vec![],
+            cpu_topology: None,
+            virtualization: None,
+            governor: None,
+            gpu: vec![],
+            gpu_unavailable: None,
+            raw_gpu_adapters: None,
+            gpu_temperature: None,
+            displays: vec![],
+            audio: None,
+            printer: None,
+            ipv4: None,
+            ipv6: None,
+            gateway: None,
+            wifi: None,
+            memory_used_mb: 0,
+            memory_total_mb: 0,
+            swap_used_mb: 0,
+            swap_total_mb: 0,
+            memory_cgroup_limited: false,
+            memory_pressure: None,
+            disks: vec![],
+            root_inodes: None,
+            encryption: None,
+            trim: None,
+            toolchains: vec![],
+            repo: None,
+            battery: None,
+            power: None,
+            extra_lines: vec![],
+            errors: None,
+        };
+    }
+
+    #[test]
+    fn colored_values_are_cut_by_their_visible_width() {
+        let output_info = OutputInfo { memory_used_mb: 15872, memory_total_mb: 16384, ..output_info() };
+        let logo = Logo::default();
+        let opts = RenderOptions { width: Some(logo.width() + 20), color: true, fields: vec![Field::Memory], logo, ..RenderOptions::default() };
+        let rendered = render(&output_info, &opts);
+        let line = rendered.lines().find(|line| line.contains("Memory:")).unwrap();
+        let info = &line[line.find("Memory:").unwrap()..];
+        assert_eq!(color::strip_ansi(info), "Memory:    15872/16…");
+        assert!(info.ends_with(&format!("{}…", color::RESET)));
+    }
+
+    #[test]
+    fn deterministic_runs_render_the_same() {
+        let run = |uptime: usize, usage: f64, mhz: f64, error: &str| {
+            let cpu = CpuInfo {
+                num_cores: 8,
+                avg_usage: Some(usage),
+                max_frequency_mhz: mhz,
+                base_frequency_mhz: None,
+                boost_frequency_mhz: None,
+                codename: None,
+            };
+            let mut output_info = OutputInfo {
+                uptime: Uptime(uptime),
+                cpu: BTreeMap::from([("Ryzen 7", cpu)]),
+                memory_used_mb: 4096 + uptime,
+                errors: Some(BTreeMap::from([("gpu-temp", String::from(error)), ("battery", String::from("failed"))])),
+                ..output_info()
+            };
+            freeze_volatile_fields(&mut output_info);
+            return (render(&output_info, &RenderOptions::default()), render_json(&output_info, true));
+        };
+        let first = run(3600, 12.5, 4650.0, "timed out");
+        assert_eq!(first, run(7260, 87.0, 3100.0, "still running"));
+        assert!(!first.0.contains("MHz") && !first.0.contains("GHz"));
+        assert!(first.1.contains(r#""errors":{"battery":"failed"}"#));
+    }
+
+    #[test]
+    fn header_underline_matches_the_header_width() {
+        let opts = RenderOptions { header_separator: Some(String::from("─")), ..RenderOptions::default() };
+        assert_eq!(header_underline(4, &opts), "────");
+        assert_eq!(header_underline(4, &RenderOptions { ascii_only: true, ..opts }), "----");
+        let wide = RenderOptions { header_separator: Some(String::from("＝")), ..RenderOptions::default() };
+        assert_eq!(header_underline(5, &wide), "＝＝");
+        assert_eq!(header_underline(3, &RenderOptions::default()), "---");
+    }
+
+    #[test]
+    fn fields_fill_columns_top_to_bottom() {
+        let lines: Vec<String> = ["OS: Arch", "CPU: Ryzen 7", "Memory: 16 GB", "IP: 10.0.0.2", "Disk: /"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            arrange_columns(lines.clone(), 2, None),
+            ["OS: Arch        IP: 10.0.0.2", "CPU: Ryzen 7    Disk: /", "Memory: 16 GB"]
+        );
+        // Two columns need 28; narrower terminals keep a single column.
+        assert_eq!(arrange_columns(lines.clone(), 2, Some(27)), lines);
+    }
+
+    #[test]
+    fn cache_sizes_use_the_largest_whole_unit() {
+        let caches = [(1, 512 * 1024), (2, 8 * 1024 * 1024), (3, 107520 * 1024)];
+        assert_eq!(format_cpu_caches(&caches), "L1 512K, L2 8M, L3 105M");
+        assert_eq!(format_cpu_caches(&[(2, 1536 * 1024)]), "L2 1536K");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_sockets_are_replaced_but_live_ones_are_not() {
+        let path = std::env::temp_dir().join(format!("ashwin-fetch-serve-{}.sock", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        // A server that's gone leaves its socket file behind.
+        drop(std::os::unix::net::UnixListener::bind(path).unwrap());
+        let live = bind_socket(path).unwrap();
+        assert_eq!(bind_socket(path).unwrap_err().kind(), std::io::ErrorKind::AddrInUse);
+        drop(live);
+        std::fs::remove_file(path).unwrap();
+        std::fs::write(path, "not a socket").unwrap();
+        assert!(bind_socket(path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn serve_clients_get_lines_until_they_hang_up() {
+        use std::io::BufRead;
+
+        let (server, client) = std::os::unix::net::UnixStream::pair().unwrap();
+        let lines = serve_client(server, Some(std::sync::Arc::from("{\"tick\":1}\n"))).unwrap();
+        let mut reader = std::io::BufReader::new(client);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"tick\":1}\n");
+        lines.send(std::sync::Arc::from("{\"tick\":2}\n")).unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"tick\":2}\n");
+        drop(reader);
+        let started = Instant::now();
+        while lines.send(std::sync::Arc::from("{\"tick\":3}\n")).is_ok() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(5), "writer outlived its client");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn watch_changes_rewrites_only_changed_lines() {
+        let previous = "OS: Arch\nCPU: 12.00%\nMemory: 4 GB\n";
+        let current = "OS: Arch\nCPU: 3.50%\nMemory: 4 GB\n";
+        assert_eq!(changed_lines(previous, current, Some(24)).as_deref(), Some("\x1b[2;1HCPU: 3.50%\x1b[K\x1b[4;1H"));
+        assert_eq!(changed_lines(previous, current, None).as_deref(), Some("\x1b[2;1HCPU: 3.50%\x1b[K\x1b[4;1H"));
+        assert_eq!(changed_lines(previous, "OS: Arch\n", Some(24)), None);
+        // Taller than the terminal: it scrolled, so only a redraw lines up.
+        assert_eq!(changed_lines(previous, current, Some(3)), None);
+    }
+
+    #[test]
+    fn default_printer_from_lpstat() {
+        assert_eq!(parse_default_printer("system default destination: HP_LaserJet\n").as_deref(), Some("HP_LaserJet"));
+        assert_eq!(parse_default_printer("no system default destination\n"), None);
+    }
+
+    #[test]
+    fn memory_usage_formatting() {
+        let mut opts = RenderOptions::default();
+        assert_eq!(format_usage(9948, 16384, None, &opts), "9948/16384 MB used");
+        opts.memory_display = MemoryDisplay::Free;
+        assert_eq!(format_usage(9948, 16384, None, &opts), "6.3 GB free of 16 GB");
+        opts.memory_display = MemoryDisplay::Both;
+        assert_eq!(format_usage(512, 1024, None, &opts), "512 MB used, 512 MB free of 1 GB");
+        // Used can exceed total briefly while the figures are read apart.
+        assert_eq!(format_usage(2048, 1024, None, &opts), "2 GB used, 0 MB free of 1 GB");
+    }
+
+    #[test]
+    fn combined_memory_swap_shows_only_the_picked_halves() {
+        let output_info = OutputInfo { memory_used_mb: 15 * 1024, memory_total_mb: 32 * 1024, swap_total_mb: 0, ..output_info() };
+        let opts = |fields: Vec<Field>| RenderOptions { memory_swap_combined: true, show_swap: true, fields, ..RenderOptions::default() };
+        assert!(build_fields(&output_info, &opts(vec![Field::Swap])).0.is_empty());
+        let with_swap = OutputInfo { swap_used_mb: 1024, swap_total_mb: 8 * 1024, ..output_info };
+        let fields = build_fields(&with_swap, &opts(vec![Field::Swap])).0;
+        assert_eq!(fields, [(String::from("Swap"), String::from("1/8 GB"))]);
+        let fields = build_fields(&with_swap, &opts(vec![Field::Memory, Field::Swap])).0;
+        assert_eq!(fields, [(String::from("Mem/Swap"), String::from("15/32 GB | 1/8 GB"))]);
+        let free = RenderOptions { memory_display: MemoryDisplay::Free, ..opts(vec![Field::Memory]) };
+        assert_eq!(build_fields(&with_swap, &free).0, [(String::from("Memory"), String::from("17 GB free of 32 GB"))]);
+    }
+
+    #[test]
+    fn compact_usage_writes_a_shared_unit_once() {
+        assert_eq!(format_compact_usage(15 * 1024, 32 * 1024, None, None), "15/32 GB");
+        assert_eq!(format_compact_usage(512, 32 * 1024, None, None), "512 MB/32 GB");
+        assert_eq!(format_compact_usage(1024, 8 * 1024, Some(SizeUnit::Gib), None), "1/8 GiB");
+    }
+
+    #[test]
+    fn memory_usage_in_a_configured_unit() {
+        let mut opts = RenderOptions::default();
+        assert_eq!(format_usage(9948, 16384, Some(SizeUnit::Gib), &opts), "9.7/16 GiB used");
+        opts.memory_display = MemoryDisplay::Free;
+        // The same basis as the automatic units, only the label differs.
+        assert_eq!(format_usage(9948, 16384, Some(SizeUnit::Gb), &opts), "6.3 GB free of 16 GB");
+        assert_eq!(format_usage(9948, 16384, None, &opts), "6.3 GB free of 16 GB");
+        assert_eq!(format_bytes_as(512 * MB, Some(SizeUnit::Mib), None), "512 MiB");
+        assert_eq!(format_bytes_as(512 * MB, None, None), "512 MB");
+    }
+
+    #[test]
+    fn memory_usage_digit_grouping() {
+        let opts = RenderOptions { digit_grouping: Some(Locale::en), ..RenderOptions::default() };
+        assert_eq!(format_usage(9948, 16384, None, &opts), "9,948/16,384 MB used");
+        assert_eq!(format_usage(9948, 16384, Some(SizeUnit::Mib), &opts), "9,948/16,384 MiB used");
+        assert_eq!(format_bytes_as(4500 * 1024 * MB, Some(SizeUnit::Gb), Some(Locale::de)), "4.500 GB");
+        assert_eq!(format_bytes_as(4500 * 1024 * MB + 512 * MB, Some(SizeUnit::Gb), Some(Locale::de)), "4.500,5 GB");
+    }
+
+    fn gpu(device_index: usize, gpu_name: &str, pci_bus_id: Option<&str>) -> GpuInfo {
+        return GpuInfo {
+            device_index,
+            gpu_name: gpu_name.to_string(),
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            vram_mb: None,
+            vram_used_mb: None,
+            power_watts: None,
+            api_version: None,
+            pci_bus_id: pci_bus_id.map(str::to_string),
+        };
+    }
+
+    #[test]
+    fn gpu_indices_follow_the_pci_bus() {
+        let mut gpus = vec![
+            gpu(0, "B", Some("0000:41:00.0")),
+            gpu(1, "A", None),
+            gpu(2, "C", Some("0000:01:00.0")),
+        ];
+        assign_stable_indices(&mut gpus);
+        let order: Vec<(usize, &str)> = gpus.iter().map(|gpu| (gpu.device_index, gpu.gpu_name.as_str())).collect();
+        assert_eq!(order, [(0, "C"), (1, "B"), (2, "A")]);
+    }
+
+    #[test]
+    fn gpus_listed_by_several_backends_are_the_same_card() {
+        let adapter = |name: &str, device, backend| wgpu::AdapterInfo {
+            name: name.to_string(),
+            vendor: 0x10de,
+            device,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend,
+        };
+        let vulkan = adapter("NVIDIA GeForce RTX 4090", 0x2684, wgpu::Backend::Vulkan);
+        let gl = adapter("NVIDIA GeForce RTX 4090/PCIe/SSE2", 0x2684, wgpu::Backend::Gl);
+        assert_eq!(gpu_identity(&vulkan, None, 0), gpu_identity(&gl, None, 0));
+        assert_ne!(gpu_identity(&vulkan, None, 0), gpu_identity(&vulkan, None, 1));
+        assert_eq!(gpu_identity(&vulkan, Some("0000:01:00.0"), 1), "0000:01:00.0");
+        assert_eq!(gpu_identity(&adapter("llvmpipe", 0, wgpu::Backend::Vulkan), None, 0), "llvmpipe");
+    }
+
+    #[test]
+    fn max_gpus_counts_the_hidden_gpus() {
+        let gpus = [gpu(0, "A100", None), gpu(1, "A100", None), gpu(2, "T4", None), gpu(3, "L4", None)];
+        let (shown, hidden) = cap_gpu_groups(group_gpus(&gpus), Some(1));
+        assert_eq!(shown.iter().map(|(gpu, count)| (gpu.gpu_name.as_str(), *count)).collect::<Vec<_>>(), [("A100", 2)]);
+        assert_eq!(hidden, 2);
+        assert_eq!(cap_gpu_groups(group_gpus(&gpus), Some(3)).1, 0);
+    }
+
+    #[test]
+    fn combined_cpu_line_lists_the_clusters() {
+        let readings = [reading("Cortex-A55", 1800), reading("Cortex-A78", 2400), reading("Cortex-A55", 1800), reading("Cortex-A55", 1800)];
+        let usage = [10.0, 50.0, 10.0, 10.0];
+        let cpus = aggregate_cpu_info(&readings, Some(&usage), |_| (None, None));
+        assert_eq!(format_combined_cpus(&cpus), "Cortex-A55 x3 + Cortex-A78 x1, 20.00% avg");
+        let cpus = aggregate_cpu_info(&readings, None, |_| (None, None));
+        assert_eq!(format_combined_cpus(&cpus), "Cortex-A55 x3 + Cortex-A78 x1, --% avg");
+    }
+
+    #[test]
+    fn disks_are_tagged_by_storage_type() {
+        let disk = |rotational, nvme| DiskInfo {
+            mount_point: String::from("/"),
+            file_system: String::from("ext4"),
+            used_bytes: 0,
+            total_bytes: 1,
+            rotational,
+            nvme,
+        };
+        assert_eq!(disk(Some(false), true).storage_type(), Some("NVMe"));
+        assert_eq!(disk(Some(false), false).storage_type(), Some("SSD"));
+        assert_eq!(disk(Some(true), false).storage_type(), Some("HDD"));
+        assert_eq!(disk(None, false).storage_type(), None);
+    }
+
+    #[test]
+    fn dumped_config_loads_back() {
+        let options = cli::Options { no_header: true, ..Default::default() };
+        let dumped = config::dump_config(&effective_config(&options, config::Config::default()));
+        let config: config::Config = toml::from_str(&dumped).unwrap();
+        assert_eq!(config.header_position, Some(HeaderPosition::None));
+        assert_eq!(config.gap, Some(logo::GAP));
+        assert!(config.extra.is_empty());
+    }
+
+    #[test]
+    fn logo_mask_recolors_single_characters() {
+        let mask_colors = [String::from("<1>"), String::from("<2>")];
+        let row_color = String::from("<row>");
+        assert_eq!(
+            logo_row("abcd", Some(&row_color), Some(&[0, 1, 1, 2]), &mask_colors),
+            format!("<row>a<1>bc<2>d{}", color::RESET)
+        );
+        // Numbers without a color keep the row color.
+        assert_eq!(logo_row("ab", None, Some(&[9, 0]), &mask_colors), format!("{0}ab{0}", color::RESET));
+        assert_eq!(logo_row("ab", Some(&row_color), None, &mask_colors), format!("<row>ab{}", color::RESET));
+    }
+
+    #[test]
+    fn env_output_is_shell_safe() {
+        assert_eq!(env_var_name("CPU Temp"), "FETCH_CPU_TEMP");
+        assert_eq!(env_var_name("GPU 0"), "FETCH_GPU_0");
+        assert_eq!(env_var_name("$(rm -rf)"), "FETCH_RM_RF");
+        assert_eq!(shell_quote("Ubuntu 24.04"), "'Ubuntu 24.04'");
+        assert_eq!(shell_quote("it's $HOME `x`"), "'it'\\''s $HOME `x`'");
+    }
+
+    #[test]
+    fn hypervisors_are_named_by_their_cpuid_signature() {
+        assert_eq!(cpuid_hypervisor_name(b"KVMKVMKVM\0\0\0"), "KVM");
+        assert_eq!(cpuid_hypervisor_name(b" lrpepyh  vr"), "Parallels");
+        assert_eq!(cpuid_hypervisor_name(b"NewVisorNewV"), "VM");
+    }
+
+    #[test]
+    fn affinity_is_only_shown_for_a_single_brand() {
+        let cpu = |num_cores: usize| CpuInfo {
+            num_cores,
+            avg_usage: None,
+            max_frequency_mhz: 0.0,
+            base_frequency_mhz: None,
+            boost_frequency_mhz: None,
+            codename: None,
+        };
+        let single = OutputInfo { cpu: BTreeMap::from([("Ryzen 9", cpu(16))]), cpu_affinity: Some(12), ..output_info() };
+        assert_eq!(format_core_count(&single, &single.cpu["Ryzen 9"]), "16 cores (12 available)");
+        let unrestricted = OutputInfo { cpu_affinity: Some(16), ..single };
+        assert_eq!(format_core_count(&unrestricted, &unrestricted.cpu["Ryzen 9"]), "16 cores");
+        // The mask covers both clusters, so it says nothing about either one.
+        let mixed = OutputInfo { cpu: BTreeMap::from([("Cortex-A76", cpu(4)), ("Cortex-A55", cpu(4))]), cpu_affinity: Some(6), ..output_info() };
+        assert_eq!(format_core_count(&mixed, &mixed.cpu["Cortex-A76"]), "4 cores");
+    }
+
+    #[test]
+    fn hybrid_cores_are_counted_by_type() {
+        let hybrid = HybridCores { performance: 16, efficiency: 8, threads: 24 };
+        assert_eq!(format_cores(24, Some(&hybrid)), "16P + 8E cores");
+        // Hyper-Threaded P-cores: an i9-13900K has 32 threads on 24 cores.
+        let hybrid = HybridCores { performance: 8, efficiency: 16, threads: 32 };
+        assert_eq!(format_cores(32, Some(&hybrid)), "8P + 16E cores");
+        // Offline or hidden CPUs make the types disagree with the total.
+        assert_eq!(format_cores(20, Some(&hybrid)), "20 cores");
+        assert_eq!(format_cores(8, None), "8 cores");
+    }
+
+    #[test]
+    fn hundreds_of_cores_stay_on_short_lines() {
+        let readings: Vec<CpuReading> = (0..256).map(|_| reading("AMD EPYC 9754 128-Core Processor", 2250)).collect();
+        let usage: Vec<f32> = (0..256).map(|index| (index % 4) as f32 * 10.0).collect();
+        let cpus = aggregate_cpu_info(&readings, Some(&usage), |_| (None, None));
+        assert_eq!(cpus.len(), 1);
+        assert_eq!(cpus["AMD EPYC 9754 128-Core Processor"].num_cores, 256);
+        assert_eq!(cpus["AMD EPYC 9754 128-Core Processor"].avg_usage, Some(15.0));
+
+        let brands: Vec<String> = (0..128).map(|index| format!("Core {}", index)).collect();
+        let readings: Vec<CpuReading> = brands.iter().flat_map(|brand| [reading(brand, 2000), reading(brand, 2000)]).collect();
+        let cpus = aggregate_cpu_info(&readings, None, |_| (None, None));
+        let line = format_combined_cpus(&cpus);
+        assert_eq!(line, "Core 0 x2 + Core 1 x2 + Core 10 x2 + Core 100 x2 + 124 more x248, --% avg");
+    }
+
+    #[test]
+    fn qr_codes_are_square_with_a_quiet_zone() {
+        let ascii = render_qr(&output_info(), true).unwrap();
+        let rows: Vec<&str> = ascii.lines().collect();
+        // Two columns per module, and a light margin all around.
+        assert!(rows.iter().all(|row| row.len() == rows.len() * 2));
+        assert_eq!(rows[0], "##".repeat(rows.len()));
+        assert_eq!(rows[rows.len() - 1], rows[0]);
+        // Half blocks: two rows of modules per line.
+        let blocks = render_qr(&output_info(), false).unwrap();
+        let width = blocks.lines().next().unwrap().chars().count();
+        assert_eq!(blocks.lines().count(), width.div_ceil(2));
+    }
+
+    #[test]
+    fn server_preset_turns_on_its_opt_in_fields() {
+        let args = ["--fields-preset", "server"].map(String::from);
+        let mut options = cli::parse_args(args).unwrap();
+        for field in options.fields_preset.clone() {
+            options.opt_in(field);
+        }
+        assert!(options.show_failed_units && options.show_swap && options.show_ip && options.show_network);
+        assert!(!options.show_wifi);
+    }
 }
@@ -0,0 +1,54 @@
+//! Live NVIDIA stats from `nvidia-smi`, which ships with the driver on both
+//! Linux and Windows.
+
+use std::process::Command;
+
+pub const VENDOR_ID: u32 = 0x10de;
+
+/// One `nvidia-smi` row: the PCI device id followed by the requested fields.
+pub struct NvidiaGpu {
+    pub device_id: u32,
+    pub values: Vec<String>,
+    claimed: bool,
+}
+
+/// Runs a single `nvidia-smi` query for `fields` on every GPU. Empty when the
+/// tool is missing or fails.
+pub fn query(fields: &[&str]) -> Vec<NvidiaGpu> {
+    let query = format!("pci.device_id,{}", fields.join(","));
+    let Ok(output) = Command::new("nvidia-smi")
+        .arg(format!("--query-gpu={}", query))
+        .arg("--format=csv,noheader,nounits")
+        .output()
+    else {
+        return vec![];
+    };
+    if !output.status.success() {
+        return vec![];
+    }
+    return String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split(',').map(|column| column.trim().to_string());
+            // `0x268410DE`: device id in the high half, vendor in the low half.
+            let pci_id = u32::from_str_radix(columns.next()?.trim_start_matches("0x"), 16).ok()?;
+            return Some(NvidiaGpu {
+                device_id: pci_id >> 16,
+                values: columns.collect(),
+                claimed: false,
+            });
+        })
+        .collect();
+}
+
+/// Hands out rows in order, so identical cards each get their own.
+pub fn claim(gpus: &mut [NvidiaGpu], device_id: u32) -> Option<&NvidiaGpu> {
+    let gpu = gpus.iter_mut().find(|gpu| !gpu.claimed && gpu.device_id == device_id)?;
+    gpu.claimed = true;
+    return Some(gpu);
+}
+
+/// Parses a numeric field; `nvidia-smi` reports `[N/A]` for unsupported ones.
+pub fn parse_value(value: Option<&String>) -> Option<f64> {
+    return value?.parse().ok();
+}
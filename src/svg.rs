@@ -0,0 +1,199 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::color::Rgb;
+use crate::color::strip_ansi;
+
+const FONT_SIZE: f64 = 14.0;
+/// Advance of one column in a typical monospace font at `FONT_SIZE`.
+const CELL_WIDTH: f64 = FONT_SIZE * 0.6;
+const LINE_HEIGHT: f64 = FONT_SIZE * 1.3;
+const MARGIN: f64 = 16.0;
+const BACKGROUND: Rgb = Rgb { r: 0x1e, g: 0x1e, b: 0x1e };
+const FOREGROUND: Rgb = Rgb { r: 0xd0, g: 0xd0, b: 0xd0 };
+
+/// The 16 basic colors, as xterm draws them.
+const BASIC: [Rgb; 16] = [
+    Rgb { r: 0x00, g: 0x00, b: 0x00 },
+    Rgb { r: 0xcd, g: 0x00, b: 0x00 },
+    Rgb { r: 0x00, g: 0xcd, b: 0x00 },
+    Rgb { r: 0xcd, g: 0xcd, b: 0x00 },
+    Rgb { r: 0x00, g: 0x00, b: 0xee },
+    Rgb { r: 0xcd, g: 0x00, b: 0xcd },
+    Rgb { r: 0x00, g: 0xcd, b: 0xcd },
+    Rgb { r: 0xe5, g: 0xe5, b: 0xe5 },
+    Rgb { r: 0x7f, g: 0x7f, b: 0x7f },
+    Rgb { r: 0xff, g: 0x00, b: 0x00 },
+    Rgb { r: 0x00, g: 0xff, b: 0x00 },
+    Rgb { r: 0xff, g: 0xff, b: 0x00 },
+    Rgb { r: 0x5c, g: 0x5c, b: 0xff },
+    Rgb { r: 0xff, g: 0x00, b: 0xff },
+    Rgb { r: 0x00, g: 0xff, b: 0xff },
+    Rgb { r: 0xff, g: 0xff, b: 0xff },
+];
+
+/// Text attributes set by SGR escapes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Style {
+    fg: Option<Rgb>,
+    bold: bool,
+    dim: bool,
+}
+
+impl Style {
+    /// Applies the parameters of one `ESC [ ... m` sequence.
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|code| code.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                22 => (self.bold, self.dim) = (false, false),
+                30..=37 => self.fg = Some(BASIC[code as usize - 30]),
+                39 => self.fg = None,
+                90..=97 => self.fg = Some(BASIC[code as usize - 90 + 8]),
+                38 => match codes.next() {
+                    Some(5) => self.fg = codes.next().map(ansi256),
+                    Some(2) => {
+                        let mut channel = || codes.next().unwrap_or(0).min(255) as u8;
+                        self.fg = Some(Rgb { r: channel(), g: channel(), b: channel() });
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// An index in the xterm 256-color palette.
+fn ansi256(index: u16) -> Rgb {
+    let index = index.min(255) as u8;
+    return match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let cube = index - 16;
+            Rgb { r: level(cube / 36), g: level(cube / 6 % 6), b: level(cube % 6) }
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Rgb { r: gray, g: gray, b: gray }
+        }
+    };
+}
+
+fn hex(color: Rgb) -> String {
+    return format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+}
+
+fn escape_xml(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+/// Splits a line into runs of text sharing a style. Escapes other than SGR
+/// (cursor movement, hyperlinks) are dropped.
+fn styled_runs(line: &str, style: &mut Style) -> Vec<(Style, String)> {
+    let mut runs: Vec<(Style, String)> = vec![];
+    let mut rest = line;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('\x1b') else {
+            runs.push((*style, rest.to_string()));
+            break;
+        };
+        if start > 0 {
+            runs.push((*style, rest[..start].to_string()));
+        }
+        rest = &rest[start..];
+        if let Some(csi) = rest.strip_prefix("\x1b[") {
+            let end = csi.find(|c: char| ('@'..='~').contains(&c)).unwrap_or(csi.len());
+            if csi[end..].starts_with('m') {
+                style.apply(&csi[..end]);
+            }
+            rest = csi.get(end + 1..).unwrap_or("");
+        } else {
+            rest = &rest[escape_len(rest)..];
+        }
+    }
+    runs.retain(|(_, text)| !text.is_empty());
+    return runs;
+}
+
+/// Length of the non-CSI escape `text` starts with: an OSC up to BEL or ST,
+/// or a two-character escape.
+fn escape_len(text: &str) -> usize {
+    if text.starts_with("\x1b]") {
+        let bel = text.find('\x07').map(|i| i + 1);
+        let st = text.find("\x1b\\").map(|i| i + 2);
+        return match (bel, st) {
+            (Some(bel), Some(st)) => bel.min(st),
+            (end, None) | (None, end) => end.unwrap_or(text.len()),
+        };
+    }
+    return text.char_indices().nth(2).map_or(text.len(), |(i, _)| i);
+}
+
+/// Draws colored terminal output as an SVG on a dark background, one
+/// `<text>` per line with a `<tspan>` per colored run.
+pub fn render_svg(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let columns = lines.iter().map(|line| strip_ansi(line).width()).max().unwrap_or(0);
+    let width = (MARGIN * 2.0 + columns as f64 * CELL_WIDTH).ceil();
+    let height = (MARGIN * 2.0 + lines.len() as f64 * LINE_HEIGHT).ceil();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height,
+    );
+    svg.push_str(&format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex(BACKGROUND)));
+    svg.push_str(&format!(
+        "<g font-family=\"'DejaVu Sans Mono', Menlo, Consolas, monospace\" font-size=\"{}\" fill=\"{}\" xml:space=\"preserve\">\n",
+        FONT_SIZE,
+        hex(FOREGROUND),
+    ));
+    let mut style = Style::default();
+    for (row, line) in lines.iter().enumerate() {
+        let y = MARGIN + FONT_SIZE + row as f64 * LINE_HEIGHT;
+        svg.push_str(&format!("<text x=\"{}\" y=\"{:.1}\">", MARGIN, y));
+        for (run_style, text) in styled_runs(line, &mut style) {
+            let mut attributes = String::new();
+            if let Some(fg) = run_style.fg {
+                attributes.push_str(&format!(" fill=\"{}\"", hex(fg)));
+            }
+            if run_style.bold {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            if run_style.dim {
+                attributes.push_str(" fill-opacity=\"0.6\"");
+            }
+            svg.push_str(&format!("<tspan{}>{}</tspan>", attributes, escape_xml(&text)));
+        }
+        svg.push_str("</text>\n");
+    }
+    svg.push_str("</g>\n</svg>\n");
+    return svg;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colored_runs_become_tspans() {
+        let svg = render_svg("\x1b[38;2;255;0;128mlogo\x1b[0m  \x1b[96mOS:\x1b[0m a < b\n\x1b[2mdim\x1b[0m");
+        assert!(svg.contains("<tspan fill=\"#ff0080\">logo</tspan><tspan>  </tspan>"));
+        assert!(svg.contains("<tspan fill=\"#00ffff\">OS:</tspan><tspan> a &lt; b</tspan>"));
+        assert!(svg.contains("<tspan fill-opacity=\"0.6\">dim</tspan>"));
+        assert_eq!(ansi256(196), Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(ansi256(244), Rgb { r: 128, g: 128, b: 128 });
+    }
+
+    #[test]
+    fn hyperlinks_keep_only_their_text() {
+        let mut style = Style::default();
+        let runs = styled_runs("\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\ done", &mut style);
+        let text: String = runs.into_iter().map(|(_, text)| text).collect();
+        assert_eq!(text, "link done");
+    }
+}
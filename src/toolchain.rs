@@ -0,0 +1,65 @@
+//! `--show-toolchains`: versions of common developer toolchains, found by
+//! running their binaries.
+
+use std::process::Command;
+use std::process::Stdio;
+
+/// Probed when the config doesn't list its own `toolchains`.
+pub const DEFAULT_TOOLCHAINS: [&str; 5] = ["rustc", "python3", "node", "go", "java"];
+
+/// The version flag and how many version components to show, for binaries
+/// that don't follow `--version` or read better shorter. Others get
+/// `--version` and `major.minor`.
+const PROBES: [(&str, &str, usize); 4] = [
+    ("go", "version", 2),
+    ("java", "-version", 1),
+    ("javac", "-version", 1),
+    ("node", "--version", 1),
+];
+
+/// The first dotted version in `output`, e.g. `1.22` from
+/// `go version go1.22.0 linux/amd64`, cut to `parts` components.
+fn parse_version(output: &str, parts: usize) -> Option<String> {
+    return output.split_whitespace().find_map(|token| {
+        let token = token.trim_start_matches(|c: char| !c.is_ascii_digit());
+        let end = token.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(token.len());
+        let version = token[..end].trim_end_matches('.');
+        if !version.contains('.') {
+            return None;
+        }
+        return Some(version.split('.').take(parts).collect::<Vec<_>>().join("."));
+    });
+}
+
+/// `rustc 1.80`, or `None` when `binary` isn't installed. Some tools
+/// (java, older pythons) print their version on stderr, so both are read.
+pub fn probe(binary: &str) -> Option<String> {
+    let (args, parts) = match PROBES.iter().find(|(name, _, _)| *name == binary) {
+        Some((_, args, parts)) => (*args, *parts),
+        None => ("--version", 2),
+    };
+    let output = Command::new(binary).arg(args).stdin(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    let version = parse_version(&text, parts)?;
+    // `python3` reads as `python 3.12`.
+    let name = binary.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let name = if name.is_empty() { binary } else { name };
+    return Some(format!("{} {}", name, version));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_found_in_common_formats() {
+        assert_eq!(parse_version("rustc 1.80.0 (051478957 2024-07-21)", 2).as_deref(), Some("1.80"));
+        assert_eq!(parse_version("go version go1.22.0 linux/amd64", 2).as_deref(), Some("1.22"));
+        assert_eq!(parse_version("v20.11.0", 1).as_deref(), Some("20"));
+        assert_eq!(parse_version("openjdk version \"21.0.2\" 2024-01-16", 1).as_deref(), Some("21"));
+        assert_eq!(parse_version("no version here", 2), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! `--check-updates`: looks up the newest release on crates.io and mentions
+//! it when it's newer than this build. Nothing is ever installed.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// How long a lookup is reused before crates.io is asked again.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long a failed lookup is remembered, so being offline doesn't slow
+/// every run but the check is back soon after the network is.
+const FAILURE_TTL: Duration = Duration::from_secs(60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+const CRATE_URL: &str = "https://crates.io/api/v1/crates/ashwin-fetch";
+
+pub fn cache_path() -> Option<PathBuf> {
+    return dirs::cache_dir().map(|dir| dir.join("ashwin-fetch").join("latest-version"));
+}
+
+/// `1.2.3` as comparable numbers; pre-release and build suffixes are ignored.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    return version
+        .trim()
+        .split(['-', '+'])
+        .next()?
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect();
+}
+
+/// Newest stable version on crates.io, whose API asks for a descriptive
+/// user agent.
+fn fetch_latest_version() -> Option<String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .user_agent(format!("ashwin-fetch/{} (update check)", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into();
+    let body = agent.get(CRATE_URL).call().ok()?.into_body().read_to_string().ok()?;
+    let response: serde_json::Value = serde_json::from_str(&body).ok()?;
+    return response["crate"]["max_stable_version"].as_str().map(str::to_string);
+}
+
+/// The cached lookup while it's fresh, else a new one. Failed lookups are
+/// cached as an empty file, for `FAILURE_TTL` only.
+fn latest_version() -> Option<String> {
+    let path = cache_path()?;
+    let age = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if let (Some(age), Ok(cached)) = (age, std::fs::read_to_string(&path)) {
+        let cached = cached.trim();
+        if cached.is_empty() && age < FAILURE_TTL {
+            return None;
+        }
+        if !cached.is_empty() && age < CACHE_TTL {
+            return Some(cached.to_string());
+        }
+    }
+    let latest = fetch_latest_version();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(&path, latest.as_deref().unwrap_or(""));
+    return latest;
+}
+
+/// The footer line when a newer release exists. Any failure means `None`.
+pub fn update_notice() -> Option<String> {
+    let latest = latest_version()?;
+    if parse_version(&latest)? <= parse_version(env!("CARGO_PKG_VERSION"))? {
+        return None;
+    }
+    return Some(format!("A newer ashwin-fetch ({}) is available", latest));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_by_their_numbers() {
+        assert_eq!(parse_version("1.2.3"), Some(vec![1, 2, 3]));
+        assert_eq!(parse_version(" 0.10.0\n"), Some(vec![0, 10, 0]));
+        assert_eq!(parse_version("2.0.0-beta.1+build.5"), Some(vec![2, 0, 0]));
+        assert_eq!(parse_version("1.x"), None);
+        assert_eq!(parse_version(""), None);
+        assert!(parse_version("0.10.0") > parse_version("0.9.12"));
+        assert!(parse_version("1.2") < parse_version("1.2.1"));
+    }
+}
@@ -0,0 +1,177 @@
+//! Readers for Windows-only sources (registry, WMI).
+
+use serde::Deserialize;
+use winreg::RegKey;
+use winreg::enums::HKEY_LOCAL_MACHINE;
+use wmi::WMIConnection;
+
+const CURRENT_VERSION_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+
+/// First build number of Windows 11, which still reports itself as
+/// "Windows 10" in `ProductName`.
+const WINDOWS_11_FIRST_BUILD: u32 = 22000;
+
+/// `Windows 11 Pro 23H2 (Build 22631)`, assembled from the registry.
+pub fn get_windows_version() -> Option<String> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(CURRENT_VERSION_KEY).ok()?;
+    let mut product_name: String = key.get_value("ProductName").ok()?;
+    let build: Option<String> = key.get_value("CurrentBuildNumber").ok();
+    let build_number = build.as_deref().and_then(|build| build.parse::<u32>().ok());
+    if build_number.is_some_and(|build| build >= WINDOWS_11_FIRST_BUILD) {
+        product_name = product_name.replacen("Windows 10", "Windows 11", 1);
+    }
+    let mut parts = vec![product_name];
+    if let Ok(display_version) = key.get_value::<String, _>("DisplayVersion") {
+        parts.push(display_version);
+    }
+    if let Some(build) = build {
+        parts.push(format!("(Build {})", build));
+    }
+    return Some(parts.join(" "));
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Win32_SystemEnclosure", rename_all = "PascalCase")]
+struct SystemEnclosure {
+    chassis_types: Option<Vec<u16>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Win32_ComputerSystem", rename_all = "PascalCase")]
+struct ComputerSystem {
+    manufacturer: Option<String>,
+    model: Option<String>,
+}
+
+/// SMBIOS chassis type as reported by WMI.
+pub fn get_dmi_chassis_type() -> Option<u32> {
+    let connection = WMIConnection::new().ok()?;
+    let enclosures: Vec<SystemEnclosure> = connection.query().ok()?;
+    return enclosures
+        .into_iter()
+        .find_map(|enclosure| enclosure.chassis_types?.first().copied())
+        .map(u32::from);
+}
+
+/// Manufacturer and model strings, used to spot well-known hypervisors.
+pub fn get_dmi_system_identity() -> String {
+    let Some(connection) = WMIConnection::new().ok() else {
+        return String::new();
+    };
+    let systems: Vec<ComputerSystem> = connection.query().unwrap_or_default();
+    return systems
+        .into_iter()
+        .flat_map(|system| [system.manufacturer, system.model])
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Win32_Processor", rename_all = "PascalCase")]
+struct Processor {
+    l2_cache_size: Option<u32>,
+    l3_cache_size: Option<u32>,
+}
+
+/// `(level, bytes)` of the L2 and L3 caches, summed over the sockets.
+/// `Win32_Processor` reports both in KB; it has no L1 figure.
+pub fn get_cpu_caches() -> Vec<(u8, u64)> {
+    let Some(connection) = WMIConnection::new().ok() else {
+        return vec![];
+    };
+    let processors: Vec<Processor> = connection.query().unwrap_or_default();
+    let total = |size: fn(&Processor) -> Option<u32>| processors.iter().filter_map(size).map(u64::from).sum::<u64>() * 1024;
+    return [(2, total(|processor| processor.l2_cache_size)), (3, total(|processor| processor.l3_cache_size))]
+        .into_iter()
+        .filter(|(_, bytes)| *bytes > 0)
+        .collect();
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Win32_Battery", rename_all = "PascalCase")]
+struct Battery {
+    estimated_charge_remaining: Option<u16>,
+}
+
+/// `(charge percent, health percent, cycle count)`. WMI's `Win32_Battery`
+/// only has the charge; wear needs the vendor's battery report.
+pub fn get_battery() -> Option<(f64, Option<f64>, Option<u32>)> {
+    let connection = WMIConnection::new().ok()?;
+    let batteries: Vec<Battery> = connection.query().ok()?;
+    let percent = batteries.into_iter().find_map(|battery| battery.estimated_charge_remaining)?;
+    return Some((percent as f64, None, None));
+}
+
+/// `(SSID, signal dBm)` of the connected Wi-Fi network, from
+/// `netsh wlan show interfaces`. netsh gives the signal as a 0-100 quality,
+/// which the WLAN API maps linearly onto -100 to -50 dBm.
+pub fn get_wifi() -> Option<(String, Option<i32>)> {
+    let output = std::process::Command::new("netsh").args(["wlan", "show", "interfaces"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let interfaces = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| {
+        return interfaces.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            return if name.trim() == key { Some(value.trim().to_string()) } else { None };
+        });
+    };
+    let ssid = value("SSID").filter(|ssid| !ssid.is_empty())?;
+    let quality = value("Signal").and_then(|signal| signal.trim_end_matches('%').parse::<i32>().ok());
+    return Some((ssid, quality.map(|quality| quality / 2 - 100)));
+}
+
+/// BitLocker state of the system drive from `manage-bde -status`, which
+/// needs an elevated prompt; `None` otherwise.
+pub fn get_bitlocker_status() -> Option<String> {
+    let drive = std::env::var("SystemDrive").unwrap_or_else(|_| String::from("C:"));
+    let output = std::process::Command::new("manage-bde").args(["-status", &drive]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let status = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| {
+        return status.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            return if name.trim() == key { Some(value.trim().to_string()) } else { None };
+        });
+    };
+    return match value("Conversion Status")?.as_str() {
+        "Fully Decrypted" => Some(String::from("none")),
+        _ if value("Protection Status").is_some_and(|protection| protection.contains("On")) => {
+            Some(String::from("BitLocker (active)"))
+        }
+        _ => Some(String::from("BitLocker (suspended)")),
+    };
+}
+
+/// `(uefi, secure_boot)` from `PEFirmwareType` (1 BIOS, 2 UEFI) and the
+/// `SecureBoot\State` key.
+pub fn get_firmware_mode() -> Option<(bool, Option<bool>)> {
+    let control = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(r"SYSTEM\CurrentControlSet\Control").ok()?;
+    let firmware_type: u32 = control.get_value("PEFirmwareType").ok()?;
+    let secure_boot = control
+        .open_subkey(r"SecureBoot\State")
+        .and_then(|state| state.get_value::<u32, _>("UEFISecureBootEnabled"))
+        .ok()
+        .map(|enabled| enabled == 1);
+    return match firmware_type {
+        1 => Some((false, None)),
+        2 => Some((true, secure_boot)),
+        _ => None,
+    };
+}
+
+#[link(name = "shell32")]
+unsafe extern "system" {
+    fn IsUserAnAdmin() -> i32;
+}
+
+/// Whether we run with an elevated token, i.e. as an administrator with
+/// UAC's consent rather than a filtered one.
+pub fn is_elevated() -> Option<bool> {
+    // SAFETY: takes no arguments and only inspects our own token.
+    return Some(unsafe { IsUserAnAdmin() } != 0);
+}